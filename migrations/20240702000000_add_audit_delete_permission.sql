@@ -0,0 +1,11 @@
+-- Permission required by DELETE /api/history/cleanup
+INSERT INTO permission (code, name, description, category)
+VALUES ('audit:delete', 'Delete Audit Logs', 'Delete old system audit logs', 'audit')
+ON CONFLICT(code) DO NOTHING;
+
+-- Grant it to super admin alongside the other audit permissions
+INSERT INTO user_type_permission (user_type_id, permission_id)
+SELECT ut.id, p.id
+FROM user_type ut, permission p
+WHERE ut.code = 'super_admin' AND p.code = 'audit:delete'
+ON CONFLICT(user_type_id, permission_id) DO NOTHING;
@@ -0,0 +1,21 @@
+-- Refresh tokens are stored hashed, one row per issued token.
+-- Tokens issued from the same login share a family_id; every refresh marks the presented
+-- token as used and issues a new one in the same family. Presenting a used token again
+-- revokes the whole family.
+-- Existing plaintext tokens cannot be converted, so current sessions must log in again.
+DROP TABLE IF EXISTS user_refresh_token;
+
+CREATE TABLE user_refresh_token (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id     INTEGER NOT NULL REFERENCES admin_user (id) ON DELETE CASCADE,
+    token_hash  TEXT NOT NULL,
+    family_id   TEXT NOT NULL,
+    expires_at  DATETIME NOT NULL,
+    used_at     DATETIME,
+    revoked_at  DATETIME,
+    created_at  DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE(token_hash)
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_token_user_id ON user_refresh_token (user_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_token_family_id ON user_refresh_token (family_id);
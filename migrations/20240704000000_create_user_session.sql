@@ -0,0 +1,27 @@
+-- One row per login (device). The session id doubles as the refresh token family id,
+-- so a user can stay signed in on several devices at the same time.
+CREATE TABLE IF NOT EXISTS user_session (
+    id            TEXT PRIMARY KEY,
+    user_id       INTEGER NOT NULL REFERENCES admin_user (id) ON DELETE CASCADE,
+    device_label  TEXT,
+    user_agent    TEXT,
+    ip_address    TEXT,
+    expires_at    DATETIME NOT NULL,
+    revoked_at    DATETIME,
+    created_at    DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_seen_at  DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_session_user_id ON user_session (user_id);
+
+-- Backfill sessions for refresh token families issued before this table existed
+INSERT INTO user_session (id, user_id, expires_at, revoked_at, created_at, last_seen_at)
+SELECT
+    family_id,
+    user_id,
+    MAX(expires_at),
+    CASE WHEN SUM(revoked_at IS NULL) = 0 THEN MAX(revoked_at) END,
+    MIN(created_at),
+    MAX(created_at)
+FROM user_refresh_token
+GROUP BY family_id, user_id;
@@ -0,0 +1,7 @@
+-- token_version is embedded in every issued JWT (claim `ver`). Bumping it invalidates
+-- all outstanding access and refresh tokens of the user immediately.
+ALTER TABLE admin_user ADD COLUMN token_version INTEGER NOT NULL DEFAULT 0;
+
+-- A locked account cannot log in or use existing tokens until an admin unlocks it
+ALTER TABLE admin_user ADD COLUMN locked_at DATETIME;
+ALTER TABLE admin_user ADD COLUMN locked_reason TEXT;
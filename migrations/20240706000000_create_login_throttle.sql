@@ -0,0 +1,10 @@
+-- Failed login counters used by the SQLite-backed login throttle store
+-- (LOGIN_THROTTLE_STORE=sqlite). Keys look like `user:<username>` or `ip:<address>`.
+-- Times are unix timestamps (seconds).
+CREATE TABLE IF NOT EXISTS login_throttle (
+    key              TEXT PRIMARY KEY,
+    failures         INTEGER NOT NULL DEFAULT 0,
+    first_failure_at INTEGER NOT NULL,
+    last_failure_at  INTEGER NOT NULL,
+    locked_until     INTEGER
+);
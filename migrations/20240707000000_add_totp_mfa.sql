@@ -0,0 +1,19 @@
+-- TOTP (RFC 6238) second factor. totp_secret is written when enrollment starts and only
+-- takes effect once totp_enabled_at is set after the first valid code.
+ALTER TABLE admin_user ADD COLUMN totp_secret TEXT;
+ALTER TABLE admin_user ADD COLUMN totp_enabled_at DATETIME;
+-- Last accepted time step, so a code cannot be replayed within its validity window
+ALTER TABLE admin_user ADD COLUMN totp_last_step INTEGER;
+
+-- One-time recovery codes, stored as SHA-256 hashes
+CREATE TABLE IF NOT EXISTS user_recovery_code (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id    INTEGER NOT NULL REFERENCES admin_user (id) ON DELETE CASCADE,
+    code_hash  TEXT NOT NULL,
+    used_at    DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    UNIQUE (user_id, code_hash)
+);
+
+-- Users of a user type with require_mfa must enroll before their login completes
+ALTER TABLE user_type ADD COLUMN require_mfa BOOLEAN NOT NULL DEFAULT FALSE;
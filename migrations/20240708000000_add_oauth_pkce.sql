@@ -0,0 +1,9 @@
+-- PKCE (RFC 7636) parameters sent with the authorization request
+ALTER TABLE oauth_code ADD COLUMN code_challenge TEXT;
+ALTER TABLE oauth_code ADD COLUMN code_challenge_method TEXT;
+-- Set when the code is exchanged; a second exchange is treated as a replay
+ALTER TABLE oauth_code ADD COLUMN used_at DATETIME;
+
+-- Authorization code a token was issued from, so a replayed code can revoke its tokens
+ALTER TABLE oauth_token ADD COLUMN authorization_code TEXT;
+CREATE INDEX IF NOT EXISTS idx_oauth_token_authorization_code ON oauth_token (authorization_code);
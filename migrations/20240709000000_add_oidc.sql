@@ -0,0 +1,6 @@
+-- OpenID Connect: nonce from the authorization request, echoed in the ID token
+ALTER TABLE oauth_code ADD COLUMN nonce TEXT;
+
+-- Let the sample client request the OpenID Connect scopes
+UPDATE oauth_client SET scope = 'read write openid profile email'
+WHERE client_id = 'test-client' AND scope = 'read write';
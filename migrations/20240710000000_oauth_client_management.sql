@@ -0,0 +1,29 @@
+-- OAuth client management: display name, several redirect URIs and hashed secrets
+ALTER TABLE oauth_client ADD COLUMN name TEXT;
+UPDATE oauth_client SET name = client_id WHERE name IS NULL;
+
+-- Space separated, like scope
+ALTER TABLE oauth_client RENAME COLUMN redirect_uri TO redirect_uris;
+
+-- Secrets are stored as SHA-256 hashes. Plaintext secrets from before this migration stay in
+-- legacy_client_secret until the client first authenticates, then they are hashed and cleared.
+ALTER TABLE oauth_client RENAME COLUMN client_secret TO legacy_client_secret;
+ALTER TABLE oauth_client ADD COLUMN client_secret_hash TEXT;
+-- The secret replaced by the last rotation, still accepted until previous_secret_expires_at
+ALTER TABLE oauth_client ADD COLUMN previous_secret_hash TEXT;
+ALTER TABLE oauth_client ADD COLUMN previous_secret_expires_at DATETIME;
+
+-- Permissions for the client management API and pages
+INSERT INTO permission (code, name, description, category)
+VALUES
+    ('oauth_client:read', 'View OAuth Clients', 'View registered OAuth clients', 'oauth'),
+    ('oauth_client:create', 'Create OAuth Clients', 'Register new OAuth clients', 'oauth'),
+    ('oauth_client:update', 'Edit OAuth Clients', 'Edit OAuth clients and rotate their secrets', 'oauth'),
+    ('oauth_client:delete', 'Delete OAuth Clients', 'Delete OAuth clients', 'oauth')
+ON CONFLICT(code) DO NOTHING;
+
+INSERT INTO user_type_permission (user_type_id, permission_id)
+SELECT ut.id, p.id
+FROM user_type ut, permission p
+WHERE ut.code = 'super_admin' AND p.category = 'oauth'
+ON CONFLICT(user_type_id, permission_id) DO NOTHING;
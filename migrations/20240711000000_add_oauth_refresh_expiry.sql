@@ -0,0 +1,4 @@
+-- Refresh tokens outlive the access token they were issued with
+ALTER TABLE oauth_token ADD COLUMN refresh_expires_at DATETIME;
+UPDATE oauth_token SET refresh_expires_at = datetime(created_at, '+30 days') WHERE refresh_token IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_oauth_token_refresh_token ON oauth_token (refresh_token);
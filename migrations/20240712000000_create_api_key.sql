@@ -0,0 +1,16 @@
+-- Personal API keys for scripts and automation accounts. Only the SHA-256 hash of a key
+-- is stored; key_prefix keeps the first characters so users can tell keys apart.
+CREATE TABLE IF NOT EXISTS api_key (
+    id            INTEGER PRIMARY KEY AUTOINCREMENT,
+    user_id       INTEGER NOT NULL REFERENCES admin_user (id) ON DELETE CASCADE,
+    name          TEXT NOT NULL,
+    key_prefix    TEXT NOT NULL,
+    key_hash      TEXT NOT NULL UNIQUE,
+    -- Space-separated permission codes; the effective set is also limited by the owner's user type
+    permissions   TEXT NOT NULL,
+    expires_at    DATETIME,
+    last_used_at  DATETIME,
+    created_at    DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_user_id ON api_key (user_id);
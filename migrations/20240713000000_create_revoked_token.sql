@@ -0,0 +1,10 @@
+-- Denylist of JWT ids (claim `jti`) revoked before they expire, e.g. the access token
+-- presented at logout. A row is only needed until the token would have expired anyway.
+CREATE TABLE IF NOT EXISTS revoked_token (
+    jti         TEXT PRIMARY KEY,
+    user_id     INTEGER NOT NULL REFERENCES admin_user (id) ON DELETE CASCADE,
+    expires_at  DATETIME NOT NULL,
+    revoked_at  DATETIME DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_revoked_token_expires_at ON revoked_token (expires_at);
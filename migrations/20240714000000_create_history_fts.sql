@@ -0,0 +1,39 @@
+-- Full-text index over the free-text columns of history. It is an external content
+-- table, so the text is not stored twice; the triggers below keep it in sync.
+CREATE VIRTUAL TABLE IF NOT EXISTS history_fts USING fts5(
+    action,
+    details,
+    ip_address,
+    user_agent,
+    content = 'history',
+    content_rowid = 'id'
+);
+
+-- Index the rows written before this table existed
+INSERT INTO history_fts (history_fts) VALUES ('rebuild');
+
+CREATE TRIGGER IF NOT EXISTS history_fts_insert
+    AFTER INSERT ON history
+BEGIN
+    INSERT INTO history_fts (rowid, action, details, ip_address, user_agent)
+    VALUES (NEW.id, NEW.action, NEW.details, NEW.ip_address, NEW.user_agent);
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_fts_delete
+    AFTER DELETE ON history
+BEGIN
+    INSERT INTO history_fts (history_fts, rowid, action, details, ip_address, user_agent)
+    VALUES ('delete', OLD.id, OLD.action, OLD.details, OLD.ip_address, OLD.user_agent);
+END;
+
+CREATE TRIGGER IF NOT EXISTS history_fts_update
+    AFTER UPDATE ON history
+BEGIN
+    INSERT INTO history_fts (history_fts, rowid, action, details, ip_address, user_agent)
+    VALUES ('delete', OLD.id, OLD.action, OLD.details, OLD.ip_address, OLD.user_agent);
+    INSERT INTO history_fts (rowid, action, details, ip_address, user_agent)
+    VALUES (NEW.id, NEW.action, NEW.details, NEW.ip_address, NEW.user_agent);
+END;
+
+-- Filters on action are common; entity and user filters already have an index
+CREATE INDEX IF NOT EXISTS idx_history_action ON history (action);
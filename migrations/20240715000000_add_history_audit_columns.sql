@@ -0,0 +1,6 @@
+-- Structured audit trail: the kind of record an entry is about (`entity_id` refers to it)
+-- and the `x-request-id` of the request that caused it
+ALTER TABLE history ADD COLUMN entity_type TEXT;
+ALTER TABLE history ADD COLUMN request_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_history_entity ON history (entity_type, entity_id);
@@ -0,0 +1,29 @@
+-- Tamper-evident history: every row stores the hash of its predecessor and its own hash
+-- over its content plus that link. Rows written before this migration are sealed in id
+-- order when the server starts.
+ALTER TABLE history ADD COLUMN prev_hash TEXT;
+ALTER TABLE history ADD COLUMN hash TEXT;
+
+-- Retention cleanup removes the oldest rows; the hash of the last removed row is kept here,
+-- signed, so the remaining chain still has a verifiable anchor.
+CREATE TABLE IF NOT EXISTS history_checkpoint (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    through_id INTEGER NOT NULL,
+    through_hash TEXT NOT NULL,
+    deleted_count INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    signature TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_history_checkpoint_through_id ON history_checkpoint (through_id);
+
+-- Sealing a row updates only the hash columns; re-index the text only when it changes
+DROP TRIGGER IF EXISTS history_fts_update;
+CREATE TRIGGER IF NOT EXISTS history_fts_update
+    AFTER UPDATE OF action, details, ip_address, user_agent ON history
+BEGIN
+    INSERT INTO history_fts (history_fts, rowid, action, details, ip_address, user_agent)
+    VALUES ('delete', OLD.id, OLD.action, OLD.details, OLD.ip_address, OLD.user_agent);
+    INSERT INTO history_fts (rowid, action, details, ip_address, user_agent)
+    VALUES (NEW.id, NEW.action, NEW.details, NEW.ip_address, NEW.user_agent);
+END;
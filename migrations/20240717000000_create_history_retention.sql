@@ -0,0 +1,18 @@
+-- Retention policies remove expired rows anywhere in the chain, not only the oldest ones.
+-- A checkpoint now covers one run of removed rows: anchor_hash is the hash the first removed
+-- row pointed at, so verification can bridge the gap. Older checkpoints keep NULL here.
+ALTER TABLE history_checkpoint ADD COLUMN from_id INTEGER;
+ALTER TABLE history_checkpoint ADD COLUMN anchor_hash TEXT;
+
+-- One row per retention run, shown on the dashboard
+CREATE TABLE IF NOT EXISTS history_retention_run (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    triggered_by TEXT NOT NULL,              -- scheduled | manual
+    status TEXT NOT NULL DEFAULT 'running',  -- running | succeeded | interrupted | failed
+    archived_count INTEGER NOT NULL DEFAULT 0,
+    deleted_count INTEGER NOT NULL DEFAULT 0,
+    archive_files INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    started_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    finished_at DATETIME
+);
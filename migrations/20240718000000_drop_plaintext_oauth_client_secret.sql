@@ -0,0 +1,5 @@
+-- Drop the plaintext client secrets kept by 20240710000000 until the first authentication.
+-- SQLite has no SHA-256 function to hash them here, so every client still holding a plaintext
+-- secret (including the sample test-client) is left without one and must have its secret
+-- rotated (POST /api/oauth-client/{id}/secret) before it can authenticate again.
+ALTER TABLE oauth_client DROP COLUMN legacy_client_secret;
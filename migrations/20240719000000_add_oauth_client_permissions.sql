@@ -0,0 +1,11 @@
+-- Permissions of tokens a client obtains on its own behalf (client_credentials).
+-- Space-separated permission codes like api_key.permissions; a client is granted none by default.
+ALTER TABLE oauth_client ADD COLUMN permissions TEXT NOT NULL DEFAULT '';
+
+-- token_version of the resource owner when a user-bound token was issued, so that a force
+-- logout, lock or deactivation revokes OAuth tokens the same way it revokes JWTs
+ALTER TABLE oauth_token ADD COLUMN token_version INTEGER;
+
+UPDATE oauth_token
+SET token_version = (SELECT token_version FROM admin_user WHERE admin_user.id = oauth_token.user_id)
+WHERE user_id IS NOT NULL;
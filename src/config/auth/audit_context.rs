@@ -0,0 +1,78 @@
+use crate::config::auth::{
+    authn_api_key::AuthnApiKey, authn_client::AuthnClient, authn_user::AuthnUser,
+};
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{header, request::Parts},
+};
+use std::{convert::Infallible, net::SocketAddr};
+
+/// Who made a request and where it came from, recorded with every audited change
+#[derive(Debug, Clone, Default)]
+pub struct AuditContext {
+    /// Acting user; `None` for OAuth clients acting on their own behalf
+    pub actor_id: Option<i64>,
+    /// OAuth client the request was made through
+    pub client_id: Option<String>,
+    /// Personal API key the request was made with
+    pub api_key_id: Option<i64>,
+    /// `x-request-id` set (or passed through) by the request id layer
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl AuditContext {
+    /// The same request attributed to `user_id`, for steps of a login where the user has
+    /// proven who they are but is not authenticated on the request yet
+    pub fn acting_as(&self, user_id: i64) -> Self {
+        Self {
+            actor_id: Some(user_id),
+            ..self.clone()
+        }
+    }
+
+    /// The same request attributed to OAuth client `client_id`, acting for `user_id` if set,
+    /// for endpoints where the client authenticates itself instead of the auth middleware
+    pub fn via_client(&self, client_id: &str, user_id: Option<i64>) -> Self {
+        Self {
+            actor_id: user_id,
+            client_id: Some(client_id.to_string()),
+            ..self.clone()
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for AuditContext
+where
+    S: Send + Sync,
+{
+    type Rejection = Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let client = parts.extensions.get::<AuthnClient>();
+        let header = |name: &str| {
+            parts
+                .headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+
+        Ok(Self {
+            actor_id: parts
+                .extensions
+                .get::<AuthnUser>()
+                .or_else(|| client.and_then(|client| client.user.as_ref()))
+                .map(|user| user.id),
+            client_id: client.map(|client| client.client_id.clone()),
+            api_key_id: parts.extensions.get::<AuthnApiKey>().map(|key| key.id),
+            request_id: header("x-request-id"),
+            ip_address: parts
+                .extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip().to_string()),
+            user_agent: header(header::USER_AGENT.as_str()),
+        })
+    }
+}
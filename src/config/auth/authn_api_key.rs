@@ -0,0 +1,7 @@
+/// The personal API key a request was authenticated with. The key's owner is resolved as
+/// the `AuthnUser`, limited to the permissions granted to the key.
+#[derive(Debug, Clone)]
+pub struct AuthnApiKey {
+    pub id: i64,
+    pub name: String,
+}
@@ -0,0 +1,77 @@
+use crate::{config::auth::authn_user::AuthnUser, errors::AppError};
+use axum::{extract::FromRequestParts, http::request::Parts};
+use std::{collections::HashSet, fmt};
+
+/// Caller authenticated with an OAuth access token issued by `/api/oauth/token`
+#[derive(Debug, Clone)]
+pub struct AuthnClient {
+    pub client_id: String,
+    pub scopes: HashSet<String>,
+    /// Resource owner the token was issued for; `None` for `client_credentials` tokens
+    pub user: Option<AuthnUser>,
+    /// Permission codes granted to the client itself, checked when there is no `user`
+    pub permissions: HashSet<String>,
+    /// Set by `require_scope` once the route's scope has been granted
+    pub(crate) scope_checked: bool,
+}
+
+impl AuthnClient {
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
+}
+
+/// Whoever is making the request: a signed-in user (session, JWT or user-bound OAuth token)
+/// or an OAuth client acting on its own behalf.
+#[derive(Debug, Clone)]
+pub enum Principal {
+    User(AuthnUser),
+    Client(AuthnClient),
+}
+
+impl Principal {
+    pub fn user_id(&self) -> Option<i64> {
+        match self {
+            Principal::User(user) => Some(user.id),
+            Principal::Client(_) => None,
+        }
+    }
+
+    /// RBAC check. Clients without a user hold only the permissions granted to the client,
+    /// none by default.
+    pub fn has_permission(&self, code: &str) -> bool {
+        match self {
+            Principal::User(user) => user.permissions.contains(code),
+            Principal::Client(client) => client.permissions.contains(code),
+        }
+    }
+}
+
+impl fmt::Display for Principal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Principal::User(user) => write!(f, "user {}", user.id),
+            Principal::Client(client) => write!(f, "client {}", client.client_id),
+        }
+    }
+}
+
+impl<S> FromRequestParts<S> for Principal
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        if let Some(authn_user) = parts.extensions.get::<AuthnUser>() {
+            return Ok(Principal::User(authn_user.clone()));
+        }
+        match parts.extensions.get::<AuthnClient>() {
+            Some(client) if client.scope_checked => Ok(Principal::Client(client.clone())),
+            Some(_) => Err(AppError::Forbidden(
+                "This endpoint does not accept OAuth access tokens".to_string(),
+            )),
+            None => Err(AppError::Unauthorized("Not authenticated".to_string())),
+        }
+    }
+}
@@ -1,5 +1,8 @@
-use crate::errors::AppError;
-use axum::{extract::FromRequestParts, http::request::Parts};
+use crate::{config::auth::authn_client::AuthnClient, errors::AppError};
+use axum::{
+    extract::{FromRequestParts, OptionalFromRequestParts},
+    http::request::Parts,
+};
 use std::{collections::HashSet, sync::Arc};
 
 #[derive(Debug, Clone)]
@@ -17,10 +20,30 @@ where
     type Rejection = AppError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let authn_user = parts
-            .extensions
-            .get::<AuthnUser>()
-            .ok_or(AppError::Unauthorized("Not authenticated".to_string()))?;
-        Ok(authn_user.clone())
+        if let Some(authn_user) = parts.extensions.get::<AuthnUser>() {
+            return Ok(authn_user.clone());
+        }
+        // OAuth 토큰은 scope가 지정된 라우트에서만 사용자로 인정
+        if parts.extensions.get::<AuthnClient>().is_some() {
+            return Err(AppError::Forbidden(
+                "This endpoint does not accept OAuth access tokens".to_string(),
+            ));
+        }
+        Err(AppError::Unauthorized("Not authenticated".to_string()))
+    }
+}
+
+/// Lets handlers that serve both anonymous and signed-in users take `Option<AuthnUser>`
+impl<S> OptionalFromRequestParts<S> for AuthnUser
+where
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        _state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        Ok(parts.extensions.get::<AuthnUser>().cloned())
     }
 }
@@ -2,6 +2,9 @@
 //!
 //! This module contains types and utilities for handling user authentication and authorization,
 //! including JWT token handling, user authentication, and permission checking.
+pub mod audit_context;
+pub mod authn_api_key;
+pub mod authn_client;
 pub mod authn_user;
 pub mod extractor;
 pub mod user;
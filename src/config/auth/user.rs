@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -5,24 +6,50 @@ pub struct User {
     pub id: i64,
     pub username: String,
     pub password: String,
+    pub user_type_id: i64,
+    /// user_type.code
     pub role: String,
+    pub token_version: i64,
+    pub locked: bool,
 }
 
 impl User {
-    pub fn new(id: i64, username: String, password: String, role: String) -> Self {
+    pub fn new(
+        id: i64,
+        username: String,
+        password: String,
+        user_type_id: i64,
+        role: String,
+        token_version: i64,
+        locked: bool,
+    ) -> Self {
         Self {
             id,
             username,
             password,
+            user_type_id,
             role,
+            token_version,
+            locked,
         }
     }
+}
 
-    pub fn is_admin(&self) -> bool {
-        self.role == "admin"
-    }
+/// Account fields checked on every authenticated request
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct AccountState {
+    pub id: i64,
+    pub username: String,
+    pub user_type_id: i64,
+    pub is_active: bool,
+    pub token_version: i64,
+    pub locked_at: Option<NaiveDateTime>,
+    pub locked_reason: Option<String>,
+}
 
-    pub fn is_user(&self) -> bool {
-        self.role == "user"
+impl AccountState {
+    /// Whether tokens carrying `token_version` may still be used for this account
+    pub fn accepts(&self, token_version: i64) -> bool {
+        self.is_active && self.locked_at.is_none() && self.token_version == token_version
     }
 }
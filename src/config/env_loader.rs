@@ -1,5 +1,7 @@
+use crate::util::jwk_util::JwtKey;
 use dotenvy::from_filename;
 use env::var;
+use std::collections::BTreeMap;
 use std::env;
 use std::path::Path;
 use std::sync::OnceLock;
@@ -7,7 +9,7 @@ use std::sync::OnceLock;
 static CONFIG: OnceLock<AppConfig> = OnceLock::new();
 
 pub fn get_config() -> &'static AppConfig {
-    CONFIG.get_or_init(|| AppConfig::from_env())
+    CONFIG.get_or_init(AppConfig::from_env)
 }
 
 #[derive(Clone)]
@@ -16,26 +18,43 @@ pub struct AppConfig {
     pub database_url: String,
     pub server_host: String,
     pub server_port: u16,
+    /// Public URL used when building links sent outside the app (e.g. password reset mails)
+    pub base_url: String,
     pub log: Log,
     pub token: Token,
     pub cookie: Cookie,
+    pub mail: Mail,
+    pub login_throttle: LoginThrottle,
+    pub oidc: Oidc,
+    pub history_log: HistoryLog,
 }
 
 impl AppConfig {
     pub fn from_env() -> Self {
         load_env_files();
 
+        let server_host = var("SERVER_HOST").expect("SERVER_HOST must be set");
+        let server_port: u16 = var("SERVER_PORT")
+            .expect("SERVER_PORT must be a valid number")
+            .parse()
+            .expect("SERVER_PORT must be a valid number");
+
+        let base_url =
+            var("APP_BASE_URL").unwrap_or(format!("http://{}:{}", server_host, server_port));
+
         Self {
             app_name: var("APP_NAME").unwrap_or("admin-server".to_string()),
             database_url: var("DATABASE_URL").expect("DATABASE_URL must be set"),
-            server_host: var("SERVER_HOST").expect("SERVER_HOST must be set"),
-            server_port: var("SERVER_PORT")
-                .expect("SERVER_PORT must be a valid number")
-                .parse()
-                .expect("SERVER_PORT must be a valid number"),
+            oidc: Oidc::from_env(&base_url),
+            token: Token::from_env(&base_url),
+            base_url,
+            server_host,
+            server_port,
             log: Log::from_env(),
-            token: Token::from_env(),
             cookie: Cookie::from_env(),
+            mail: Mail::from_env(),
+            login_throttle: LoginThrottle::from_env(),
+            history_log: HistoryLog::from_env(),
         }
     }
 }
@@ -74,10 +93,53 @@ pub struct Token {
     pub access_exp: i64,
     pub refresh_name: String,
     pub refresh_exp: i64,
+    pub password_reset_exp: i64,
+    /// Lifetime of the "mfa pending" token handed out between the password and TOTP steps
+    pub mfa_exp: i64,
+    /// Asymmetric key signing access and refresh tokens; HS256 with `secret` while unset
+    pub signing_key: Option<JwtKey>,
+    /// Retired keys whose tokens are still accepted until they expire
+    pub verification_keys: Vec<JwtKey>,
+    /// `iss` of issued tokens
+    pub issuer: String,
+    /// `aud` of issued tokens
+    pub audience: String,
+    /// Clock skew tolerated when checking `exp` and `nbf`, in seconds
+    pub leeway: u64,
 }
 
 impl Token {
-    pub fn from_env() -> Self {
+    pub fn from_env(base_url: &str) -> Self {
+        let signing_key = var("TOKEN_SIGNING_KEY_PATH").ok().map(|path| {
+            let key = JwtKey::from_pem_file(&path, None).unwrap_or_else(|e| {
+                panic!(
+                    "TOKEN_SIGNING_KEY_PATH must point to a PEM private key: {:#}",
+                    e
+                )
+            });
+            assert!(
+                key.can_sign(),
+                "TOKEN_SIGNING_KEY_PATH must be a private key"
+            );
+            key
+        });
+        let verification_keys = var("TOKEN_VERIFICATION_KEY_PATHS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(|path| {
+                JwtKey::from_pem_file(path, None).unwrap_or_else(|e| {
+                    panic!("TOKEN_VERIFICATION_KEY_PATHS must list PEM keys: {:#}", e)
+                })
+            })
+            .filter(|key| {
+                signing_key
+                    .as_ref()
+                    .is_none_or(|active| active.kid != key.kid)
+            })
+            .collect();
+
         Self {
             secret: var("TOKEN_SECRET").expect("TOKEN_SECRET must be set"),
             access_name: var("TOKEN_ACCESS_NAME").unwrap_or("access".to_string()),
@@ -90,8 +152,39 @@ impl Token {
                 .unwrap_or("86400".to_string())
                 .parse()
                 .expect("TOKEN_REFRESH_EXP must be a valid number"),
+            password_reset_exp: var("TOKEN_PASSWORD_RESET_EXP")
+                .unwrap_or("1800".to_string())
+                .parse()
+                .expect("TOKEN_PASSWORD_RESET_EXP must be a valid number"),
+            mfa_exp: var("TOKEN_MFA_EXP")
+                .unwrap_or("300".to_string())
+                .parse()
+                .expect("TOKEN_MFA_EXP must be a valid number"),
+            signing_key,
+            verification_keys,
+            issuer: var("TOKEN_ISSUER").unwrap_or(base_url.to_string()),
+            audience: var("TOKEN_AUDIENCE")
+                .or_else(|_| var("APP_NAME"))
+                .unwrap_or("admin-server".to_string()),
+            leeway: var("TOKEN_LEEWAY")
+                .unwrap_or("60".to_string())
+                .parse()
+                .expect("TOKEN_LEEWAY must be a valid number"),
         }
     }
+
+    /// Key that may have signed a token with the given `kid`
+    pub fn verification_key(&self, kid: &str) -> Option<&JwtKey> {
+        self.public_keys().into_iter().find(|key| key.kid == kid)
+    }
+
+    /// Active signing key followed by the retired ones, as published in the JWKS
+    pub fn public_keys(&self) -> Vec<&JwtKey> {
+        self.signing_key
+            .iter()
+            .chain(self.verification_keys.iter())
+            .collect()
+    }
 }
 
 #[derive(Clone)]
@@ -135,6 +228,156 @@ impl Cookie {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub sender: String, // "file", "log"
+    pub from: String,
+    pub outbox_path: String,
+}
+
+impl Mail {
+    pub fn from_env() -> Self {
+        Self {
+            sender: var("MAIL_SENDER").unwrap_or("log".to_string()),
+            from: var("MAIL_FROM").unwrap_or("no-reply@localhost".to_string()),
+            outbox_path: var("MAIL_OUTBOX_PATH").unwrap_or("logs/mail".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LoginThrottle {
+    pub enabled: bool,
+    pub store: String, // "memory", "sqlite"
+    /// Failures within `window_secs` that trigger a lockout
+    pub max_failures: u32,
+    /// Same limit for a single IP address; higher since an address may be shared
+    pub ip_max_failures: u32,
+    pub window_secs: i64,
+    pub lockout_secs: i64,
+    /// Delay after the first failure, doubled on every further failure
+    pub backoff_base_secs: i64,
+    pub backoff_max_secs: i64,
+}
+
+impl LoginThrottle {
+    pub fn from_env() -> Self {
+        Self {
+            enabled: var("LOGIN_THROTTLE_ENABLE")
+                .unwrap_or("true".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_ENABLE must be a valid boolean"),
+            store: var("LOGIN_THROTTLE_STORE").unwrap_or("memory".to_string()),
+            max_failures: var("LOGIN_THROTTLE_MAX_FAILURES")
+                .unwrap_or("5".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_MAX_FAILURES must be a valid number"),
+            ip_max_failures: var("LOGIN_THROTTLE_IP_MAX_FAILURES")
+                .unwrap_or("20".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_IP_MAX_FAILURES must be a valid number"),
+            window_secs: var("LOGIN_THROTTLE_WINDOW")
+                .unwrap_or("900".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_WINDOW must be a valid number"),
+            lockout_secs: var("LOGIN_THROTTLE_LOCKOUT")
+                .unwrap_or("900".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_LOCKOUT must be a valid number"),
+            backoff_base_secs: var("LOGIN_THROTTLE_BACKOFF_BASE")
+                .unwrap_or("1".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_BACKOFF_BASE must be a valid number"),
+            backoff_max_secs: var("LOGIN_THROTTLE_BACKOFF_MAX")
+                .unwrap_or("60".to_string())
+                .parse()
+                .expect("LOGIN_THROTTLE_BACKOFF_MAX must be a valid number"),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct HistoryLog {
+    /// HMAC key for the history hash chain; plain SHA-256 when unset. Changing it
+    /// invalidates every hash written with the previous key.
+    pub hmac_key: Option<String>,
+    /// Whether the retention job runs on a schedule; cleanup can always be run manually
+    pub retention_enabled: bool,
+    pub retention_interval_secs: u64,
+    /// Days to keep actions without their own policy; 0 keeps them forever
+    pub retention_days: i64,
+    /// Days to keep per action (e.g. `login_failed=30,user_deleted=2555`); 0 keeps forever
+    pub retention_policies: BTreeMap<String, i64>,
+    /// Root directory of the compressed, date-partitioned archive of removed rows
+    pub archive_path: String,
+}
+
+impl HistoryLog {
+    pub fn from_env() -> Self {
+        Self {
+            hmac_key: var("HISTORY_HMAC_KEY").ok().filter(|key| !key.is_empty()),
+            retention_enabled: var("HISTORY_RETENTION_ENABLE")
+                .unwrap_or("false".to_string())
+                .parse()
+                .expect("HISTORY_RETENTION_ENABLE must be a valid boolean"),
+            retention_interval_secs: var("HISTORY_RETENTION_INTERVAL")
+                .unwrap_or("86400".to_string())
+                .parse()
+                .expect("HISTORY_RETENTION_INTERVAL must be a valid number"),
+            retention_days: var("HISTORY_RETENTION_DAYS")
+                .unwrap_or("90".to_string())
+                .parse()
+                .expect("HISTORY_RETENTION_DAYS must be a valid number"),
+            retention_policies: var("HISTORY_RETENTION_POLICIES")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|policy| !policy.is_empty())
+                .map(|policy| {
+                    policy
+                        .split_once('=')
+                        .and_then(|(action, days)| {
+                            Some((action.trim().to_string(), days.trim().parse().ok()?))
+                        })
+                        .expect("HISTORY_RETENTION_POLICIES must be a list of action=days")
+                })
+                .collect(),
+            archive_path: var("HISTORY_ARCHIVE_PATH").unwrap_or("data/archive".to_string()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Oidc {
+    /// `iss` of issued ID tokens, also the base of the discovery document
+    pub issuer: String,
+    /// Key for ID tokens; OpenID Connect is disabled while no key is configured
+    pub signing_key: Option<JwtKey>,
+    pub id_token_exp: i64,
+}
+
+impl Oidc {
+    pub fn from_env(base_url: &str) -> Self {
+        Self {
+            issuer: var("OIDC_ISSUER").unwrap_or(base_url.to_string()),
+            signing_key: var("OIDC_SIGNING_KEY_PATH").ok().map(|path| {
+                JwtKey::from_pem_file(&path, var("OIDC_KEY_ID").ok())
+                    .ok()
+                    .filter(JwtKey::can_sign)
+                    .expect("OIDC_SIGNING_KEY_PATH must point to a PEM encoded private key")
+            }),
+            id_token_exp: var("OIDC_ID_TOKEN_EXP")
+                .unwrap_or("3600".to_string())
+                .parse()
+                .expect("OIDC_ID_TOKEN_EXP must be a valid number"),
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.signing_key.is_some()
+    }
+}
+
 fn load_env_files() {
     // 환경 확인
     let rust_env = var("RUST_ENV").unwrap_or_else(|_| "dev".to_string());
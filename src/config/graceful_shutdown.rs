@@ -1,7 +1,8 @@
-use tokio::signal;
+use tokio::{signal, sync::watch};
 use tracing::info;
 
-pub async fn shutdown_signal() {
+/// Waits for Ctrl+C or SIGTERM, then tells background jobs to stop through `shutdown`
+pub async fn shutdown_signal(shutdown: watch::Sender<bool>) {
     let ctrl_c = async {
         signal::ctrl_c()
             .await
@@ -28,5 +29,8 @@ pub async fn shutdown_signal() {
         },
     }
 
+    // Background jobs may already be gone; nothing to notify then
+    let _ = shutdown.send(true);
+
     info!("Shutdown complete");
 }
@@ -1,50 +1,125 @@
 use crate::{
+    config::env_loader::AppConfig,
     repository::{
-        history::HistoryRepository, oauth::OAuthRepository, AuthRepository, PermissionRepository,
+        ApiKeyRepository, AuthRepository, HistoryRepository, HistoryRetentionRepository,
+        LoginThrottleRepository, MfaRepository, OAuthRepository, PermissionRepository,
         UserRepository, UserTypeRepository,
     },
     service::{
-        auth::AuthService, history::HistoryService, oauth::OAuthService,
-        permission::PermissionService, user::UserService, user_type::UserTypeService,
+        api_key::ApiKeyService,
+        audit::AuditService,
+        auth::AuthService,
+        history::HistoryService,
+        history_retention::HistoryRetentionService,
+        login_throttle::{self, LoginThrottleService},
+        mail,
+        mfa::MfaService,
+        oauth::OAuthService,
+        oauth_client::OAuthClientService,
+        permission::PermissionService,
+        user::UserService,
+        user_type::UserTypeService,
     },
+    util::hash_chain_util::ChainHasher,
 };
 use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct ServiceContainer {
+    pub api_key_service: Arc<ApiKeyService>,
     pub auth_service: Arc<AuthService>,
     pub history_service: Arc<HistoryService>,
+    pub history_retention_service: Arc<HistoryRetentionService>,
+    pub login_throttle_service: Arc<LoginThrottleService>,
+    pub mfa_service: Arc<MfaService>,
     pub oauth_service: Arc<OAuthService>,
+    pub oauth_client_service: Arc<OAuthClientService>,
     pub permission_service: Arc<PermissionService>,
     pub user_service: Arc<UserService>,
     pub user_type_service: Arc<UserTypeService>,
 }
 
 impl ServiceContainer {
-    pub fn new(db: Arc<sqlx::SqlitePool>) -> Self {
+    pub fn new(db: Arc<sqlx::SqlitePool>, config: &AppConfig) -> Self {
+        let api_key_repo = ApiKeyRepository::new(db.clone());
         let auth_repo = AuthRepository::new(db.clone());
-        let history_repo = HistoryRepository::new(db.clone());
+        let hasher = ChainHasher::new(config.history_log.hmac_key.as_deref().map(str::as_bytes));
+        let history_repo = HistoryRepository::new(db.clone(), hasher.clone());
+        let history_retention_repo = HistoryRetentionRepository::new(db.clone());
+        let login_throttle_repo = LoginThrottleRepository::new(db.clone());
+        let mfa_repo = MfaRepository::new(db.clone());
         let oauth_repo = OAuthRepository::new(db.clone());
         let permission_repo = PermissionRepository::new(db.clone());
         let user_repo = UserRepository::new(db.clone());
         let user_type_repo = UserTypeRepository::new(db.clone());
 
-        let history = Arc::new(HistoryService::new(history_repo));
+        let mail_sender = mail::build_mail_sender(&config.mail);
+
+        let audit = Arc::new(AuditService::new(history_repo.clone()));
+        let history_retention = Arc::new(HistoryRetentionService::new(
+            history_repo.clone(),
+            history_retention_repo,
+            config.history_log.clone(),
+        ));
+        let history = Arc::new(HistoryService::new(history_repo, hasher));
+        let permission = Arc::new(PermissionService::new(
+            permission_repo.clone(),
+            audit.clone(),
+        ));
+        let login_throttle = Arc::new(LoginThrottleService::new(
+            config.login_throttle.clone(),
+            login_throttle::build_throttle_store(&config.login_throttle, login_throttle_repo),
+            audit.clone(),
+        ));
+        let mfa = Arc::new(MfaService::new(
+            mfa_repo,
+            audit.clone(),
+            config.app_name.clone(),
+        ));
+        let api_key = Arc::new(ApiKeyService::new(
+            api_key_repo,
+            auth_repo.clone(),
+            permission.clone(),
+            audit.clone(),
+        ));
         let auth = Arc::new(AuthService::new(
-            auth_repo,
+            auth_repo.clone(),
             user_repo.clone(),
             user_type_repo.clone(),
+            oauth_repo.clone(),
+            permission.clone(),
             history.clone(),
+            audit.clone(),
+            mail_sender,
+            login_throttle.clone(),
+            mfa.clone(),
+        ));
+        let oauth_client = Arc::new(OAuthClientService::new(oauth_repo.clone(), audit.clone()));
+        let oauth = Arc::new(OAuthService::new(
+            oauth_repo,
+            auth_repo,
+            user_repo.clone(),
+            audit.clone(),
+            config.base_url.clone(),
+            config.oidc.clone(),
+        ));
+        let user = Arc::new(UserService::new(user_repo.clone(), audit.clone()));
+        let user_type = Arc::new(UserTypeService::new(
+            user_type_repo.clone(),
+            permission_repo.clone(),
+            permission.clone(),
+            audit,
         ));
-        let oauth = Arc::new(OAuthService::new(oauth_repo));
-        let permission = Arc::new(PermissionService::new(permission_repo.clone()));
-        let user = Arc::new(UserService::new(user_repo.clone()));
-        let user_type = Arc::new(UserTypeService::new(user_type_repo.clone()));
 
         Self {
+            api_key_service: api_key,
             auth_service: auth,
             history_service: history,
+            history_retention_service: history_retention,
+            login_throttle_service: login_throttle,
+            mfa_service: mfa,
             oauth_service: oauth,
+            oauth_client_service: oauth_client,
             permission_service: permission,
             user_service: user,
             user_type_service: user_type,
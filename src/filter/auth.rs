@@ -1,6 +1,7 @@
 use crate::{
+    config::auth::{authn_api_key::AuthnApiKey, authn_client::AuthnClient, authn_user::AuthnUser},
     errors::AppError,
-    util::{cookie_util, header_util, token_util},
+    util::{cookie_util, header_util},
 };
 use axum::{
     extract::Request,
@@ -10,19 +11,6 @@ use axum::{
 };
 use tracing::warn;
 
-/// Wrapper type for user ID that implements Send + Sync + 'static
-#[derive(Debug, Clone)]
-pub struct UserId(pub i64);
-
-impl UserId {
-    /// Creates a new UserId with the given user ID.
-    pub fn new(user_id: i64) -> Self {
-        Self(user_id)
-    }
-}
-
-/// Middleware that authenticates requests using a JWT token from the Authorization header or access_token cookie.
-/// Returns an error if the token is missing, invalid, or expired.
 /// Renders an error page for unauthorized access
 async fn render_unauthorized_page() -> Response {
     // Create a simple error page
@@ -50,48 +38,55 @@ async fn render_unauthorized_page() -> Response {
     (StatusCode::UNAUTHORIZED, Html(html)).into_response()
 }
 
-/// Middleware that authenticates requests using a JWT token from the Authorization header or access_token cookie.
-/// Returns an error if the token is missing, invalid, or expired.
-pub async fn auth(headers: HeaderMap, mut request: Request, next: Next) -> Response {
+/// Extracts the access token from the Authorization header, falling back to the access_token cookie.
+pub(crate) fn extract_token(headers: &HeaderMap) -> Option<String> {
+    header_util::extract_token_from_header(headers)
+        .map(|s| s.to_string())
+        .or_else(|| cookie_util::get_access_token(None, headers))
+        .filter(|token| !token.is_empty())
+}
+
+/// Middleware that requires an authenticated user or OAuth client.
+/// `optional_auth` resolves the `AuthnUser` (JWT) or `AuthnClient` (OAuth access token) for every
+/// request; this filter rejects the request when neither could be resolved because the token is
+/// missing, invalid, or expired. OAuth clients still need a `require_scope` guard on the route.
+pub async fn auth(headers: HeaderMap, request: Request, next: Next) -> Response {
+    if request.extensions().get::<AuthnUser>().is_some()
+        || request.extensions().get::<AuthnClient>().is_some()
+    {
+        return next.run(request).await;
+    }
+
     // Check if this is an API request
     let is_api_request = header_util::is_api_request(&headers);
 
-    // 1. Extract token from Authorization header
-    let token = header_util::extract_token_from_header(&headers)
-        // 2. If not found in header, try to get from access_token cookie
-        .map(|s| s.to_string())
-        .or_else(|| cookie_util::get_access_token(None, &headers));
-
-    let token = match token {
-        Some(token) if !token.is_empty() => token,
-        _ => {
+    let message =
+        if extract_token(&headers).is_none() && header_util::extract_api_key(&headers).is_none() {
             warn!("Missing or invalid Authorization header and access_token cookie");
-            return if is_api_request {
-                AppError::Unauthorized(
-                    "Missing or invalid Authorization header or access_token cookie. Please login again."
-                        .to_string(),
-                )
-                    .into_response()
-            } else {
-                render_unauthorized_page().await
-            };
-        }
-    };
+            "Missing or invalid Authorization header or access_token cookie. Please login again."
+        } else {
+            warn!("Token did not resolve to an authenticated user");
+            "Invalid or expired token"
+        };
+
+    if is_api_request {
+        AppError::Unauthorized(message.to_string()).into_response()
+    } else {
+        render_unauthorized_page().await
+    }
+}
 
-    // Validate the token
-    match token_util::validate_token(&token) {
-        Ok(claims) => {
-            // Add the user ID to the request extensions for use in handlers
-            request.extensions_mut().insert(UserId::new(claims.sub));
-            next.run(request).await
-        }
-        Err(e) => {
-            warn!(error = %e, "Token validation failed");
-            if is_api_request {
-                AppError::Unauthorized("Invalid or expired token".to_string()).into_response()
-            } else {
-                render_unauthorized_page().await
-            }
-        }
+/// Middleware for account self-service routes (sessions, two-factor, API keys) and OAuth
+/// consent that must be called from a signed-in session, so that a restricted API key cannot
+/// mint a broader key or token.
+pub async fn deny_api_key(request: Request, next: Next) -> Response {
+    if let Some(api_key) = request.extensions().get::<AuthnApiKey>() {
+        warn!(
+            api_key_id = api_key.id,
+            "API key used on a session-only route"
+        );
+        return AppError::Forbidden("This endpoint cannot be used with an API key".to_string())
+            .into_response();
     }
+    next.run(request).await
 }
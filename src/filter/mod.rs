@@ -5,7 +5,11 @@
 pub mod auth;
 mod log;
 mod optional_auth;
+mod permission;
+mod scope;
 
-pub use auth::{auth, UserId};
+pub use auth::{auth, deny_api_key};
 pub use log::log;
 pub use optional_auth::optional_auth;
+pub use permission::require_permission;
+pub use scope::require_scope;
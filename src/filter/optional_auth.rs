@@ -1,38 +1,112 @@
 use crate::{
-    filter::auth::UserId,
-    util::{cookie_util, header_util, token_util},
+    config::auth::audit_context::AuditContext,
+    filter::auth::extract_token,
+    service::{api_key::API_KEY_PREFIX, oauth::ACCESS_TOKEN_PREFIX},
+    util::header_util,
+    AppState,
 };
-use axum::{body::Body, extract::Request, middleware::Next, response::Response};
-use std::convert::Infallible;
+use axum::{
+    body::Body,
+    extract::{FromRequestParts, Request, State},
+    middleware::Next,
+    response::Response,
+};
+use std::{convert::Infallible, sync::Arc};
 use tracing::warn;
 
 /// Middleware that optionally authenticates requests using a JWT token from the Authorization header or access_token cookie.
-/// If the token is valid, the user ID is added to the request extensions.
+/// If the token is valid, the resolved `AuthnUser` (id, username, user type and permission set) is added to the request extensions.
+/// OAuth access tokens (`at_...`) resolve to an `AuthnClient` instead, carrying the token's scopes and user.
+/// Personal API keys (`X-API-Key` or `Bearer adm_pk_...`) resolve to their owner plus an `AuthnApiKey`,
+/// and every request made with one is recorded in history.
 /// If the token is missing or invalid, the request continues without authentication.
-pub async fn optional_auth(mut request: Request<Body>, next: Next) -> Result<Response, Infallible> {
-    // Clone the headers to avoid moving the request
-    let headers = request.headers().clone();
+pub async fn optional_auth(
+    State(state): State<Arc<AppState>>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Result<Response, Infallible> {
+    // 1. Try to extract token from Authorization header or access_token cookie
+    let token = extract_token(request.headers());
 
-    // 1. Try to extract token from Authorization header
-    let token = header_util::extract_token_from_header(&headers)
-        // 2. If not found, try to get from access_token cookie
-        .map(|s| s.to_string())
-        .or_else(|| cookie_util::get_access_token(None, &headers));
+    let api_key = header_util::extract_api_key(request.headers())
+        .map(str::to_string)
+        .or_else(|| {
+            token
+                .clone()
+                .filter(|token| token.starts_with(API_KEY_PREFIX))
+        });
+    if let Some(api_key) = api_key {
+        return Ok(authenticate_api_key(&state, &api_key, request, next).await);
+    }
 
-    // 3. If token exists, validate it
+    // 2. If token exists, validate it and resolve the user
     if let Some(token_str) = token {
-        match token_util::validate_token(&token_str) {
-            Ok(claims) => {
-                // Insert the user ID into request extensions
-                request.extensions_mut().insert(UserId::new(claims.sub));
+        if token_str.starts_with(ACCESS_TOKEN_PREFIX) {
+            match state
+                .service
+                .auth_service
+                .authenticate_oauth_token(&token_str)
+                .await
+            {
+                Ok(authn_client) => {
+                    request.extensions_mut().insert(authn_client);
+                }
+                Err(e) => {
+                    warn!(error = %e, "OAuth token authentication failed");
+                }
+            }
+            return Ok(next.run(request).await);
+        }
+
+        match state.service.auth_service.authenticate(&token_str).await {
+            Ok(authn_user) => {
+                request.extensions_mut().insert(authn_user);
             }
             Err(e) => {
-                warn!(error = %e, "Token validation failed");
+                warn!(error = %e, "Token authentication failed");
                 // Continue without authentication
             }
         }
     }
 
-    // 4. Continue to the next middleware/handler without authentication
+    // 3. Continue to the next middleware/handler
     Ok(next.run(request).await)
 }
+
+async fn authenticate_api_key(
+    state: &AppState,
+    api_key: &str,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let (authn_user, authn_api_key) =
+        match state.service.api_key_service.authenticate(api_key).await {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                warn!(error = %e, "API key authentication failed");
+                return next.run(request).await;
+            }
+        };
+
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+
+    request.extensions_mut().insert(authn_user);
+    request.extensions_mut().insert(authn_api_key.clone());
+    let (mut parts, body) = request.into_parts();
+    let Ok(audit) = AuditContext::from_request_parts(&mut parts, &()).await;
+    let response = next.run(Request::from_parts(parts, body)).await;
+
+    state
+        .service
+        .api_key_service
+        .log_request(
+            &audit,
+            &authn_api_key,
+            &method,
+            &path,
+            response.status().as_u16(),
+        )
+        .await;
+    response
+}
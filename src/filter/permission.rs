@@ -0,0 +1,112 @@
+//! Permission guards for routes.
+//!
+//! Guards read the `AuthnUser` resolved by `optional_auth` and reject the request when the
+//! user's type has not been granted the required permission code. OAuth clients without a
+//! user must have passed the route's scope (see `require_scope`) and been granted the
+//! permission on the client itself.
+use crate::{
+    config::auth::{authn_client::AuthnClient, authn_user::AuthnUser},
+    errors::AppError,
+    util::header_util,
+};
+use axum::{
+    extract::Request,
+    http::StatusCode,
+    middleware::Next,
+    response::{Html, IntoResponse, Response},
+};
+use std::{future::Future, pin::Pin};
+use tracing::warn;
+
+type GuardFuture = Pin<Box<dyn Future<Output = Response> + Send>>;
+
+/// Builds a middleware function that only lets requests through when the authenticated user
+/// holds the `code` permission (e.g. `"user:delete"`).
+///
+/// # Example
+/// ```ignore
+/// Router::new()
+///     .route("/{id}", delete(delete_user))
+///     .route_layer(middleware::from_fn(require_permission("user:delete")))
+/// ```
+pub fn require_permission(
+    code: &'static str,
+) -> impl Fn(Request, Next) -> GuardFuture + Clone + Send + Sync + 'static {
+    move |request: Request, next: Next| Box::pin(check_permission(code, request, next))
+}
+
+async fn check_permission(code: &'static str, request: Request, next: Next) -> Response {
+    let is_api_request = header_util::is_api_request(request.headers());
+
+    let Some(authn_user) = request.extensions().get::<AuthnUser>() else {
+        if let Some(client) = request.extensions().get::<AuthnClient>() {
+            if client.scope_checked && client.user.is_none() {
+                if client.permissions.contains(code) {
+                    return next.run(request).await;
+                }
+                warn!(
+                    client_id = client.client_id,
+                    permission = code,
+                    "Permission denied"
+                );
+                return AppError::Forbidden(format!("Missing required permission: {}", code))
+                    .into_response();
+            }
+            warn!(
+                client_id = client.client_id,
+                permission = code,
+                "OAuth token used on a route without a scope"
+            );
+            return AppError::Forbidden(
+                "This endpoint does not accept OAuth access tokens".to_string(),
+            )
+            .into_response();
+        }
+        warn!(
+            permission = code,
+            "Permission check on unauthenticated request"
+        );
+        return AppError::Unauthorized("Authentication required".to_string()).into_response();
+    };
+
+    if !authn_user.permissions.contains(code) {
+        warn!(
+            user_id = authn_user.id,
+            permission = code,
+            "Permission denied"
+        );
+        return if is_api_request {
+            AppError::Forbidden(format!("Missing required permission: {}", code)).into_response()
+        } else {
+            render_forbidden_page()
+        };
+    }
+
+    next.run(request).await
+}
+
+/// Renders an error page for authenticated users lacking a permission
+fn render_forbidden_page() -> Response {
+    let html = r#"
+    <!DOCTYPE html>
+    <html>
+    <head>
+        <title>Forbidden</title>
+        <script src="https://cdn.tailwindcss.com"></script>
+    </head>
+    <body class="bg-gray-100 flex items-center justify-center min-h-screen">
+        <div class="bg-white p-8 rounded-lg shadow-md max-w-md w-full">
+            <div class="text-center">
+                <h1 class="text-2xl font-bold text-red-600 mb-4">Access Denied</h1>
+                <p class="text-gray-700 mb-6">You don't have permission to access this page.</p>
+                <a href="/dashboard" class="bg-blue-600 text-white px-4 py-2 rounded hover:bg-blue-700 transition-colors">
+                    Back to Dashboard
+                </a>
+            </div>
+        </div>
+    </body>
+    </html>
+    "#;
+
+    (StatusCode::FORBIDDEN, Html(html)).into_response()
+}
@@ -0,0 +1,69 @@
+//! OAuth scope guards for routes.
+//!
+//! Requests authenticated with an OAuth access token (`AuthnClient`) only reach routes that
+//! declare a scope, and only when the token was granted it. Session and JWT requests are not
+//! scope-limited and pass straight through.
+use crate::{
+    config::auth::{authn_client::AuthnClient, authn_user::AuthnUser},
+    errors::AppError,
+};
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use std::{future::Future, pin::Pin};
+use tracing::warn;
+
+type GuardFuture = Pin<Box<dyn Future<Output = Response> + Send>>;
+
+/// Builds a middleware function that only lets OAuth access tokens through when they carry
+/// `scope`. Add it after `require_permission` so it runs first; the user a token was issued
+/// for, or the client itself for `client_credentials` tokens, is then checked against the
+/// permission.
+///
+/// # Example
+/// ```ignore
+/// Router::new()
+///     .route("/", get(get_user))
+///     .route_layer(middleware::from_fn(require_permission("user:read")))
+///     .route_layer(middleware::from_fn(require_scope("read")))
+/// ```
+pub fn require_scope(
+    scope: &'static str,
+) -> impl Fn(Request, Next) -> GuardFuture + Clone + Send + Sync + 'static {
+    move |request: Request, next: Next| Box::pin(check_scope(scope, request, next))
+}
+
+async fn check_scope(scope: &'static str, mut request: Request, next: Next) -> Response {
+    let Some(client) = request.extensions_mut().get_mut::<AuthnClient>() else {
+        return next.run(request).await;
+    };
+
+    if !client.has_scope(scope) {
+        warn!(
+            client_id = client.client_id,
+            scope = scope,
+            "OAuth token is missing the required scope"
+        );
+        let mut response =
+            AppError::Forbidden(format!("Missing required scope: {}", scope)).into_response();
+        if let Ok(value) = HeaderValue::from_str(&format!(
+            "Bearer error=\"insufficient_scope\", scope=\"{}\"",
+            scope
+        )) {
+            response
+                .headers_mut()
+                .insert(header::WWW_AUTHENTICATE, value);
+        }
+        return response;
+    }
+
+    // 토큰의 사용자는 scope 확인 후에만 권한 검사와 핸들러에 노출
+    client.scope_checked = true;
+    if let Some(authn_user) = client.user.clone() {
+        request.extensions_mut().insert::<AuthnUser>(authn_user);
+    }
+    next.run(request).await
+}
@@ -1,14 +1,31 @@
 use crate::{
-    config::auth::authn_user::AuthnUser,
+    config::{
+        auth::{audit_context::AuditContext, authn_user::AuthnUser},
+        env_loader::AppConfig,
+    },
     errors::AppError,
-    model::dto::auth::{CurrentUserResponse, LoginRequest, LoginResponse, RegisterRequest},
-    util::cookie_util,
+    filter::{auth, auth::extract_token, deny_api_key, require_permission},
+    model::dto::{
+        api_key::CreateApiKeyRequest,
+        auth::{
+            CurrentUserResponse, ForgotPasswordRequest, LoginRequest, LoginResponse,
+            RegisterRequest, ResetPasswordRequest,
+        },
+        mfa::{
+            MfaChallengeResponse, MfaCodeRequest, MfaLoginRequest, MfaTokenRequest,
+            RecoveryCodesResponse,
+        },
+        session::RevokeSessionsQuery,
+    },
+    service::auth::LoginOutcome,
+    util::{cookie_util, header_util},
     AppState,
 };
 use axum::{
-    extract::{ConnectInfo, Form, Request, State},
+    extract::{ConnectInfo, Form, Path, Query, Request, State},
+    middleware,
     response::{IntoResponse, Response},
-    routing::{get, post},
+    routing::{delete, get, post},
     Json, Router,
 };
 use axum_extra::{headers::UserAgent, TypedHeader};
@@ -16,6 +33,7 @@ use http::{header, header::HeaderValue, HeaderMap, StatusCode};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tracing::info;
+use validator::Validate;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
@@ -24,6 +42,42 @@ pub fn route() -> Router<Arc<AppState>> {
         .route("/me", get(get_auth_me))
         .route("/register", post(post_auth_register))
         .route("/logout", post(post_auth_logout))
+        .route("/password/forgot", post(post_auth_password_forgot))
+        .route("/password/reset", post(post_auth_password_reset))
+        .route("/login/mfa", post(post_auth_login_mfa))
+        .route("/login/mfa/setup", post(post_auth_login_mfa_setup))
+        .route("/login/mfa/enroll", post(post_auth_login_mfa_enroll))
+        .merge(
+            Router::new()
+                .route(
+                    "/sessions",
+                    get(get_auth_sessions).delete(delete_auth_sessions),
+                )
+                .route("/sessions/{id}", delete(delete_auth_session))
+                .route("/mfa", get(get_auth_mfa))
+                .route("/mfa/totp/setup", post(post_auth_mfa_totp_setup))
+                .route("/mfa/totp/enable", post(post_auth_mfa_totp_enable))
+                .route("/mfa/totp/disable", post(post_auth_mfa_totp_disable))
+                .route("/mfa/recovery-codes", post(post_auth_mfa_recovery_codes))
+                .route("/api-keys", get(get_auth_api_keys).post(post_auth_api_key))
+                .route("/api-keys/{id}", delete(delete_auth_api_key))
+                .route_layer(middleware::from_fn(deny_api_key))
+                .route_layer(middleware::from_fn(auth)),
+        )
+        .merge(
+            Router::new()
+                .merge(
+                    Router::new()
+                        .route("/lockouts", get(get_auth_lockouts))
+                        .route_layer(middleware::from_fn(require_permission("user:read"))),
+                )
+                .merge(
+                    Router::new()
+                        .route("/lockouts/{key}", delete(delete_auth_lockout))
+                        .route_layer(middleware::from_fn(require_permission("user:update"))),
+                )
+                .route_layer(middleware::from_fn(auth)),
+        )
 }
 
 async fn post_auth_login(
@@ -34,31 +88,130 @@ async fn post_auth_login(
 ) -> Result<impl IntoResponse, AppError> {
     info!("Login request for user: {}", req.username);
 
-    if req.redirect_url.is_none() {
-        req.redirect_url = Some("/dashboard".to_string());
-    }
+    // 외부 사이트로의 리다이렉트(open redirect) 방지
+    req.redirect_url = req
+        .redirect_url
+        .as_deref()
+        .and_then(header_util::sanitize_redirect_url);
+    let redirect_url = req.redirect_url.clone();
 
     let ip_address = Some(addr.ip().to_string());
     let user_agent = Some(user_agent.to_string());
 
-    let (access_token, refresh_token) = state
+    let outcome = state
         .service
         .auth_service
         .login(&state.config, req, ip_address, user_agent)
         .await?;
 
-    let access_cookie = cookie_util::create_access_token_cookie(&state.config, &access_token);
-    let refresh_cookie = cookie_util::create_refresh_token_cookie(&state.config, &refresh_token);
+    match outcome {
+        LoginOutcome::Authenticated {
+            access_token,
+            refresh_token,
+        } => Ok(login_success_response(
+            &state.config,
+            &access_token,
+            &refresh_token,
+            redirect_url,
+            None,
+        )),
+        LoginOutcome::MfaRequired {
+            mfa_token,
+            enrollment_required,
+        } => {
+            let response = MfaChallengeResponse {
+                mfa_required: true,
+                enrollment_required,
+                mfa_token,
+                expires_in: state.config.token.mfa_exp,
+                redirect_url,
+            };
+            Ok((
+                StatusCode::OK,
+                [(header::CACHE_CONTROL, "no-store")],
+                Json(response),
+            )
+                .into_response())
+        }
+    }
+}
+
+/// Second login step: exchanges the "mfa pending" token and a TOTP or recovery code for the token cookies
+async fn post_auth_login_mfa(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Form(req): Form<MfaLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (access_token, refresh_token) = state
+        .service
+        .auth_service
+        .verify_mfa_login(&state.config, req, &audit)
+        .await?;
+
+    Ok(login_success_response(
+        &state.config,
+        &access_token,
+        &refresh_token,
+        None,
+        None,
+    ))
+}
+
+/// Creates a TOTP secret for a user who must enroll before the login can finish
+async fn post_auth_login_mfa_setup(
+    State(state): State<Arc<AppState>>,
+    Form(req): Form<MfaTokenRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .auth_service
+        .begin_mfa_enrollment(&state.config, req)
+        .await?;
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Confirms the enrollment with a first code and finishes the login
+async fn post_auth_login_mfa_enroll(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Form(req): Form<MfaLoginRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let (access_token, refresh_token, recovery_codes) = state
+        .service
+        .auth_service
+        .complete_mfa_enrollment(&state.config, req, &audit)
+        .await?;
+
+    Ok(login_success_response(
+        &state.config,
+        &access_token,
+        &refresh_token,
+        None,
+        Some(recovery_codes),
+    ))
+}
+
+/// Sets the access/refresh cookies of a completed login
+fn login_success_response(
+    config: &AppConfig,
+    access_token: &str,
+    refresh_token: &str,
+    redirect_url: Option<String>,
+    recovery_codes: Option<Vec<String>>,
+) -> Response {
+    let access_cookie = cookie_util::create_access_token_cookie(config, access_token);
+    let refresh_cookie = cookie_util::create_refresh_token_cookie(config, refresh_token);
 
     let response = LoginResponse {
-        access_token: access_token.clone(),
+        access_token: access_token.to_string(),
         token_type: "Bearer".to_string(),
-        expires_in: state.config.token.access_exp,
-        redirect_url: Some("/dashboard".to_string()),
+        expires_in: config.token.access_exp,
+        redirect_url: Some(redirect_url.unwrap_or_else(|| "/dashboard".to_string())),
+        recovery_codes,
     };
 
     // Set-Cookie 헤더를 명확하게 여러 개 추가
-    let res = Response::builder()
+    Response::builder()
         .status(StatusCode::OK)
         .header(
             header::SET_COOKIE,
@@ -71,34 +224,23 @@ async fn post_auth_login(
         .header("Cache-Control", "no-store")
         .header("Pragma", "no-cache")
         .header("Content-Type", "application/json")
-        .body(serde_json::to_string(&response).unwrap())
-        .unwrap();
-
-    Ok(res)
+        .body(serde_json::to_string(&response).unwrap().into())
+        .unwrap()
 }
 
 async fn post_auth_refresh(
     State(state): State<Arc<AppState>>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    audit: AuditContext,
     request: Request,
 ) -> Result<impl IntoResponse, AppError> {
     let headers = request.headers();
     let refresh_token = cookie_util::get_refresh_token(Some(&state.config), headers)
         .ok_or_else(|| AppError::Unauthorized("No refresh token provided".to_string()))?;
 
-    let ip_address = Some(addr.ip().to_string());
-    let user_agent = Some(user_agent.to_string());
-
     let (access_token, new_refresh_token) = state
         .service
         .auth_service
-        .refresh_access_token(
-            &state.config,
-            refresh_token.to_string(),
-            ip_address,
-            user_agent,
-        )
+        .refresh_access_token(&state.config, refresh_token.to_string(), &audit)
         .await?;
 
     let response = LoginResponse {
@@ -106,6 +248,7 @@ async fn post_auth_refresh(
         token_type: "Bearer".to_string(),
         expires_in: state.config.token.access_exp,
         redirect_url: None,
+        recovery_codes: None,
     };
 
     let access_cookie = cookie_util::create_access_token_cookie(&state.config, &access_token);
@@ -139,27 +282,33 @@ async fn get_auth_me(
 
 async fn post_auth_register(
     State(state): State<Arc<AppState>>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
-    TypedHeader(user_agent): TypedHeader<UserAgent>,
+    audit: AuditContext,
     Form(req): Form<RegisterRequest>,
 ) -> Result<(StatusCode, Json<i64>), AppError> {
     info!("Register request for username: {}", req.username);
 
-    let ip_address = Some(addr.ip().to_string());
-    let user_agent = Some(user_agent.to_string());
-
-    let user_id = state
-        .service
-        .auth_service
-        .register(req, ip_address, user_agent)
-        .await?;
+    let user_id = state.service.auth_service.register(&audit, req).await?;
 
     Ok((StatusCode::CREATED, Json(user_id)))
 }
 
 async fn post_auth_logout(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
+    // 서버에 저장된 refresh token 패밀리 폐기, access token은 폐기 목록에 추가
+    state
+        .service
+        .auth_service
+        .logout(
+            &state.config,
+            extract_token(&headers),
+            cookie_util::get_refresh_token(Some(&state.config), &headers),
+            &audit,
+        )
+        .await?;
+
     // 토큰 쿠키 삭제
     let access_cookie = cookie_util::expire_access_token_cookie(&state.config);
     let refresh_cookie = cookie_util::expire_refresh_token_cookie(&state.config);
@@ -182,3 +331,242 @@ async fn post_auth_logout(
         })),
     ))
 }
+
+async fn post_auth_password_forgot(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Form(req): Form<ForgotPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .auth_service
+        .request_password_reset(&state.config, req, &audit)
+        .await?;
+
+    // 계정 존재 여부와 관계없이 동일한 응답을 반환
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(serde_json::json!({
+            "success": true,
+            "message": "If the email is registered, a password reset link has been sent"
+        })),
+    ))
+}
+
+async fn post_auth_password_reset(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Form(req): Form<ResetPasswordRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .auth_service
+        .reset_password(&audit, req)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "redirect": "/auth/login"
+    })))
+}
+
+/// Lists the current user's signed-in devices
+async fn get_auth_sessions(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .auth_service
+        .list_sessions(
+            authn_user.id,
+            cookie_util::get_refresh_token(Some(&state.config), &headers),
+        )
+        .await?;
+    Ok(Json(response))
+}
+
+/// Signs out one of the current user's devices
+async fn delete_auth_session(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .auth_service
+        .revoke_session(&audit, authn_user.id, &id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Signs out every device of the current user (`?except_current=true` keeps this one)
+async fn delete_auth_sessions(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Query(query): Query<RevokeSessionsQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let keep = if query.except_current {
+        cookie_util::get_refresh_token(Some(&state.config), &headers)
+    } else {
+        None
+    };
+
+    let revoked = state
+        .service
+        .auth_service
+        .revoke_all_sessions(&audit, authn_user.id, keep)
+        .await?;
+
+    Ok(Json(serde_json::json!({
+        "success": true,
+        "revoked": revoked
+    })))
+}
+
+/// Lists the current user's API keys (never the keys themselves)
+async fn get_auth_api_keys(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .api_key_service
+        .list_keys(authn_user.id)
+        .await?;
+    Ok(Json(response))
+}
+
+/// Creates an API key. The key is only returned in this response.
+async fn post_auth_api_key(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Json(req): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .api_key_service
+        .create_key(&audit, &authn_user, req)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    ))
+}
+
+async fn delete_auth_api_key(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .api_key_service
+        .revoke_key(&audit, authn_user.id, id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Lists usernames and IP addresses with failed login attempts on record
+async fn get_auth_lockouts(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.login_throttle_service.list_lockouts().await?;
+    Ok(Json(response))
+}
+
+/// Clears the throttle of a username (`user:<name>`) or IP address (`ip:<address>`)
+async fn delete_auth_lockout(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(key): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .login_throttle_service
+        .clear(&audit, &key)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Second factor state of the current user
+async fn get_auth_mfa(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.mfa_service.get_status(authn_user.id).await?;
+    Ok(Json(response))
+}
+
+/// Starts TOTP enrollment from the profile page
+async fn post_auth_mfa_totp_setup(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .mfa_service
+        .begin_enrollment(authn_user.id)
+        .await?;
+    Ok(([(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+/// Confirms TOTP enrollment with a first code and returns the recovery codes
+async fn post_auth_mfa_totp_enable(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    req.validate()?;
+    let recovery_codes = state
+        .service
+        .mfa_service
+        .confirm_enrollment(&audit, authn_user.id, &req.code)
+        .await?;
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(RecoveryCodesResponse { recovery_codes }),
+    ))
+}
+
+async fn post_auth_mfa_totp_disable(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    req.validate()?;
+    state
+        .service
+        .mfa_service
+        .disable(&audit, authn_user.id, &req.code)
+        .await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Replaces the recovery codes of the current user
+async fn post_auth_mfa_recovery_codes(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Json(req): Json<MfaCodeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    req.validate()?;
+    let recovery_codes = state
+        .service
+        .mfa_service
+        .regenerate_recovery_codes(&audit, authn_user.id, &req.code)
+        .await?;
+    Ok((
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(RecoveryCodesResponse { recovery_codes }),
+    ))
+}
@@ -1,16 +1,14 @@
 use crate::{
-    errors::AppError,
-    filter::{auth, UserId},
-    model::dto::dashboard::DashboardData,
-    AppState,
+    config::auth::authn_user::AuthnUser, errors::AppError, filter::auth,
+    model::dto::dashboard::DashboardData, AppState,
 };
-use axum::{extract::State, middleware, response::Json, routing::get, Extension, Router};
+use axum::{extract::State, middleware, response::Json, routing::get, Router};
 use serde::Serialize;
 use std::sync::Arc;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(|state, auth| api_dashboard_data(state, auth)))
+        .route("/", get(api_dashboard_data))
         .layer(middleware::from_fn(auth))
 }
 
@@ -21,10 +19,9 @@ struct ApiDashboardResponse {
 
 async fn api_dashboard_data(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> Result<Json<ApiDashboardResponse>, AppError> {
-    // Extract the numeric ID from the UserId type
-    let user_id_num = user_id.0;
+    let user_id_num = authn_user.id;
     let dashboard_data = DashboardData::new(&state.pool, user_id_num).await?;
     Ok(Json(ApiDashboardResponse { dashboard_data }))
 }
@@ -1,13 +1,17 @@
 use crate::{
+    config::auth::{audit_context::AuditContext, authn_client::Principal},
     errors::AppError,
-    filter::{auth, UserId},
-    model::dto::history::HistoryListQuery,
+    filter::{auth, require_permission, require_scope},
+    model::dto::history::{HistoryExportParams, HistoryListQuery, HistoryVerifyQuery},
+    service::history_retention::RetentionTrigger,
     AppState,
 };
 use axum::{
-    extract::{Extension, Path, Query, State},
+    body::Body,
+    extract::{Path, Query, State},
+    http::header,
     middleware,
-    response::Json,
+    response::{IntoResponse, Json},
     routing::{delete, get},
     Router,
 };
@@ -17,29 +21,30 @@ use std::sync::Arc;
 use tracing::info;
 use uuid::Uuid;
 
-// Default number of days to keep history
-const DEFAULT_RETENTION_DAYS: i64 = 90;
-
 /// Create router for history endpoints
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route(
-            "/",
-            get(|state, auth, query| list_history(state, auth, query)),
-        )
-        .route(
-            "/recent",
-            get(|state, auth, query| get_recent_history(state, auth, query)),
+        .merge(
+            Router::new()
+                .route("/", get(list_history))
+                .route("/recent", get(get_recent_history))
+                .route("/{id}", get(get_history))
+                .route_layer(middleware::from_fn(require_scope("read"))),
         )
-        .route(
-            "/{id}",
-            get(|state, auth, path| get_history(state, auth, path)),
+        .merge(
+            Router::new()
+                .route("/verify", get(verify_history))
+                .route("/export", get(export_history))
+                .route_layer(middleware::from_fn(require_permission("audit:read")))
+                .route_layer(middleware::from_fn(require_scope("read"))),
         )
-        .route(
-            "/cleanup",
-            delete(|state, auth, params| cleanup_old_logs(state, auth, params)),
+        .merge(
+            Router::new()
+                .route("/cleanup", delete(cleanup_old_logs))
+                .route_layer(middleware::from_fn(require_permission("audit:delete")))
+                .route_layer(middleware::from_fn(require_scope("write"))),
         )
-        .layer(middleware::from_fn(auth))
+        .route_layer(middleware::from_fn(auth))
 }
 
 /// List history with pagination and filtering
@@ -53,31 +58,25 @@ pub fn route() -> Router<Arc<AppState>> {
 /// - `ip_address`: Filter by IP address
 /// - `start_date`: Filter by start date (ISO 8601 format)
 /// - `end_date`: Filter by end date (ISO 8601 format)
-/// - `search`: Search term to filter by (searches in action and details)
+/// - `search`: Full-text search; every word must match (as a prefix) in action, details,
+///   IP address or user agent
 ///
 /// # Permissions
-/// - Admin users can view all history
+/// - Users with `audit:read` can view all history
 /// - Regular users can only view their own history
 async fn list_history(
     State(state): State<Arc<AppState>>,
-    Extension(auth): Extension<Option<UserId>>,
+    principal: Principal,
     Query(mut query): Query<HistoryListQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Check if user is authenticated
-    let user_id =
-        auth.ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
-    info!("Listing history with query: {:?}", query);
+    info!("{} listing history with query: {:?}", principal, query);
 
-    // Check if user has admin role
-    let has_permission = state
-        .service
-        .permission_service
-        .has_permission(user_id.0, "history:read_all")
-        .await?;
+    // Check if user can read every user's history
+    let has_permission = principal.has_permission("audit:read");
 
     // If user doesn't have permission, they can only see their own history
     if !has_permission {
-        query.user_id = Some(user_id.0);
+        query.user_id = principal.user_id();
     }
 
     // Get paginated logs from the service
@@ -88,7 +87,7 @@ async fn list_history(
         .await?;
 
     // Get total count for pagination
-    let total = logs.len() as i64;
+    let total = state.service.history_service.count_history(&query).await?;
 
     // Prepare pagination metadata
     let page = query.get_page();
@@ -128,17 +127,14 @@ async fn list_history(
 /// - `entity_id`: Filter by entity ID
 ///
 /// # Permissions
-/// - Admin users can view all history
+/// - Users with `audit:read` can view all history
 /// - Regular users can only view their own history
 async fn get_recent_history(
     State(state): State<Arc<AppState>>,
-    Extension(auth): Extension<Option<UserId>>,
+    principal: Principal,
     Query(mut query): Query<HistoryListQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Check if user is authenticated
-    let user_id =
-        auth.ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
-    info!("Getting recent history");
+    info!("{} getting recent history", principal);
 
     // Default to 10 most recent logs if no limit is specified
     if query.limit.is_none() {
@@ -148,16 +144,12 @@ async fn get_recent_history(
         query.limit = Some(query.limit.unwrap().min(100));
     }
 
-    // Check if user has admin role
-    let has_permission = state
-        .service
-        .permission_service
-        .has_permission(user_id.0, "history:read_all")
-        .await?;
+    // Check if user can read every user's history
+    let has_permission = principal.has_permission("audit:read");
 
     // If user doesn't have permission, they can only see their own history
     if !has_permission {
-        query.user_id = Some(user_id.0);
+        query.user_id = principal.user_id();
     }
 
     // Get paginated logs from the service
@@ -180,17 +172,14 @@ async fn get_recent_history(
 /// Get a specific history by ID
 ///
 /// # Permissions
-/// - Admin users can view any history
+/// - Users with `audit:read` can view any history
 /// - Regular users can only view their own history
 async fn get_history(
     State(state): State<Arc<AppState>>,
-    Extension(auth): Extension<Option<UserId>>,
+    principal: Principal,
     Path(id): Path<i64>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Check if user is authenticated
-    let user_id =
-        auth.ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
-    info!("Getting history with ID: {}", id);
+    info!("{} getting history with ID: {}", principal, id);
 
     // Get the history
     let log = state
@@ -201,14 +190,10 @@ async fn get_history(
         .ok_or_else(|| AppError::NotFound("History not found".to_string()))?;
 
     // Check if user has permission to read all history
-    let has_permission = state
-        .service
-        .permission_service
-        .has_permission(user_id.0, "history:read_all")
-        .await?;
+    let has_permission = principal.has_permission("audit:read");
 
     // If user doesn't have permission and is not the owner of the log, deny access
-    if !has_permission && log.user_id != Some(user_id.0) {
+    if !has_permission && log.user_id != principal.user_id() {
         return Err(AppError::Forbidden(
             "You don't have permission to view this history".to_string(),
         ));
@@ -225,72 +210,142 @@ async fn get_history(
     Ok(Json(response))
 }
 
-/// Clean up old history
+/// Verify the history hash chain
 ///
-/// Deletes history older than the specified number of days (default: 90 days)
+/// Walks the rows in id order, checking that each one links to the hash of the row before it
+/// and still matches its own hash. Stops at the first broken link.
 ///
 /// # Parameters
-/// - `days`: Number of days of logs to keep (default: 90, min: 1, max: 3650)
+/// - `from_id`: First row to verify (default: the oldest row)
+/// - `to_id`: Last row to verify (default: the newest row)
 ///
 /// # Permissions
-/// - Requires admin privileges
-async fn cleanup_old_logs(
+/// - Requires `audit:read`
+async fn verify_history(
     State(state): State<Arc<AppState>>,
-    Extension(auth): Extension<Option<UserId>>,
-    Query(params): Query<std::collections::HashMap<String, i64>>,
+    principal: Principal,
+    Query(query): Query<HistoryVerifyQuery>,
 ) -> Result<Json<serde_json::Value>, AppError> {
-    // Check if user is authenticated
-    let user_id =
-        auth.ok_or_else(|| AppError::Unauthorized("Authentication required".to_string()))?;
+    info!("{} verifying history chain: {:?}", principal, query);
+
+    let result = state.service.history_service.verify_chain(query).await?;
+
+    let response = json!({
+        "success": true,
+        "data": result,
+        "message": if result.valid { "History chain is intact" } else { "History chain is broken" },
+        "timestamp": Utc::now().to_rfc3339(),
+        "request_id": Uuid::new_v4().to_string(),
+    });
+
+    Ok(Json(response))
+}
+
+/// Export history as a file
+///
+/// Streams every row matching the filters, oldest first, without paging. The export itself
+/// is recorded as a `history_exported` event.
+///
+/// # Parameters
+/// - `format`: `csv` (default), `jsonl` or `ndjson`
+/// - Same filters as the list endpoint (`user_id`, `action`, `entity_id`, `entity_type`,
+///   `ip_address`, `start_date`, `end_date`, `search`); paging parameters are ignored
+///
+/// # Permissions
+/// - Requires `audit:read`
+async fn export_history(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    principal: Principal,
+    Query(query): Query<HistoryListQuery>,
+    Query(params): Query<HistoryExportParams>,
+) -> Result<impl IntoResponse, AppError> {
+    info!(
+        "{} exporting history as {:?}: {:?}",
+        principal, params.format, query
+    );
 
-    // Check if user has permission to delete logs
-    let has_permission = state
+    let format = params.format;
+    let chunks = state
         .service
-        .permission_service
-        .has_permission(user_id.0, "history:delete")
-        .await
-        .unwrap_or(false); // Default to false if there's an error
+        .history_service
+        .export_history(query, format, &audit)
+        .await?;
 
-    if !has_permission {
-        return Err(AppError::Forbidden(
-            "You don't have permission to perform this action".to_string(),
-        ));
-    }
+    let filename = format!(
+        "history-{}.{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        format.extension()
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, format.content_type().to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        Body::from_stream(chunks),
+    ))
+}
 
-    // Get the number of days from query params or use default
+/// Clean up old history
+///
+/// Runs the retention job now: history past its retention period is archived to compressed
+/// JSON Lines and then deleted, with signed checkpoints keeping the remaining chain verifiable.
+/// Per-action policies (`HISTORY_RETENTION_POLICIES`) apply as configured.
+///
+/// # Parameters
+/// - `days`: Days to keep actions without their own policy (default: `HISTORY_RETENTION_DAYS`,
+///   0 keeps them, max: 3650)
+///
+/// # Permissions
+/// - Requires `audit:delete`
+async fn cleanup_old_logs(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    principal: Principal,
+    Query(params): Query<std::collections::HashMap<String, i64>>,
+) -> Result<Json<serde_json::Value>, AppError> {
+    // Get the number of days from query params or use the configured default
     let days = params
         .get("days")
         .copied()
-        .unwrap_or(DEFAULT_RETENTION_DAYS);
+        .unwrap_or(state.config.history_log.retention_days);
 
     // Ensure days is within a reasonable range
-    if days < 1 || days > 3650 {
+    if !(0..=3650).contains(&days) {
         // 10 years max
         return Err(AppError::BadRequest(
-            "Days must be between 1 and 3650".to_string(),
+            "Days must be between 0 and 3650".to_string(),
         ));
     }
 
     info!(
-        "User {} initiated cleanup of history older than {} days",
-        user_id.0, days
+        "{} initiated cleanup of history older than {} days",
+        principal, days
     );
 
-    // Delete old logs
-    let deleted = state.service.history_service.cleanup_old_logs(days).await?;
+    let run = state
+        .service
+        .history_retention_service
+        .run(RetentionTrigger::Manual, Some(days), Some(&audit), None)
+        .await?;
 
     info!(
-        "User {} completed cleanup of {} old history",
-        user_id.0, deleted
+        "{} completed cleanup of {} old history",
+        principal, run.deleted_count
     );
 
     let response = json!({
         "success": true,
         "data": {
-            "deleted": deleted,
+            "deleted": run.deleted_count,
+            "archived": run.archived_count,
             "days": days,
+            "run": run,
         },
-        "message": format!("Successfully deleted {} old history", deleted),
+        "message": format!("Successfully archived and deleted {} old history", run.deleted_count),
         "timestamp": Utc::now().to_rfc3339(),
         "request_id": Uuid::new_v4().to_string(),
     });
@@ -2,6 +2,7 @@ mod auth;
 mod dashboard;
 mod history;
 mod oauth;
+mod oauth_client;
 mod permission;
 mod user;
 mod user_type;
@@ -16,6 +17,7 @@ pub fn route() -> Router<Arc<AppState>> {
         .nest("/dashboard", dashboard::route())
         .nest("/history", history::route())
         .nest("/oauth", oauth::route())
+        .nest("/oauth-client", oauth_client::route())
         .nest("/permission", permission::route())
         .nest("/user", user::route())
         .nest("/user-type", user_type::route())
@@ -1,37 +1,310 @@
 use crate::{
-    model::dto::oauth::{OAuthAuthorizeRequest, OAuthTokenRequest},
+    config::auth::{audit_context::AuditContext, authn_user::AuthnUser},
+    errors::AppError,
+    filter::deny_api_key,
+    model::dto::oauth::{
+        OAuthAuthorizeRequest, OAuthConsentRequest, OAuthErrorResponse, OAuthScopeView,
+        OAuthTokenLookupRequest, OAuthTokenRequest,
+    },
+    service::oauth::{describe_scope, AuthorizeError, OAuthError},
+    util::{header_util, token_util},
     AppState,
 };
 use axum::{
-    extract::{Form, State},
-    response::IntoResponse,
+    extract::{rejection::FormRejection, Form, OriginalUri, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware,
+    response::{Html, IntoResponse, Redirect, Response},
     routing::{get, post},
     Json, Router,
 };
 use std::sync::Arc;
+use tera::Context;
+use tracing::error;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/authorize", get(authorize))
+        .merge(
+            Router::new()
+                .route("/authorize", get(authorize).post(authorize_consent))
+                .route_layer(middleware::from_fn(deny_api_key)),
+        )
         .route("/token", post(token))
+        .route("/introspect", post(introspect))
+        .route("/revoke", post(revoke))
+        .route("/userinfo", get(userinfo).post(userinfo))
+        .route("/jwks", get(jwks))
 }
 
+/// Authorization endpoint (RFC 6749 4.1.1). Signed-out users are sent through the login
+/// page first; signed-in users get a consent screen for the requested scopes.
 pub async fn authorize(
     State(state): State<Arc<AppState>>,
-    Form(req): Form<OAuthAuthorizeRequest>,
-) -> impl IntoResponse {
-    match state.service.oauth_service.authorize(req).await {
-        Ok(resp) => Json(resp).into_response(),
-        Err(e) => (axum::http::StatusCode::BAD_REQUEST, e.to_string()).into_response(),
-    }
+    authn_user: Option<AuthnUser>,
+    OriginalUri(uri): OriginalUri,
+    Query(req): Query<OAuthAuthorizeRequest>,
+) -> Response {
+    let ctx = match state.service.oauth_service.validate_authorize(&req).await {
+        Ok(ctx) => ctx,
+        Err(e) => return authorize_error_response(&state, e),
+    };
+
+    let Some(authn_user) = authn_user else {
+        // 로그인 후 같은 authorize 요청으로 돌아오도록 원래 URL을 전달
+        let query = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("redirect_url", &uri.to_string())
+            .finish();
+        return Redirect::to(&format!("/auth/login?{}", query)).into_response();
+    };
+
+    let consent_token = match token_util::generate_consent_token(
+        &state.config,
+        authn_user.id,
+        &ctx.client.client_id,
+    ) {
+        Ok(token) => token,
+        Err(e) => return e.into_response(),
+    };
+    let scopes: Vec<OAuthScopeView> = ctx
+        .scopes
+        .iter()
+        .map(|scope| OAuthScopeView {
+            name: scope.clone(),
+            description: describe_scope(scope).to_string(),
+        })
+        .collect();
+
+    let mut context = Context::new();
+    context.insert("title", "애플리케이션 접근 승인");
+    context.insert("username", &authn_user.username);
+    context.insert("client_id", &ctx.client.client_id);
+    context.insert("scopes", &scopes);
+    context.insert("request", &ConsentFormView::from(&req));
+    context.insert("scope", &ctx.scopes.join(" "));
+    context.insert("consent_token", &consent_token);
+    render_consent_page(&state, &context, StatusCode::OK)
+}
+
+/// Handles the approve / deny buttons of the consent screen
+pub async fn authorize_consent(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Form(form): Form<OAuthConsentRequest>,
+) -> Result<Response, AppError> {
+    token_util::validate_consent_token(
+        &state.config,
+        &form.consent_token,
+        authn_user.id,
+        &form.client_id,
+    )?;
+
+    let req = form.authorize_request();
+    let result = if form.decision == "approve" {
+        state
+            .service
+            .oauth_service
+            .approve(&audit, &req, authn_user.id)
+            .await
+    } else {
+        state.service.oauth_service.deny(&audit, &req).await
+    };
+
+    Ok(match result {
+        Ok(redirect_url) => Redirect::to(&redirect_url).into_response(),
+        Err(e) => authorize_error_response(&state, e),
+    })
 }
 
+/// Token endpoint (RFC 6749 3.2). Errors use the RFC 6749 5.2 JSON body.
 pub async fn token(
     State(state): State<Arc<AppState>>,
-    Form(req): Form<OAuthTokenRequest>,
-) -> impl IntoResponse {
-    match state.service.oauth_service.token(req).await {
-        Ok(resp) => Json(resp).into_response(),
-        Err(e) => (axum::http::StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+    audit: AuditContext,
+    headers: HeaderMap,
+    form: Result<Form<OAuthTokenRequest>, FormRejection>,
+) -> Response {
+    let Form(req) = match form {
+        Ok(form) => form,
+        Err(e) => return oauth_error_response(form_rejection_error(e)),
+    };
+    // client_secret_basic; client_secret_post credentials arrive in the form body
+    let basic_credentials = header_util::extract_basic_credentials(&headers);
+    match state
+        .service
+        .oauth_service
+        .token(&audit, req, basic_credentials)
+        .await
+    {
+        Ok(resp) => (
+            [
+                (header::CACHE_CONTROL, "no-store"),
+                (header::PRAGMA, "no-cache"),
+            ],
+            Json(resp),
+        )
+            .into_response(),
+        Err(e) => oauth_error_response(e),
+    }
+}
+
+/// Token introspection endpoint (RFC 7662) for resource servers
+pub async fn introspect(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    form: Result<Form<OAuthTokenLookupRequest>, FormRejection>,
+) -> Response {
+    let Form(req) = match form {
+        Ok(form) => form,
+        Err(e) => return oauth_error_response(form_rejection_error(e)),
+    };
+    let basic_credentials = header_util::extract_basic_credentials(&headers);
+    match state
+        .service
+        .oauth_service
+        .introspect(req, basic_credentials)
+        .await
+    {
+        Ok(resp) => ([(header::CACHE_CONTROL, "no-store")], Json(resp)).into_response(),
+        Err(e) => oauth_error_response(e),
+    }
+}
+
+/// Token revocation endpoint (RFC 7009). Answers 200 whether or not the token existed.
+pub async fn revoke(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    headers: HeaderMap,
+    form: Result<Form<OAuthTokenLookupRequest>, FormRejection>,
+) -> Response {
+    let Form(req) = match form {
+        Ok(form) => form,
+        Err(e) => return oauth_error_response(form_rejection_error(e)),
+    };
+    let basic_credentials = header_util::extract_basic_credentials(&headers);
+    match state
+        .service
+        .oauth_service
+        .revoke(&audit, req, basic_credentials)
+        .await
+    {
+        Ok(()) => StatusCode::OK.into_response(),
+        Err(e) => oauth_error_response(e),
+    }
+}
+
+/// OpenID Connect UserInfo endpoint, authenticated with an OAuth access token (RFC 6750)
+pub async fn userinfo(State(state): State<Arc<AppState>>, headers: HeaderMap) -> Response {
+    let result = match header_util::extract_token_from_header(&headers) {
+        Some(access_token) => state.service.oauth_service.userinfo(access_token).await,
+        None => Err(AppError::Unauthorized("invalid_token".to_string())),
+    };
+    match result {
+        Ok(resp) => ([(header::CACHE_CONTROL, "no-store")], Json(resp)).into_response(),
+        Err(e) => {
+            let (status, error) = match e {
+                AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "insufficient_scope"),
+                _ => (StatusCode::UNAUTHORIZED, "invalid_token"),
+            };
+            (
+                status,
+                [(
+                    header::WWW_AUTHENTICATE,
+                    format!("Bearer error=\"{}\"", error),
+                )],
+                Json(serde_json::json!({ "error": error })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Public keys for verifying ID tokens (RFC 7517)
+pub async fn jwks(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
+    let jwks = state
+        .service
+        .oauth_service
+        .jwks()
+        .ok_or_else(|| AppError::NotFound("OpenID Connect is not enabled".to_string()))?;
+    Ok(Json(jwks))
+}
+
+/// Original authorization parameters, carried through the consent form as hidden fields
+#[derive(serde::Serialize)]
+struct ConsentFormView {
+    response_type: String,
+    client_id: String,
+    redirect_uri: String,
+    state: Option<String>,
+    code_challenge: Option<String>,
+    code_challenge_method: String,
+    nonce: Option<String>,
+}
+
+impl From<&OAuthAuthorizeRequest> for ConsentFormView {
+    fn from(req: &OAuthAuthorizeRequest) -> Self {
+        Self {
+            response_type: req.response_type.clone().unwrap_or_default(),
+            client_id: req.client_id.clone(),
+            redirect_uri: req.redirect_uri.clone(),
+            state: req.state.clone(),
+            code_challenge: req.code_challenge.clone(),
+            code_challenge_method: req
+                .code_challenge_method
+                .clone()
+                .unwrap_or_else(|| "plain".to_string()),
+            nonce: req.nonce.clone(),
+        }
+    }
+}
+
+fn authorize_error_response(state: &AppState, error: AuthorizeError) -> Response {
+    match error {
+        AuthorizeError::Redirect(redirect_url) => Redirect::to(&redirect_url).into_response(),
+        AuthorizeError::InvalidClient(message) => {
+            let mut context = Context::new();
+            context.insert("title", "잘못된 요청");
+            context.insert("error", &message);
+            render_consent_page(state, &context, StatusCode::BAD_REQUEST)
+        }
+    }
+}
+
+/// RFC 6749 5.2: 400 with a JSON error body, 401 for failed client authentication
+fn oauth_error_response(e: OAuthError) -> Response {
+    let status = match e.error {
+        "invalid_client" => StatusCode::UNAUTHORIZED,
+        "server_error" => StatusCode::INTERNAL_SERVER_ERROR,
+        _ => StatusCode::BAD_REQUEST,
+    };
+    let body = Json(OAuthErrorResponse {
+        error: e.error.to_string(),
+        error_description: e.description,
+    });
+    let headers = [
+        (header::CACHE_CONTROL, "no-store"),
+        (header::PRAGMA, "no-cache"),
+    ];
+    if status == StatusCode::UNAUTHORIZED {
+        let challenge = [(header::WWW_AUTHENTICATE, "Basic realm=\"oauth\"")];
+        return (status, headers, challenge, body).into_response();
+    }
+    (status, headers, body).into_response()
+}
+
+fn form_rejection_error(e: FormRejection) -> OAuthError {
+    OAuthError::new("invalid_request", e.body_text())
+}
+
+fn render_consent_page(state: &AppState, context: &Context, status: StatusCode) -> Response {
+    match state.tera.render("oauth_consent.html", context) {
+        Ok(html) => (status, Html(html)).into_response(),
+        Err(e) => {
+            error!("Template rendering error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Template rendering error",
+            )
+                .into_response()
+        }
     }
 }
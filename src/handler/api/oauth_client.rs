@@ -0,0 +1,134 @@
+use crate::{
+    config::auth::{audit_context::AuditContext, authn_user::AuthnUser},
+    errors::AppError,
+    filter::{auth, require_permission},
+    model::dto::oauth_client::{
+        CreateOAuthClientRequest, RotateOAuthClientSecretRequest, UpdateOAuthClientRequest,
+    },
+    AppState,
+};
+use axum::{
+    extract::{Json, Path, State},
+    http::{header, StatusCode},
+    middleware,
+    response::IntoResponse,
+    routing::{delete, get, post, put},
+    Router,
+};
+use std::sync::Arc;
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .merge(
+            Router::new()
+                .route("/", get(get_oauth_clients))
+                .route("/{id}", get(get_oauth_client_by_id))
+                .route_layer(middleware::from_fn(require_permission("oauth_client:read"))),
+        )
+        .merge(
+            Router::new()
+                .route("/", post(post_oauth_client))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:create",
+                ))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", put(put_oauth_client))
+                .route("/{id}/secret", post(rotate_oauth_client_secret))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:update",
+                ))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", delete(delete_oauth_client))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:delete",
+                ))),
+        )
+        .route_layer(middleware::from_fn(auth))
+}
+
+async fn get_oauth_clients(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.oauth_client_service.get_clients().await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+async fn get_oauth_client_by_id(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.oauth_client_service.get_client(id).await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Registers a client; the response carries the only copy of its secret
+async fn post_oauth_client(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Json(req): Json<CreateOAuthClientRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .oauth_client_service
+        .create_client(&audit, req, &authn_user)
+        .await?;
+    Ok((
+        StatusCode::CREATED,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    )
+        .into_response())
+}
+
+async fn put_oauth_client(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    authn_user: AuthnUser,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateOAuthClientRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .oauth_client_service
+        .update_client(&audit, id, req, &authn_user)
+        .await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+async fn delete_oauth_client(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    state
+        .service
+        .oauth_client_service
+        .delete_client(&audit, id)
+        .await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+/// Issues a new client secret; the previous one stays valid for `overlap_secs`
+async fn rotate_oauth_client_secret(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+    Json(req): Json<RotateOAuthClientSecretRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .oauth_client_service
+        .rotate_secret(&audit, id, req)
+        .await?;
+    Ok((
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "no-store")],
+        Json(response),
+    )
+        .into_response())
+}
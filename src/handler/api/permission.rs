@@ -1,6 +1,7 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
-    filter::auth,
+    filter::{auth, require_permission, require_scope},
     model::{
         dto::common::ListQueryParams, dto::permission::CreatePermissionRequest,
         dto::permission::UpdatePermissionRequest,
@@ -12,26 +13,42 @@ use axum::{
     http::StatusCode,
     middleware,
     response::IntoResponse,
-    routing::get,
+    routing::{get, post, put},
     Router,
 };
 use std::sync::Arc;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(get_permission).post(post_permission))
-        .route("/{id}", get(get_permission_by_id).put(update_permission))
-        .layer(middleware::from_fn(auth))
+        .merge(
+            Router::new()
+                .route("/", get(get_permission))
+                .route("/{id}", get(get_permission_by_id))
+                .route_layer(middleware::from_fn(require_permission("role:read")))
+                .route_layer(middleware::from_fn(require_scope("read"))),
+        )
+        .merge(
+            Router::new()
+                .route("/", post(post_permission))
+                .route_layer(middleware::from_fn(require_permission("role:create"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", put(update_permission))
+                .route_layer(middleware::from_fn(require_permission("role:update"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 async fn post_permission(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<CreatePermissionRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = state
         .service
         .permission_service
-        .create_permission(req)
+        .create_permission(req, &audit)
         .await?;
     Ok((StatusCode::CREATED, Json(response)).into_response())
 }
@@ -62,13 +79,14 @@ async fn get_permission_by_id(
 
 async fn update_permission(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Path(id): Path<i32>,
     Json(req): Json<UpdatePermissionRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = state
         .service
         .permission_service
-        .update_permission(id, req)
+        .update_permission(id, req, &audit)
         .await?;
     Ok((StatusCode::OK, Json(response)).into_response())
 }
@@ -1,16 +1,19 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
+    filter::{auth, require_permission, require_scope},
     model::dto::{
         common::ListQueryParams,
-        user::{CreateUserRequest, UpdateUserRequest},
+        user::{CreateUserRequest, LockUserRequest, UpdateUserRequest},
     },
     AppState,
 };
 use axum::{
     extract::{Json, Path, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
-    routing::get,
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
@@ -18,18 +21,45 @@ use validator::Validate;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(get_user).post(post_user))
-        .route(
-            "/{id}",
-            get(get_user_by_id).put(update_user).delete(delete_user),
+        .merge(
+            Router::new()
+                .route("/", get(get_user))
+                .route("/{id}", get(get_user_by_id))
+                .route("/{id}/status", get(get_user_status))
+                .route_layer(middleware::from_fn(require_permission("user:read")))
+                .route_layer(middleware::from_fn(require_scope("read"))),
         )
+        .merge(
+            Router::new()
+                .route("/", post(post_user))
+                .route_layer(middleware::from_fn(require_permission("user:create")))
+                .route_layer(middleware::from_fn(require_scope("write"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", put(update_user))
+                .route("/{id}/force-logout", post(post_user_force_logout))
+                .route("/{id}/lock", post(post_user_lock))
+                .route("/{id}/unlock", post(post_user_unlock))
+                .route("/{id}/mfa", delete(delete_user_mfa))
+                .route_layer(middleware::from_fn(require_permission("user:update")))
+                .route_layer(middleware::from_fn(require_scope("write"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", delete(delete_user))
+                .route_layer(middleware::from_fn(require_permission("user:delete")))
+                .route_layer(middleware::from_fn(require_scope("write"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 async fn post_user(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<CreateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
-    let response = state.service.user_service.create_user(req).await?;
+    let response = state.service.user_service.create_user(req, &audit).await?;
     Ok((StatusCode::CREATED, Json(response)).into_response())
 }
 
@@ -51,21 +81,83 @@ async fn get_user_by_id(
 
 async fn update_user(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Path(id): Path<i64>,
     Json(req): Json<UpdateUserRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     req.validate()?;
 
-    state.service.user_service.update_user(id, req).await?;
+    state
+        .service
+        .user_service
+        .update_user(id, req, &audit)
+        .await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
 
 async fn delete_user(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
-    state.service.user_service.delete_user(id).await?;
+    state.service.user_service.delete_user(id, &audit).await?;
 
     Ok(StatusCode::NO_CONTENT)
 }
+
+/// Returns the lock state of a user account
+async fn get_user_status(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.auth_service.get_account_status(id).await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Ends every session of a user immediately
+async fn post_user_force_logout(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.auth_service.force_logout(&audit, id).await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Locks a user account and ends its sessions
+async fn post_user_lock(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+    Json(req): Json<LockUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    req.validate()?;
+
+    let response = state
+        .service
+        .auth_service
+        .lock_user(&audit, id, req.reason)
+        .await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Unlocks a user account
+async fn post_user_unlock(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.auth_service.unlock_user(&audit, id).await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Removes a user's second factor so they can enroll again (lost device and recovery codes)
+async fn delete_user_mfa(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    state.service.mfa_service.reset(&audit, id).await?;
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
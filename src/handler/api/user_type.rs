@@ -1,39 +1,71 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
+    filter::{auth, require_permission, require_scope},
     model::dto::{
         common::ListQueryParams,
-        user_type::{CreateUserTypeRequest, UpdateUserTypeRequest},
+        user_type::{
+            CreateUserTypeRequest, UpdateUserTypePermissionsRequest, UpdateUserTypeRequest,
+        },
     },
     AppState,
 };
 use axum::{
     extract::{Json, Path, Query, State},
     http::StatusCode,
+    middleware,
     response::IntoResponse,
-    routing::get,
+    routing::{delete, get, post, put},
     Router,
 };
 use std::sync::Arc;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .route("/", get(get_user_type).post(post_user_type))
-        .route(
-            "/{id}",
-            get(get_user_type_by_id)
-                .put(put_user_type)
-                .delete(delete_user_type),
+        .merge(
+            Router::new()
+                .route("/", get(get_user_type))
+                .route("/{id}", get(get_user_type_by_id))
+                .route("/{id}/permissions", get(get_user_type_permissions))
+                .route_layer(middleware::from_fn(require_permission("role:read")))
+                .route_layer(middleware::from_fn(require_scope("read"))),
         )
+        .merge(
+            Router::new()
+                .route("/", post(post_user_type))
+                .route_layer(middleware::from_fn(require_permission("role:create"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", put(put_user_type))
+                .route_layer(middleware::from_fn(require_permission("role:update"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", delete(delete_user_type))
+                .route_layer(middleware::from_fn(require_permission("role:delete"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}/permissions", put(put_user_type_permissions))
+                .route(
+                    "/{id}/permissions/{permission_id}",
+                    post(grant_user_type_permission).delete(revoke_user_type_permission),
+                )
+                .route_layer(middleware::from_fn(require_permission("role:assign"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 async fn post_user_type(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Json(req): Json<CreateUserTypeRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = state
         .service
         .user_type_service
-        .create_user_type(req)
+        .create_user_type(req, &audit)
         .await?;
     Ok((StatusCode::CREATED, Json(response)).into_response())
 }
@@ -64,21 +96,79 @@ async fn get_user_type_by_id(
 
 async fn put_user_type(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Path(id): Path<i64>,
     Json(req): Json<UpdateUserTypeRequest>,
 ) -> Result<impl IntoResponse, AppError> {
     let response = state
         .service
         .user_type_service
-        .update_user_type(id, req)
+        .update_user_type(id, req, &audit)
         .await?;
     Ok((StatusCode::OK, Json(response)).into_response())
 }
 
 async fn delete_user_type(
     State(state): State<Arc<AppState>>,
+    audit: AuditContext,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
-    state.service.user_type_service.delete_user_type(id).await?;
+    state
+        .service
+        .user_type_service
+        .delete_user_type(id, &audit)
+        .await?;
     Ok(StatusCode::NO_CONTENT.into_response())
 }
+
+/// Lists the permissions assigned to a user type
+async fn get_user_type_permissions(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<i64>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state.service.user_type_service.get_permissions(id).await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Replaces every permission assigned to a user type
+async fn put_user_type_permissions(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+    Json(req): Json<UpdateUserTypePermissionsRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .user_type_service
+        .replace_permissions(id, req.permission_ids, &audit)
+        .await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Grants a single permission to a user type
+async fn grant_user_type_permission(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path((id, permission_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .user_type_service
+        .grant_permission(id, permission_id, &audit)
+        .await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
+
+/// Revokes a single permission from a user type
+async fn revoke_user_type_permission(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path((id, permission_id)): Path<(i64, i64)>,
+) -> Result<impl IntoResponse, AppError> {
+    let response = state
+        .service
+        .user_type_service
+        .revoke_permission(id, permission_id, &audit)
+        .await?;
+    Ok((StatusCode::OK, Json(response)).into_response())
+}
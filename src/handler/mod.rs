@@ -1,5 +1,6 @@
 mod api;
 mod view;
+mod well_known;
 
 use crate::AppState;
 use axum::Router;
@@ -10,5 +11,6 @@ pub fn route() -> Router<Arc<AppState>> {
     Router::new()
         .nest_service("/static", ServeDir::new("static"))
         .nest("/api", api::route())
+        .nest("/.well-known", well_known::route())
         .merge(view::route())
 }
@@ -1,8 +1,9 @@
+use crate::util::header_util;
 use crate::AppState;
 use axum::{
     extract::{Query, State},
     http::StatusCode,
-    response::{Html, IntoResponse, Redirect},
+    response::{Html, IntoResponse, Redirect, Response},
     routing::get,
     Form, Router,
 };
@@ -16,6 +17,8 @@ pub fn route() -> Router<Arc<AppState>> {
     Router::new()
         .route("/login", get(login_page).post(login_handler))
         .route("/register", get(register_page))
+        .route("/password/forgot", get(password_forgot_page))
+        .route("/password/reset", get(password_reset_page))
 }
 
 async fn login_page(
@@ -30,6 +33,14 @@ async fn login_page(
         context.insert("error", error_message);
     }
 
+    // 로그인 후 돌아갈 경로 (예: OAuth 인가 화면), 사이트 내부 경로만 허용
+    if let Some(redirect_url) = query
+        .get("redirect_url")
+        .and_then(|url| header_util::sanitize_redirect_url(url))
+    {
+        context.insert("redirect_url", &redirect_url);
+    }
+
     match state.tera.render("login.html", &context) {
         Ok(s) => Html(s).into_response(),
         Err(e) => {
@@ -72,6 +83,43 @@ async fn register_page(State(state): State<Arc<AppState>>) -> impl IntoResponse
     }
 }
 
+async fn password_forgot_page(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut context = Context::new();
+    context.insert("title", "비밀번호 찾기");
+    context.insert("active_page", "password_forgot");
+
+    render_auth_page(&state, "password_forgot.html", &context)
+}
+
+async fn password_reset_page(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HashMap<String, String>>,
+) -> impl IntoResponse {
+    let mut context = Context::new();
+    context.insert("title", "비밀번호 재설정");
+    context.insert("active_page", "password_reset");
+    context.insert(
+        "token",
+        query.get("token").map(String::as_str).unwrap_or(""),
+    );
+
+    render_auth_page(&state, "password_reset.html", &context)
+}
+
+fn render_auth_page(state: &AppState, template: &str, context: &Context) -> Response {
+    match state.tera.render(template, context) {
+        Ok(s) => Html(s).into_response(),
+        Err(e) => {
+            error!("Template rendering error: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Template rendering error",
+            )
+                .into_response()
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct LoginForm {
     _username: String,
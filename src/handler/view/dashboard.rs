@@ -1,11 +1,12 @@
 use crate::{
+    config::auth::authn_user::AuthnUser,
     errors::AppError,
-    filter::{auth, UserId},
+    filter::auth,
     model::dto::{dashboard::DashboardData, user::UserResponse},
     AppState,
 };
 use axum::{
-    extract::{Extension, Query, State},
+    extract::{Query, State},
     middleware,
     response::{Html, IntoResponse},
     routing::get,
@@ -17,8 +18,8 @@ use tera::Context;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
         .route("/", get(dashboard_page))
+        .route_layer(middleware::from_fn(auth))
 }
 
 #[derive(Serialize)]
@@ -51,26 +52,26 @@ pub struct DashboardQuery {
 
 pub async fn dashboard_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Query(query): Query<DashboardQuery>,
 ) -> Result<impl IntoResponse, AppError> {
     // Get current user data
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
 
     // Get dashboard data with optional time range
     let range = query.range.as_deref().unwrap_or("day");
-    let dashboard_data = DashboardData::with_range(&state.pool, user_id.0, range).await?;
+    let dashboard_data = DashboardData::with_range(&state.pool, authn_user.id, range).await?;
 
     // Prepare template context
     let context = TemplateContext {
         title: "대시보드",
         active_page: "dashboard",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         dashboard_data,
     };
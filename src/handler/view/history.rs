@@ -1,6 +1,7 @@
 use crate::{
+    config::auth::authn_user::AuthnUser,
     errors::AppError,
-    filter::{auth, UserId},
+    filter::{auth, require_permission},
     model::dto::{
         history::{HistoryListQuery, HistoryResponse},
         user::UserResponse,
@@ -13,7 +14,6 @@ use axum::{
     middleware,
     response::IntoResponse,
     routing::{get, Router},
-    Extension,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -22,10 +22,11 @@ use tracing::{debug, error, info};
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
         .route("/recent", get(recent_history_page))
         .route("/{id}", get(history_detail_page))
         .route("/", get(history_page))
+        .route_layer(middleware::from_fn(require_permission("audit:read")))
+        .route_layer(middleware::from_fn(auth))
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,6 +38,7 @@ pub struct HistoryQueryParams {
     pub _entity_type: Option<String>,
     start_date: Option<String>,
     end_date: Option<String>,
+    search: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -51,6 +53,7 @@ struct TemplateContext {
     per_page: i64,
     total_pages: i64,
     id: Option<i64>,
+    search: Option<String>,
     // Pre-calculated pagination values
     start_item: i64,
     end_item: i64,
@@ -58,14 +61,22 @@ struct TemplateContext {
 
 async fn history_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Query(params): Query<HistoryQueryParams>,
 ) -> Result<impl IntoResponse, AppError> {
-    info!("Starting history page handler for user_id: {}", user_id.0);
+    info!(
+        "Starting history page handler for user_id: {}",
+        authn_user.id
+    );
     debug!("Query params: {:?}", params);
 
     // Get current user data
-    let _current_user = match state.service.user_service.get_user_by_id(user_id.0).await {
+    let _current_user = match state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         Ok(user) => {
             debug!("Found user: {:?}", user);
             Some(user)
@@ -112,8 +123,10 @@ async fn history_page(
         action: params.action,
         entity_id: None,
         entity_type: None,
+        ip_address: None,
         start_date,
         end_date: end_date.map(|dt| dt + chrono::Duration::days(1)), // Include the entire end date
+        search: params.search.clone(),
     };
 
     debug!("Fetching history with query: {:?}", query);
@@ -187,7 +200,7 @@ async fn history_page(
                 item.details = Some(serde_json::json!({}));
             }
             // Convert created_at to KST (UTC+9)
-            item.created_at = item.created_at + chrono::Duration::hours(9);
+            item.created_at += chrono::Duration::hours(9);
             item
         })
         .collect();
@@ -210,7 +223,12 @@ async fn history_page(
     };
 
     // Get current user information
-    let current_user = match state.service.user_service.get_user_by_id(user_id.0).await {
+    let current_user = match state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         Ok(user) => Some(user),
         Err(e) => {
             error!("Failed to get current user: {}", e);
@@ -224,7 +242,7 @@ async fn history_page(
     let template_context = TemplateContext {
         title: "활동 로그",
         active_page: "history",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         history: Some(history_items),
         total,
@@ -232,6 +250,7 @@ async fn history_page(
         per_page,
         total_pages: total_pages.max(1),
         id: None,
+        search: params.search,
         start_item,
         end_item,
     };
@@ -251,13 +270,13 @@ async fn history_page(
 /// Recent history page
 async fn recent_history_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> Result<impl IntoResponse, AppError> {
     // Get current user data
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
     // Create a simple query to get recent history
@@ -269,8 +288,10 @@ async fn recent_history_page(
         action: None,
         entity_type: None,
         entity_id: None,
+        ip_address: None,
         start_date: None,
         end_date: None,
+        search: None,
         offset: None,
     };
 
@@ -284,7 +305,7 @@ async fn recent_history_page(
     let context = TemplateContext {
         title: "최근 활동",
         active_page: "history",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         history: Some(history),
         total,
@@ -292,6 +313,7 @@ async fn recent_history_page(
         per_page: 10,
         total_pages: 1,
         id: None,
+        search: None,
         start_item: 1,
         end_item: std::cmp::min(10, total),
     };
@@ -302,14 +324,14 @@ async fn recent_history_page(
 /// History detail page
 async fn history_detail_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Path(id): Path<i64>,
 ) -> Result<impl IntoResponse, AppError> {
     // Get current user data
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
     // Get the history by ID
@@ -321,7 +343,7 @@ async fn history_detail_page(
     let context = TemplateContext {
         title: "활동 상세",
         active_page: "history",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         history: None,
         total: 1,
@@ -331,6 +353,7 @@ async fn history_detail_page(
         per_page: 1,
         total_pages: 1,
         id: Some(id),
+        search: None,
     };
 
     render_template(&state, "history_detail.html", context).await
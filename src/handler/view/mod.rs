@@ -1,6 +1,7 @@
 pub mod auth;
 pub mod dashboard;
 pub mod history;
+pub mod oauth_client;
 pub mod permission;
 pub mod profile;
 pub mod settings;
@@ -24,6 +25,7 @@ pub fn route() -> Router<Arc<AppState>> {
         .nest("/auth", auth::route())
         .nest("/dashboard", dashboard::route())
         .nest("/history", history::route())
+        .nest("/oauth-clients", oauth_client::route())
         .nest("/permission", permission::route())
         .nest("/profile", profile::route())
         .nest("/settings", settings::route())
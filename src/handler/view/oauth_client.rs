@@ -0,0 +1,168 @@
+use crate::{
+    config::auth::{audit_context::AuditContext, authn_user::AuthnUser},
+    errors::AppError,
+    filter::{auth, require_permission},
+    model::dto::{oauth_client::OAuthClientResponse, user::UserResponse},
+    service::oauth_client::SUPPORTED_GRANT_TYPES,
+    util::template_util,
+    AppState,
+};
+use axum::{
+    extract::{Path, State},
+    middleware,
+    response::{IntoResponse, Redirect, Response},
+    routing::{delete, get},
+    Router,
+};
+use serde::Serialize;
+use std::sync::Arc;
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .merge(
+            Router::new()
+                .route("/", get(oauth_client_list))
+                .route_layer(middleware::from_fn(require_permission("oauth_client:read"))),
+        )
+        .merge(
+            Router::new()
+                .route("/create", get(oauth_client_create_page))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:create",
+                ))),
+        )
+        .merge(
+            Router::new()
+                .route("/edit/{id}", get(oauth_client_edit_page))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:update",
+                ))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", delete(delete_oauth_client))
+                .route_layer(middleware::from_fn(require_permission(
+                    "oauth_client:delete",
+                ))),
+        )
+        .route_layer(middleware::from_fn(auth))
+}
+
+#[derive(Debug, Serialize)]
+pub struct TemplateContext {
+    title: &'static str,
+    active_page: &'static str,
+    user_id: i64,
+    current_user: Option<UserResponse>,
+    oauth_clients: Option<Vec<OAuthClientResponse>>,
+    oauth_client: Option<OAuthClientResponse>,
+    grant_types: Vec<&'static str>,
+    /// Permissions that can be granted to the client: the current user's and the client's own
+    permissions: Vec<String>,
+}
+
+pub async fn oauth_client_list(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+) -> Result<Response, AppError> {
+    let current_user = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+        .ok();
+    let oauth_clients = state.service.oauth_client_service.get_clients().await?;
+
+    let context = TemplateContext {
+        title: "OAuth 클라이언트 관리",
+        active_page: "oauth_clients",
+        user_id: authn_user.id,
+        current_user,
+        oauth_clients: Some(oauth_clients),
+        oauth_client: None,
+        grant_types: SUPPORTED_GRANT_TYPES.to_vec(),
+        permissions: Vec::new(),
+    };
+
+    template_util::render_template(&state, "oauth_client.html", context).await
+}
+
+pub async fn oauth_client_create_page(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+) -> Result<Response, AppError> {
+    let current_user = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+        .ok();
+
+    let context = TemplateContext {
+        title: "OAuth 클라이언트 등록",
+        active_page: "oauth_clients",
+        user_id: authn_user.id,
+        current_user,
+        oauth_clients: None,
+        oauth_client: None,
+        grant_types: SUPPORTED_GRANT_TYPES.to_vec(),
+        permissions: grantable_permissions(&authn_user, None),
+    };
+
+    template_util::render_template(&state, "oauth_client_form.html", context).await
+}
+
+pub async fn oauth_client_edit_page(
+    State(state): State<Arc<AppState>>,
+    authn_user: AuthnUser,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    let current_user = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+        .ok();
+    let oauth_client = state.service.oauth_client_service.get_client(id).await?;
+    let permissions = grantable_permissions(&authn_user, Some(&oauth_client));
+
+    let context = TemplateContext {
+        title: "OAuth 클라이언트 수정",
+        active_page: "oauth_clients",
+        user_id: authn_user.id,
+        current_user,
+        oauth_clients: None,
+        oauth_client: Some(oauth_client),
+        grant_types: SUPPORTED_GRANT_TYPES.to_vec(),
+        permissions,
+    };
+
+    template_util::render_template(&state, "oauth_client_form.html", context).await
+}
+
+pub async fn delete_oauth_client(
+    State(state): State<Arc<AppState>>,
+    audit: AuditContext,
+    Path(id): Path<i64>,
+) -> Result<Response, AppError> {
+    state
+        .service
+        .oauth_client_service
+        .delete_client(&audit, id)
+        .await?;
+    Ok(Redirect::to("/oauth-clients").into_response())
+}
+
+fn grantable_permissions(
+    authn_user: &AuthnUser,
+    oauth_client: Option<&OAuthClientResponse>,
+) -> Vec<String> {
+    let mut permissions: Vec<String> = authn_user.permissions.iter().cloned().collect();
+    for code in oauth_client.into_iter().flat_map(|c| &c.permissions) {
+        if !permissions.contains(code) {
+            permissions.push(code.clone());
+        }
+    }
+    permissions.sort();
+    permissions
+}
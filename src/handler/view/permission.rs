@@ -1,38 +1,56 @@
-use crate::{filter::auth, filter::UserId, AppState};
+use crate::{
+    config::auth::authn_user::AuthnUser,
+    filter::{auth, require_permission},
+    AppState,
+};
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Path, State},
     http::{HeaderMap, StatusCode},
     middleware,
     response::{Html, IntoResponse},
     routing::get,
     Json, Router,
 };
-use serde::Deserialize;
 use serde_json::json;
 use std::sync::Arc;
 use tera::Context;
 use tracing::error;
-use validator::Validate;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
-        .route("/", get(permissions_page))
-        .route("/new", get(permission_create_page))
-        .route("/edit/{id}", get(permission_edit_page))
+        .route(
+            "/",
+            get(permissions_page).route_layer(middleware::from_fn(require_permission("role:read"))),
+        )
+        .route(
+            "/new",
+            get(permission_create_page)
+                .route_layer(middleware::from_fn(require_permission("role:create"))),
+        )
+        .route(
+            "/edit/{id}",
+            get(permission_edit_page)
+                .route_layer(middleware::from_fn(require_permission("role:update"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 async fn permissions_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "권한 관리");
     context.insert("active_page", "permissions");
-    context.insert("user_id", &user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -51,15 +69,20 @@ async fn permissions_page(
 
 async fn permission_create_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "권한 추가");
     context.insert("active_page", "permissions");
-    context.insert("user_id", &user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -86,17 +109,22 @@ async fn permission_create_page(
 
 async fn permission_edit_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Path(permission_id): Path<i32>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "권한 수정");
     context.insert("active_page", "permissions");
-    context.insert("user_id", &user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -155,16 +183,3 @@ async fn permission_edit_page(
         }
     }
 }
-
-#[derive(Debug, Validate, Deserialize)]
-pub struct CreatePermissionForm {
-    #[validate(length(min = 1, message = "Code is required"))]
-    pub code: String,
-    pub _name: Option<String>,
-}
-
-#[derive(Debug, Validate, Deserialize)]
-pub struct UpdatePermissionForm {
-    pub _code: Option<String>,
-    pub _name: Option<String>,
-}
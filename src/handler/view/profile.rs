@@ -1,8 +1,17 @@
 use crate::{
-    errors::AppError, filter::auth, filter::UserId, model::dto::user::UserResponse, AppState,
+    config::auth::authn_user::AuthnUser,
+    errors::AppError,
+    filter::auth,
+    model::dto::{
+        api_key::ApiKeyResponse, mfa::MfaStatusResponse, session::SessionResponse,
+        user::UserResponse,
+    },
+    util::cookie_util,
+    AppState,
 };
 use axum::{
-    extract::{Extension, State},
+    extract::State,
+    http::HeaderMap,
     middleware,
     response::{Html, IntoResponse},
     routing::get,
@@ -15,8 +24,8 @@ use tracing::error;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
         .route("/", get(profile_page))
+        .route_layer(middleware::from_fn(auth))
 }
 
 #[derive(Debug, Serialize)]
@@ -25,6 +34,11 @@ struct TemplateContext {
     active_page: &'static str,
     user_id: i64,
     current_user: Option<UserResponse>,
+    sessions: Vec<SessionResponse>,
+    mfa: MfaStatusResponse,
+    api_keys: Vec<ApiKeyResponse>,
+    /// Permissions the user can grant to a new API key
+    permissions: Vec<String>,
 }
 
 impl From<TemplateContext> for Context {
@@ -36,27 +50,55 @@ impl From<TemplateContext> for Context {
         if let Some(user) = &ctx.current_user {
             context.insert("current_user", user);
         }
+        context.insert("sessions", &ctx.sessions);
+        context.insert("mfa", &ctx.mfa);
+        context.insert("api_keys", &ctx.api_keys);
+        context.insert("permissions", &ctx.permissions);
         context
     }
 }
 
 async fn profile_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
+    headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
     // Get current user data
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
 
+    let sessions = state
+        .service
+        .auth_service
+        .list_sessions(
+            authn_user.id,
+            cookie_util::get_refresh_token(Some(&state.config), &headers),
+        )
+        .await?;
+
+    let mfa = state.service.mfa_service.get_status(authn_user.id).await?;
+
+    let api_keys = state
+        .service
+        .api_key_service
+        .list_keys(authn_user.id)
+        .await?;
+    let mut permissions: Vec<String> = authn_user.permissions.iter().cloned().collect();
+    permissions.sort();
+
     let context = TemplateContext {
         title: "프로필",
         active_page: "profile",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
+        sessions,
+        mfa,
+        api_keys,
+        permissions,
     };
 
     match state.tera.render("profile.html", &Context::from(context)) {
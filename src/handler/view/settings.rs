@@ -1,19 +1,19 @@
-use crate::{errors::AppError, filter::auth, filter::UserId, AppState};
-use axum::{
-    extract::{Extension, State},
-    middleware,
-    response::IntoResponse,
-    routing::get,
-    Router,
+use crate::{
+    config::auth::authn_user::AuthnUser,
+    errors::AppError,
+    filter::{auth, require_permission},
+    AppState,
 };
+use axum::{extract::State, middleware, response::IntoResponse, routing::get, Router};
 use std::sync::Arc;
 use tera::Context;
 use tracing::{debug, error, info};
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
         .route("/", get(settings_page))
+        .route_layer(middleware::from_fn(require_permission("settings:read")))
+        .route_layer(middleware::from_fn(auth))
 }
 
 use crate::model::dto::user::UserResponse;
@@ -41,12 +41,20 @@ impl From<TemplateContext> for Context {
 
 async fn settings_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> Result<impl IntoResponse, AppError> {
-    info!("Starting settings page handler for user_id: {}", user_id.0);
+    info!(
+        "Starting settings page handler for user_id: {}",
+        authn_user.id
+    );
 
     // Get current user data with detailed error handling
-    let current_user = match state.service.user_service.get_user_by_id(user_id.0).await {
+    let current_user = match state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         Ok(user) => {
             debug!("Found user: {:?}", user);
             Some(user)
@@ -63,7 +71,7 @@ async fn settings_page(
     // Add basic fields
     tera_context.insert("title", "설정");
     tera_context.insert("active_page", "settings");
-    tera_context.insert("user_id", &user_id.0);
+    tera_context.insert("user_id", &authn_user.id);
 
     // Add current user if available
     if let Some(user) = &current_user {
@@ -89,7 +97,7 @@ async fn settings_page(
     debug!("Available templates: {:?}", template_names);
 
     // Check if template exists
-    if !template_names.iter().any(|&name| name == "settings.html") {
+    if !template_names.contains(&"settings.html") {
         error!("Template 'settings.html' not found in the loaded templates");
         return Err(AppError::InternalServerError(
             "Template not found".to_string(),
@@ -1,41 +1,55 @@
 use crate::{
-    filter::{auth, UserId},
+    config::auth::authn_user::AuthnUser,
+    filter::{auth, require_permission},
     model::dto::common::ListQueryParams,
     AppState,
 };
 use axum::{
-    extract::{Extension, Path, State},
+    extract::{Path, State},
     http::StatusCode,
     middleware,
     response::{Html, IntoResponse},
     routing::get,
     Router,
 };
-use serde::Deserialize;
 use std::{error::Error, sync::Arc};
 use tera::Context;
 use tracing::error;
-use validator::Validate;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
-        .route("/", get(users_page))
-        .route("/new", get(user_create_page))
-        .route("/edit/{id}", get(user_edit_page))
+        .route(
+            "/",
+            get(users_page).route_layer(middleware::from_fn(require_permission("user:read"))),
+        )
+        .route(
+            "/new",
+            get(user_create_page)
+                .route_layer(middleware::from_fn(require_permission("user:create"))),
+        )
+        .route(
+            "/edit/{id}",
+            get(user_edit_page).route_layer(middleware::from_fn(require_permission("user:update"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 async fn users_page(
     State(state): State<Arc<AppState>>,
-    Extension(_user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "사용자 관리");
     context.insert("active_page", "users");
-    context.insert("user_id", &_user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(_user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -68,15 +82,20 @@ async fn users_page(
 
 async fn user_create_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "사용자 추가");
     context.insert("active_page", "users");
-    context.insert("user_id", &user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -111,16 +130,21 @@ async fn user_create_page(
 
 async fn user_edit_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Path(id): Path<i64>,
 ) -> impl IntoResponse {
     let mut context = Context::new();
     context.insert("title", "사용자 수정");
     context.insert("active_page", "users");
-    context.insert("user_id", &user_id.0);
+    context.insert("user_id", &authn_user.id);
 
     // Add current user info for the template
-    if let Ok(current_user) = state.service.user_service.get_user_by_id(user_id.0).await {
+    if let Ok(current_user) = state
+        .service
+        .user_service
+        .get_user_by_id(authn_user.id)
+        .await
+    {
         context.insert("current_user", &current_user);
     }
 
@@ -173,35 +197,3 @@ async fn user_edit_page(
         }
     }
 }
-#[derive(Debug, Deserialize)]
-pub struct UserFormData {
-    _username: String,
-    _email: Option<String>,
-    _password: Option<String>,
-    _user_type_id: i64,
-    _is_active: bool,
-}
-
-#[derive(Debug, Validate, Deserialize)]
-pub struct CreateUserForm {
-    #[validate(length(min = 3, message = "Username must be at least 3 characters long"))]
-    username: String,
-    #[validate(email(message = "Invalid email format"))]
-    email: String,
-    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
-    password: String,
-    _user_type_id: i64,
-    _is_active: Option<bool>,
-}
-
-#[derive(Debug, Validate, Deserialize)]
-pub struct UpdateUserForm {
-    #[validate(length(min = 3, message = "Username must be at least 3 characters long"))]
-    username: Option<String>,
-    #[validate(email(message = "Invalid email format"))]
-    email: Option<String>,
-    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
-    password: Option<String>,
-    _user_type_id: Option<i64>,
-    _is_active: Option<bool>,
-}
@@ -1,9 +1,11 @@
 use crate::{
+    config::auth::{audit_context::AuditContext, authn_user::AuthnUser},
     errors::AppError,
-    filter::{auth, UserId},
+    filter::{auth, require_permission},
     model::dto::{
-        common::ListQueryParams, user::UserResponse, user_type::CreateUserTypeRequest,
-        user_type::UpdateUserTypeRequest, user_type::UserTypeResponse,
+        common::ListQueryParams, permission::PermissionResponse, user::UserResponse,
+        user_type::CreateUserTypeRequest, user_type::UpdateUserTypeRequest,
+        user_type::UserTypeResponse,
     },
     util::template_util,
     AppState,
@@ -12,8 +14,8 @@ use axum::{
     extract::{Form, Path, Query, State},
     middleware,
     response::{IntoResponse, Redirect, Response},
-    routing::{delete, get},
-    Extension, Router,
+    routing::{delete, get, post},
+    Router,
 };
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
@@ -21,14 +23,31 @@ use tera::Context;
 
 pub fn route() -> Router<Arc<AppState>> {
     Router::new()
-        .layer(middleware::from_fn(auth))
-        .route("/", get(user_type_list).post(create_user_type))
-        .route("/create", get(user_type_create_page))
-        .route(
-            "/edit/{id}",
-            get(user_type_edit_page).post(update_user_type),
+        .merge(
+            Router::new()
+                .route("/", get(user_type_list))
+                .route_layer(middleware::from_fn(require_permission("role:read"))),
         )
-        .route("/{id}", delete(delete_user_type))
+        .merge(
+            Router::new()
+                .route("/", post(create_user_type))
+                .route("/create", get(user_type_create_page))
+                .route_layer(middleware::from_fn(require_permission("role:create"))),
+        )
+        .merge(
+            Router::new()
+                .route(
+                    "/edit/{id}",
+                    get(user_type_edit_page).post(update_user_type),
+                )
+                .route_layer(middleware::from_fn(require_permission("role:update"))),
+        )
+        .merge(
+            Router::new()
+                .route("/{id}", delete(delete_user_type))
+                .route_layer(middleware::from_fn(require_permission("role:delete"))),
+        )
+        .route_layer(middleware::from_fn(auth))
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,6 +76,15 @@ pub struct TemplateContext {
     sort_by: Option<String>,
     order: Option<String>,
     status: Option<String>,
+    permission_groups: Vec<PermissionGroup>,
+    assigned_permission_ids: Vec<i64>,
+}
+
+/// Permissions sharing a category, rendered as one row group of the assignment matrix
+#[derive(Debug, Serialize)]
+pub struct PermissionGroup {
+    category: String,
+    permissions: Vec<PermissionResponse>,
 }
 
 impl From<TemplateContext> for Context {
@@ -76,6 +104,8 @@ impl From<TemplateContext> for Context {
         context.insert("sort_by", &ctx.sort_by);
         context.insert("order", &ctx.order);
         context.insert("status", &ctx.status);
+        context.insert("permission_groups", &ctx.permission_groups);
+        context.insert("assigned_permission_ids", &ctx.assigned_permission_ids);
         context
     }
 }
@@ -84,14 +114,14 @@ impl From<TemplateContext> for Context {
 
 pub async fn user_type_list(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Query(query): Query<UserTypeListQuery>,
 ) -> Result<Response, AppError> {
     // Get current user info for the template
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
 
@@ -125,7 +155,7 @@ pub async fn user_type_list(
     let context = TemplateContext {
         title: "사용자 유형 관리",
         active_page: "user_types",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         user_types: Some(user_types),
         user_type: None,
@@ -137,6 +167,8 @@ pub async fn user_type_list(
         sort_by: query.sort_by,
         order: query.order,
         status: query.status,
+        permission_groups: Vec::new(),
+        assigned_permission_ids: Vec::new(),
     };
 
     template_util::render_template(&state, "user_type.html", context).await
@@ -144,19 +176,19 @@ pub async fn user_type_list(
 
 pub async fn user_type_create_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
 ) -> Result<Response, AppError> {
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
 
     let context = TemplateContext {
         title: "사용자 유형 생성",
         active_page: "user_types",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         user_types: None,
         user_type: None,
@@ -168,6 +200,8 @@ pub async fn user_type_create_page(
         sort_by: None,
         order: None,
         status: None,
+        permission_groups: Vec::new(),
+        assigned_permission_ids: Vec::new(),
     };
 
     template_util::render_template(&state, "user_type_form.html", context).await
@@ -175,13 +209,13 @@ pub async fn user_type_create_page(
 
 pub async fn user_type_edit_page(
     State(state): State<Arc<AppState>>,
-    Extension(user_id): Extension<UserId>,
+    authn_user: AuthnUser,
     Path(user_type_id): Path<i64>,
 ) -> Result<Response, AppError> {
     let current_user = state
         .service
         .user_service
-        .get_user_by_id(user_id.0)
+        .get_user_by_id(authn_user.id)
         .await
         .ok();
     let user_type = state
@@ -190,10 +224,24 @@ pub async fn user_type_edit_page(
         .get_user_type_by_id(user_type_id)
         .await?;
 
+    let permissions = state
+        .service
+        .permission_service
+        .get_permissions_by_category()
+        .await?;
+    let assigned_permission_ids = state
+        .service
+        .user_type_service
+        .get_permissions(user_type_id)
+        .await?
+        .into_iter()
+        .filter_map(|p| p.id)
+        .collect();
+
     let context = TemplateContext {
         title: "사용자 유형 수정",
         active_page: "user_types",
-        user_id: user_id.0,
+        user_id: authn_user.id,
         current_user,
         user_types: None,
         user_type: Some(user_type),
@@ -205,47 +253,68 @@ pub async fn user_type_edit_page(
         sort_by: None,
         order: None,
         status: None,
+        permission_groups: group_by_category(permissions),
+        assigned_permission_ids,
     };
 
     template_util::render_template(&state, "user_type_form.html", context).await
 }
 
+/// Groups permissions (already ordered by category) for the assignment matrix
+fn group_by_category(permissions: Vec<PermissionResponse>) -> Vec<PermissionGroup> {
+    let mut groups: Vec<PermissionGroup> = Vec::new();
+    for permission in permissions {
+        let category = permission
+            .category
+            .clone()
+            .unwrap_or_else(|| "기타".to_string());
+        match groups.last_mut() {
+            Some(group) if group.category == category => group.permissions.push(permission),
+            _ => groups.push(PermissionGroup {
+                category,
+                permissions: vec![permission],
+            }),
+        }
+    }
+    groups
+}
+
 pub async fn create_user_type(
     State(state): State<Arc<AppState>>,
-    Extension(_user_id): Extension<UserId>,
+    audit: AuditContext,
     Form(payload): Form<CreateUserTypeRequest>,
 ) -> Result<Response, AppError> {
     state
         .service
         .user_type_service
-        .create_user_type(payload)
+        .create_user_type(payload, &audit)
         .await?;
     Ok(Redirect::to("/user-types").into_response())
 }
 
 pub async fn update_user_type(
     State(state): State<Arc<AppState>>,
-    Extension(_user_id): Extension<UserId>,
+    audit: AuditContext,
     Path(user_type_id): Path<i64>,
     Form(payload): Form<UpdateUserTypeRequest>,
 ) -> Result<Response, AppError> {
     state
         .service
         .user_type_service
-        .update_user_type(user_type_id, payload)
+        .update_user_type(user_type_id, payload, &audit)
         .await?;
     Ok(Redirect::to("/user-types").into_response())
 }
 
 pub async fn delete_user_type(
     State(state): State<Arc<AppState>>,
-    Extension(_user_id): Extension<UserId>,
+    audit: AuditContext,
     Path(user_type_id): Path<i64>,
 ) -> Result<Response, AppError> {
     state
         .service
         .user_type_service
-        .delete_user_type(user_type_id)
+        .delete_user_type(user_type_id, &audit)
         .await?;
     Ok(Redirect::to("/user-types").into_response())
 }
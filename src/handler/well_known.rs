@@ -0,0 +1,33 @@
+use crate::{errors::AppError, util::jwk_util, AppState};
+use axum::{extract::State, response::IntoResponse, routing::get, Json, Router};
+use std::sync::Arc;
+
+pub fn route() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/openid-configuration", get(openid_configuration))
+        .route("/jwks.json", get(jwks))
+}
+
+/// OpenID Connect Discovery 1.0 provider metadata
+async fn openid_configuration(
+    State(state): State<Arc<AppState>>,
+) -> Result<impl IntoResponse, AppError> {
+    let metadata = state
+        .service
+        .oauth_service
+        .discovery()
+        .ok_or_else(|| AppError::NotFound("OpenID Connect is not enabled".to_string()))?;
+    Ok(Json(metadata))
+}
+
+/// Public keys for verifying access and refresh tokens (RFC 7517). Retired keys stay listed
+/// so tokens signed before a rotation keep verifying until they expire.
+async fn jwks(State(state): State<Arc<AppState>>) -> Result<impl IntoResponse, AppError> {
+    let keys = state.config.token.public_keys();
+    if keys.is_empty() {
+        return Err(AppError::NotFound(
+            "Tokens are signed with a shared secret".to_string(),
+        ));
+    }
+    Ok(Json(jwk_util::jwk_set(&keys)))
+}
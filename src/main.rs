@@ -15,7 +15,7 @@ use anyhow::Context;
 use axum::{extract::FromRef, http::HeaderName, middleware, Router};
 use std::{net::SocketAddr, sync::Arc};
 use tera::Tera;
-use tokio::net::TcpListener;
+use tokio::{net::TcpListener, sync::watch};
 use tower::ServiceBuilder;
 use tower_http::{
     request_id::{self, PropagateRequestIdLayer, SetRequestIdLayer},
@@ -79,7 +79,14 @@ async fn async_main() -> anyhow::Result<()> {
     let tera_arc = Arc::new(tera);
 
     // Initialize service container
-    let service = ServiceContainer::new(Arc::from(db_pool.clone()));
+    let service = ServiceContainer::new(Arc::from(db_pool.clone()), &config);
+
+    // Link history written before the hash chain existed
+    service
+        .history_service
+        .seal_legacy_history()
+        .await
+        .context("활동 로그 해시 체인 봉인 실패")?;
 
     // Create application state wrapped in Arc
     let app_state = Arc::new(AppState {
@@ -105,10 +112,18 @@ async fn async_main() -> anyhow::Result<()> {
                 .layer(SetSensitiveRequestHeadersLayer::new(vec![
                     HeaderName::from_static("authorization"),
                 ]))
-                .layer(middleware::from_fn(filter::optional_auth))
+                .layer(middleware::from_fn_with_state(
+                    Arc::clone(&app_state),
+                    filter::optional_auth,
+                ))
                 .layer(middleware::from_fn(filter::log)),
         );
 
+    // Start background jobs; they stop when the shutdown signal arrives
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let retention_scheduler =
+        Arc::clone(&app_state.service.history_retention_service).spawn_scheduler(shutdown_rx);
+
     // Start HTTP server
     let addr = SocketAddr::from(([0, 0, 0, 0], app_state.config.server_port));
     tracing::info!("🌐 서버 시작 중: http://{}", addr);
@@ -120,8 +135,13 @@ async fn async_main() -> anyhow::Result<()> {
         listener,
         app.into_make_service_with_connect_info::<SocketAddr>(),
     )
-    .with_graceful_shutdown(graceful_shutdown::shutdown_signal())
+    .with_graceful_shutdown(graceful_shutdown::shutdown_signal(shutdown_tx))
     .await?;
 
+    // Let a retention run in progress finish its current batch
+    if let Err(e) = retention_scheduler.await {
+        tracing::warn!("활동 로그 보존 작업 종료 실패: {}", e);
+    }
+
     Ok(())
 }
@@ -0,0 +1,56 @@
+use crate::model::entity::api_key::ApiKey;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// New personal API key. `permissions` must be a subset of the owner's permissions.
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateApiKeyRequest {
+    #[validate(length(min = 1, max = 100, message = "Name must be 1-100 characters"))]
+    pub name: String,
+    #[validate(length(min = 1, message = "Select at least one permission"))]
+    pub permissions: Vec<String>,
+    /// Days until the key expires; the key never expires when omitted
+    #[validate(range(min = 1, max = 3650, message = "Expiry must be 1-3650 days"))]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ApiKeyResponse {
+    pub id: i64,
+    pub name: String,
+    /// First characters of the key, to tell keys apart
+    pub key_prefix: String,
+    pub permissions: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub expired: bool,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        Self {
+            id: key.id,
+            expired: key.is_expired(),
+            permissions: key
+                .permission_list()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            name: key.name,
+            key_prefix: key.key_prefix,
+            expires_at: key.expires_at.map(|t| Utc.from_utc_datetime(&t)),
+            last_used_at: key.last_used_at.map(|t| Utc.from_utc_datetime(&t)),
+            created_at: Utc.from_utc_datetime(&key.created_at),
+        }
+    }
+}
+
+/// The full key is only ever shown once, right after it is created
+#[derive(Debug, Serialize)]
+pub struct ApiKeyCreatedResponse {
+    #[serde(flatten)]
+    pub key: ApiKeyResponse,
+    pub api_key: String,
+}
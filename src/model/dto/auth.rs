@@ -18,6 +18,9 @@ pub struct LoginResponse {
     pub expires_in: i64,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub redirect_url: Option<String>,
+    /// Set once, when two-factor enrollment completes as part of the login
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_codes: Option<Vec<String>>,
 }
 
 // 현재 로그인한 사용자 정보 응답 DTO
@@ -38,7 +41,21 @@ pub struct RegisterRequest {
     pub email: Option<String>,
     #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
     pub password: String,
-    #[validate(range(min = 1, message = "User type cannot be empty"))]
-    pub user_type_id: i64,
     pub is_active: Option<bool>,
 }
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ForgotPasswordRequest {
+    #[validate(email(message = "Invalid email address"))]
+    pub email: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct ResetPasswordRequest {
+    #[validate(length(min = 1, message = "Token cannot be empty"))]
+    pub token: String,
+    #[validate(length(min = 8, message = "Password must be at least 8 characters long"))]
+    pub password: String,
+    #[validate(must_match(other = "password", message = "Passwords do not match"))]
+    pub password_confirm: String,
+}
@@ -1,4 +1,6 @@
-use crate::model::dto::history::HistoryResponse;
+use crate::model::{
+    dto::history::HistoryResponse, entity::history_retention_run::HistoryRetentionRun,
+};
 use serde::Serialize;
 use serde_with::{serde_as, DisplayFromStr};
 use sqlx::{Row, SqlitePool};
@@ -22,6 +24,9 @@ pub struct DashboardData {
     pub recent_history: Vec<HistoryResponse>,
     pub daily_active_users: Vec<DailyActiveUsers>,
 
+    // History retention
+    pub retention_runs: Vec<HistoryRetentionRun>,
+
     // Calculated fields
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde_as(as = "Option<DisplayFromStr>")]
@@ -90,7 +95,7 @@ impl DashboardData {
         // Get recent history using raw SQL to avoid type issues with query_as!
         let recent_history_rows = sqlx::query(
             r#"
-            SELECT h.id, h.user_id, au.username, h.action, h.entity_id, h.details, h.ip_address, h.created_at 
+            SELECT h.id, h.user_id, au.username, h.action, h.entity_type, h.entity_id, h.details, h.ip_address, h.request_id, h.created_at 
             FROM history h
             LEFT JOIN admin_user au ON h.user_id = au.id
             ORDER BY h.created_at DESC 
@@ -113,14 +118,18 @@ impl DashboardData {
                 username: row.get("username"),
                 action: row.get("action"),
                 entity_id: row.get("entity_id"),
-                entity_type: None,
+                entity_type: row.get("entity_type"),
                 details,
                 ip_address: row.get("ip_address"),
+                request_id: row.get("request_id"),
                 created_at: row.get("created_at"),
             };
             recent_history.push(history);
         }
 
+        // Get the latest history retention runs
+        let retention_runs = recent_retention_runs(pool).await?;
+
         // Calculate active percentage with 2 decimal places
         let active_percent = if total_users > 0 {
             let percent = (active_users as f64 / total_users as f64) * 100.0;
@@ -143,6 +152,8 @@ impl DashboardData {
             // Activity data
             recent_history,
             daily_active_users,
+            // History retention
+            retention_runs,
             // Calculated fields
             active_percent,
         })
@@ -245,7 +256,7 @@ impl DashboardData {
         // Get recent history (limited to 10 most recent)
         let recent_history_rows = sqlx::query(
             r#"
-            SELECT h.id, h.user_id, au.username, h.action, h.entity_id, h.details, h.ip_address, h.created_at 
+            SELECT h.id, h.user_id, au.username, h.action, h.entity_type, h.entity_id, h.details, h.ip_address, h.request_id, h.created_at 
             FROM history h
             LEFT JOIN admin_user au ON h.user_id = au.id
             ORDER BY h.created_at DESC 
@@ -268,14 +279,18 @@ impl DashboardData {
                 username: row.get("username"),
                 action: row.get("action"),
                 entity_id: row.get("entity_id"),
-                entity_type: None,
+                entity_type: row.get("entity_type"),
                 details,
                 ip_address: row.get("ip_address"),
+                request_id: row.get("request_id"),
                 created_at: row.get("created_at"),
             };
             recent_history.push(history);
         }
 
+        // Get the latest history retention runs
+        let retention_runs = recent_retention_runs(pool).await?;
+
         // Calculate active percentage with 2 decimal places
         let active_percent = if total_users > 0 {
             let percent = (active_users as f64 / total_users as f64) * 100.0;
@@ -301,8 +316,24 @@ impl DashboardData {
             recent_history,
             daily_active_users,
 
+            // History retention
+            retention_runs,
+
             // Calculated fields
             active_percent,
         })
     }
 }
+
+/// The five most recent history retention runs, newest first
+async fn recent_retention_runs(pool: &SqlitePool) -> Result<Vec<HistoryRetentionRun>, sqlx::Error> {
+    sqlx::query_as::<_, HistoryRetentionRun>(
+        r#"
+        SELECT id, triggered_by, status, archived_count, deleted_count, archive_files, error, started_at, finished_at
+        FROM history_retention_run
+        ORDER BY id DESC
+        LIMIT 5"#,
+    )
+    .fetch_all(pool)
+    .await
+}
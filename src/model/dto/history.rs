@@ -1,7 +1,8 @@
-use crate::model::entity::history::History;
+use crate::model::entity::history::{History, HistoryLink};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use sqlx::{QueryBuilder, Sqlite};
 
 const DEFAULT_PAGE_SIZE: i64 = 20;
 const MAX_PAGE_SIZE: i64 = 100;
@@ -18,6 +19,7 @@ pub struct HistoryResponse {
     pub entity_type: Option<String>,
     pub details: Option<Value>,
     pub ip_address: Option<String>,
+    pub request_id: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -29,9 +31,10 @@ impl From<History> for HistoryResponse {
             username: None, // Will be populated if needed
             action: log.action,
             entity_id: log.entity_id,
-            entity_type: None, // Will be populated if needed
+            entity_type: log.entity_type,
             details: log.details.and_then(|d| serde_json::from_str(&d).ok()),
             ip_address: log.ip_address,
+            request_id: log.request_id,
             created_at: log.created_at,
         }
     }
@@ -44,8 +47,11 @@ pub struct HistoryListQuery {
     pub action: Option<String>,
     pub entity_id: Option<i64>,
     pub entity_type: Option<String>,
+    pub ip_address: Option<String>,
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
+    /// Free-text search over action, details, IP address and user agent
+    pub search: Option<String>,
     /// Page number (1-based)
     #[serde(default = "default_page")]
     pub page: Option<i64>,
@@ -65,8 +71,8 @@ impl HistoryListQuery {
     pub fn get_limit(&self) -> i64 {
         self.limit
             .or(self.per_page)
-            .unwrap_or_else(|| MAX_PAGE_SIZE)
-            .clamp(1, i64::MAX)
+            .unwrap_or(MAX_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE)
     }
 
     /// Calculates the offset for pagination
@@ -75,60 +81,95 @@ impl HistoryListQuery {
             .unwrap_or_else(|| (self.get_page() - 1) * self.get_limit())
     }
 
-    /// Converts the query into SQL conditions and parameters
-    pub fn to_sql_conditions(&self) -> (String, sqlx::sqlite::SqliteArguments) {
-        use sqlx::Arguments;
+    /// The filters of the query, as recorded with an export
+    pub fn filters(&self) -> Value {
+        json!({
+            "user_id": self.user_id,
+            "action": non_empty(&self.action),
+            "entity_id": self.entity_id,
+            "entity_type": non_empty(&self.entity_type),
+            "ip_address": non_empty(&self.ip_address),
+            "start_date": self.start_date,
+            "end_date": self.end_date,
+            "search": non_empty(&self.search),
+        })
+    }
 
-        let mut conditions = Vec::new();
-        let mut args = sqlx::sqlite::SqliteArguments::default();
-        let mut param_count = 0;
+    /// Appends the WHERE clause for the filters to `builder`, binding every value
+    pub fn push_conditions<'a>(&'a self, builder: &mut QueryBuilder<'a, Sqlite>) {
+        let mut has_condition = false;
+        let mut next = |builder: &mut QueryBuilder<'a, Sqlite>| {
+            builder.push(if has_condition { " AND " } else { " WHERE " });
+            has_condition = true;
+        };
 
         if let Some(user_id) = self.user_id {
-            param_count += 1;
-            conditions.push(format!("user_id = ${}", param_count));
-            let _ = args.add(user_id);
+            next(builder);
+            builder.push("user_id = ").push_bind(user_id);
         }
 
-        if let Some(action) = &self.action {
-            param_count += 1;
-            conditions.push(format!("action = ${}", param_count));
-            let _ = args.add(action);
+        if let Some(action) = non_empty(&self.action) {
+            next(builder);
+            builder.push("action = ").push_bind(action);
         }
 
         if let Some(entity_id) = self.entity_id {
-            param_count += 1;
-            conditions.push(format!("entity_id = ${}", param_count));
-            let _ = args.add(entity_id);
+            next(builder);
+            builder.push("entity_id = ").push_bind(entity_id);
+        }
+
+        if let Some(entity_type) = non_empty(&self.entity_type) {
+            next(builder);
+            builder.push("entity_type = ").push_bind(entity_type);
         }
 
-        if let Some(entity_type) = &self.entity_type {
-            param_count += 1;
-            conditions.push(format!("action LIKE ${}", param_count));
-            let _ = args.add(format!("%:{}%", entity_type));
+        if let Some(ip_address) = non_empty(&self.ip_address) {
+            next(builder);
+            builder.push("ip_address = ").push_bind(ip_address);
         }
 
+        // created_at은 CURRENT_TIMESTAMP 형식으로 저장되므로 같은 형식으로 비교해야 인덱스를 탄다
         if let Some(start_date) = self.start_date {
-            param_count += 1;
-            conditions.push(format!("created_at >= ${}", param_count));
-            let _ = args.add(start_date);
+            next(builder);
+            builder
+                .push("created_at >= ")
+                .push_bind(start_date.format(SQLITE_DATETIME_FORMAT).to_string());
         }
 
         if let Some(end_date) = self.end_date {
-            param_count += 1;
-            conditions.push(format!("created_at <= ${}", param_count));
-            let _ = args.add(end_date);
+            next(builder);
+            builder
+                .push("created_at <= ")
+                .push_bind(end_date.format(SQLITE_DATETIME_FORMAT).to_string());
         }
 
-        let where_clause = if conditions.is_empty() {
-            String::new()
-        } else {
-            format!("WHERE {}", conditions.join(" AND "))
-        };
-
-        (where_clause, args)
+        if let Some(search) = self.search.as_deref().and_then(fts_query) {
+            next(builder);
+            builder
+                .push("id IN (SELECT rowid FROM history_fts WHERE history_fts MATCH ")
+                .push_bind(search)
+                .push(")");
+        }
     }
 }
 
+/// Format of `CURRENT_TIMESTAMP`, which history rows are stamped with
+pub const SQLITE_DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Turns free text into an FTS5 query: every word must match as a prefix. Words are
+/// quoted, so operators and syntax characters in the input are searched literally.
+fn fts_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = search
+        .split_whitespace()
+        .map(|term| format!("\"{}\"*", term.replace('"', "\"\"")))
+        .collect();
+    (!terms.is_empty()).then(|| terms.join(" "))
+}
+
 fn default_page() -> Option<i64> {
     Some(1)
 }
@@ -136,3 +177,182 @@ fn default_page() -> Option<i64> {
 fn default_page_size() -> Option<i64> {
     Some(DEFAULT_PAGE_SIZE)
 }
+
+/// Range of history to verify; both ends are inclusive and default to the whole table
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct HistoryVerifyQuery {
+    pub from_id: Option<i64>,
+    pub to_id: Option<i64>,
+}
+
+/// Where the verified range is anchored
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainAnchor {
+    /// The range starts at the first row ever written
+    Genesis,
+    /// The rows before the range were removed by retention cleanup
+    Checkpoint,
+    /// The range starts after a row that still exists (trusted as is)
+    History,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChainBreak {
+    /// The row was never sealed (inserted around the application)
+    MissingHash,
+    /// The row does not point at the hash of the row before it (rows removed or reordered)
+    PrevHashMismatch,
+    /// The row content no longer matches its hash (row edited)
+    HashMismatch,
+    /// The retention checkpoint anchoring the range was altered
+    CheckpointSignatureMismatch,
+}
+
+/// First broken link found while walking the chain
+#[derive(Debug, Serialize, Clone)]
+pub struct BrokenLink {
+    /// History row, or the checkpoint id for `checkpoint_signature_mismatch`
+    pub id: i64,
+    pub reason: ChainBreak,
+    pub expected: Option<String>,
+    pub actual: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryVerifyResponse {
+    pub valid: bool,
+    /// Whether hashes are HMAC signed with the configured key
+    pub signed: bool,
+    pub anchor: ChainAnchor,
+    /// Rows verified before the walk stopped
+    pub checked: i64,
+    pub first_id: Option<i64>,
+    pub last_id: Option<i64>,
+    /// Hash of the last verified row; keep it to detect later truncation of the tail
+    pub last_hash: Option<String>,
+    pub broken: Option<BrokenLink>,
+}
+
+/// File format of a history export
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    /// JSON Lines, one object per row
+    Jsonl,
+    /// Same encoding as `jsonl`, served under the NDJSON name
+    Ndjson,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Jsonl | ExportFormat::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Ndjson => "ndjson",
+        }
+    }
+
+    /// First line of the file, before any row
+    pub fn header(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "id,created_at,user_id,action,entity_type,entity_id,details,ip_address,user_agent,request_id,prev_hash,hash\r\n",
+            ExportFormat::Jsonl | ExportFormat::Ndjson => "",
+        }
+    }
+
+    /// One row, terminated by a line break
+    pub fn encode(&self, row: HistoryExportRow) -> String {
+        match self {
+            ExportFormat::Csv => {
+                let fields = [
+                    row.id.to_string(),
+                    csv_field(&row.created_at),
+                    row.user_id.map(|id| id.to_string()).unwrap_or_default(),
+                    csv_field(&row.action),
+                    csv_field(row.entity_type.as_deref().unwrap_or_default()),
+                    row.entity_id.map(|id| id.to_string()).unwrap_or_default(),
+                    csv_field(&row.details.map(|d| d.to_string()).unwrap_or_default()),
+                    csv_field(row.ip_address.as_deref().unwrap_or_default()),
+                    csv_field(row.user_agent.as_deref().unwrap_or_default()),
+                    csv_field(row.request_id.as_deref().unwrap_or_default()),
+                    row.prev_hash.unwrap_or_default(),
+                    row.hash.unwrap_or_default(),
+                ];
+                fields.join(",") + "\r\n"
+            }
+            ExportFormat::Jsonl | ExportFormat::Ndjson => json!(row).to_string() + "\n",
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default, Clone)]
+pub struct HistoryExportParams {
+    #[serde(default)]
+    pub format: ExportFormat,
+}
+
+/// One exported history row; the chain hashes are included so an extract can be checked
+/// against `/api/history/verify`
+#[derive(Debug, Serialize, Clone)]
+pub struct HistoryExportRow {
+    pub id: i64,
+    pub created_at: String,
+    pub user_id: Option<i64>,
+    pub action: String,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    pub details: Option<Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub prev_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl From<HistoryLink> for HistoryExportRow {
+    fn from(link: HistoryLink) -> Self {
+        Self {
+            id: link.id,
+            created_at: link.created_at,
+            user_id: link.user_id,
+            action: link.action,
+            entity_type: link.entity_type,
+            entity_id: link.entity_id,
+            // Details that are not JSON are kept as a string rather than dropped
+            details: link
+                .details
+                .map(|d| serde_json::from_str(&d).unwrap_or(Value::String(d))),
+            ip_address: link.ip_address,
+            user_agent: link.user_agent,
+            request_id: link.request_id,
+            prev_hash: link.prev_hash,
+            hash: link.hash,
+        }
+    }
+}
+
+/// Quotes a CSV field when needed (RFC 4180). Text that a spreadsheet would run as a formula
+/// is prefixed with `'`.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
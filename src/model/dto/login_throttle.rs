@@ -0,0 +1,30 @@
+use crate::model::entity::login_throttle::LoginThrottleEntry;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+
+/// A throttled username or IP address as shown to admins
+#[derive(Debug, Serialize, Clone)]
+pub struct LockoutResponse {
+    pub key: String,
+    pub failures: i64,
+    pub first_failure_at: Option<DateTime<Utc>>,
+    pub last_failure_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+    /// Seconds until the next login attempt is accepted (0 if allowed now)
+    pub retry_after: i64,
+}
+
+impl LockoutResponse {
+    pub fn new(entry: LoginThrottleEntry, retry_after: i64) -> Self {
+        Self {
+            key: entry.key,
+            failures: entry.failures,
+            first_failure_at: Utc.timestamp_opt(entry.first_failure_at, 0).single(),
+            last_failure_at: Utc.timestamp_opt(entry.last_failure_at, 0).single(),
+            locked_until: entry
+                .locked_until
+                .and_then(|ts| Utc.timestamp_opt(ts, 0).single()),
+            retry_after,
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+/// Second factor state shown on the profile page
+#[derive(Debug, Serialize)]
+pub struct MfaStatusResponse {
+    pub enabled: bool,
+    /// Enrollment was started but not confirmed with a code yet
+    pub pending: bool,
+    /// The user's type requires a second factor
+    pub required: bool,
+    pub recovery_codes_remaining: i64,
+}
+
+/// New TOTP secret to be added to an authenticator app
+#[derive(Debug, Serialize)]
+pub struct TotpSetupResponse {
+    pub secret: String,
+    pub otpauth_uri: String,
+    /// PNG QR code of `otpauth_uri` as a data URL
+    pub qr_code: Option<String>,
+}
+
+/// A TOTP code or a recovery code
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaCodeRequest {
+    #[validate(length(min = 1, max = 32, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+/// Recovery codes are only ever shown once, right after they are generated
+#[derive(Debug, Serialize)]
+pub struct RecoveryCodesResponse {
+    pub recovery_codes: Vec<String>,
+}
+
+/// Second login step: the pending token from `/api/auth/login` plus a code
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaLoginRequest {
+    #[validate(length(min = 1, message = "MFA token cannot be empty"))]
+    pub mfa_token: String,
+    #[validate(length(min = 1, max = 32, message = "Code cannot be empty"))]
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct MfaTokenRequest {
+    #[validate(length(min = 1, message = "MFA token cannot be empty"))]
+    pub mfa_token: String,
+}
+
+/// Returned by `/api/auth/login` instead of the token cookies when a second factor is needed
+#[derive(Debug, Serialize)]
+pub struct MfaChallengeResponse {
+    pub mfa_required: bool,
+    /// The user has no authenticator yet and must enroll one to finish logging in
+    pub enrollment_required: bool,
+    pub mfa_token: String,
+    pub expires_in: i64,
+    /// Where to send the user once the second step succeeds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirect_url: Option<String>,
+}
@@ -1,8 +1,13 @@
+pub mod api_key;
 pub mod auth;
 pub mod common;
 pub mod dashboard;
 pub mod history;
+pub mod login_throttle;
+pub mod mfa;
 pub mod oauth;
+pub mod oauth_client;
 pub mod permission;
+pub mod session;
 pub mod user;
 pub mod user_type;
@@ -1,18 +1,52 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct OAuthAuthorizeRequest {
-    pub response_type: String, // "code"
+    pub response_type: Option<String>, // "code"
     pub client_id: String,
     pub redirect_uri: String,
     pub scope: Option<String>,
     pub state: Option<String>,
+    pub code_challenge: Option<String>,        // PKCE (RFC 7636)
+    pub code_challenge_method: Option<String>, // "S256", "plain"
+    pub nonce: Option<String>,                 // OpenID Connect
 }
 
-#[derive(Debug, Serialize)]
-pub struct OAuthAuthorizeResponse {
-    pub code: String,
+/// Submitted by the consent screen: the original authorization request plus the user's decision
+#[derive(Debug, Deserialize)]
+pub struct OAuthConsentRequest {
+    pub response_type: Option<String>,
+    pub client_id: String,
+    pub redirect_uri: String,
+    pub scope: Option<String>,
     pub state: Option<String>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>,
+    pub nonce: Option<String>,
+    pub consent_token: String,
+    pub decision: String, // "approve", "deny"
+}
+
+impl OAuthConsentRequest {
+    pub fn authorize_request(&self) -> OAuthAuthorizeRequest {
+        OAuthAuthorizeRequest {
+            response_type: self.response_type.clone(),
+            client_id: self.client_id.clone(),
+            redirect_uri: self.redirect_uri.clone(),
+            scope: self.scope.clone(),
+            state: self.state.clone(),
+            code_challenge: self.code_challenge.clone(),
+            code_challenge_method: self.code_challenge_method.clone(),
+            nonce: self.nonce.clone(),
+        }
+    }
+}
+
+/// A requested scope as listed on the consent screen
+#[derive(Debug, Serialize)]
+pub struct OAuthScopeView {
+    pub name: String,
+    pub description: String,
 }
 
 #[derive(Debug, Deserialize)]
@@ -20,6 +54,7 @@ pub struct OAuthTokenRequest {
     pub grant_type: String, // "authorization_code", "client_credentials", "password", "refresh_token"
     pub code: Option<String>, // authorization_code
     pub redirect_uri: Option<String>,
+    pub code_verifier: Option<String>, // PKCE (RFC 7636)
     pub client_id: Option<String>,
     pub client_secret: Option<String>,
     pub username: Option<String>, // password grant
@@ -35,4 +70,74 @@ pub struct OAuthTokenResponse {
     pub expires_in: i64,
     pub refresh_token: Option<String>,
     pub scope: Option<String>,
+    /// Issued when the `openid` scope was granted
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_token: Option<String>,
+}
+
+/// RFC 6749 5.2 error body of the token, introspection and revocation endpoints
+#[derive(Debug, Serialize)]
+pub struct OAuthErrorResponse {
+    pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
+}
+
+/// Token introspection (RFC 7662) and revocation (RFC 7009) request
+#[derive(Debug, Deserialize)]
+pub struct OAuthTokenLookupRequest {
+    pub token: String,
+    pub token_type_hint: Option<String>, // "access_token", "refresh_token"
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+/// RFC 7662 introspection response. Only `active` is set for unknown or expired tokens.
+#[derive(Debug, Default, Serialize)]
+pub struct OAuthIntrospectResponse {
+    pub active: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub iat: Option<i64>,
+}
+
+/// OpenID Connect UserInfo response. Claims beyond `sub` depend on the granted scopes.
+#[derive(Debug, Serialize)]
+pub struct OidcUserInfoResponse {
+    pub sub: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<i64>,
+}
+
+/// OpenID Provider metadata served at `/.well-known/openid-configuration`
+#[derive(Debug, Serialize)]
+pub struct OidcDiscoveryResponse {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub userinfo_endpoint: String,
+    pub jwks_uri: String,
+    pub introspection_endpoint: String,
+    pub revocation_endpoint: String,
+    pub response_types_supported: Vec<&'static str>,
+    pub grant_types_supported: Vec<&'static str>,
+    pub subject_types_supported: Vec<&'static str>,
+    pub id_token_signing_alg_values_supported: Vec<jsonwebtoken::Algorithm>,
+    pub scopes_supported: Vec<&'static str>,
+    pub claims_supported: Vec<&'static str>,
+    pub token_endpoint_auth_methods_supported: Vec<&'static str>,
+    pub code_challenge_methods_supported: Vec<&'static str>,
 }
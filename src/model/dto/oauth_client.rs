@@ -0,0 +1,133 @@
+use crate::model::entity::oauth_client::OAuthClient;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateOAuthClientRequest {
+    /// Generated when omitted
+    #[validate(length(
+        min = 3,
+        max = 64,
+        message = "Client ID must be between 3 and 64 characters"
+    ))]
+    pub client_id: Option<String>,
+
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: String,
+
+    #[serde(default)]
+    pub redirect_uris: Vec<String>,
+
+    #[validate(length(min = 1, message = "At least one grant type is required"))]
+    pub grant_types: Vec<String>,
+
+    #[serde(default)]
+    pub scopes: Vec<String>,
+
+    /// Permissions of `client_credentials` tokens; each must be held by the caller
+    #[serde(default)]
+    pub permissions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateOAuthClientRequest {
+    #[validate(length(
+        min = 1,
+        max = 100,
+        message = "Name must be between 1 and 100 characters"
+    ))]
+    pub name: Option<String>,
+
+    pub redirect_uris: Option<Vec<String>>,
+
+    #[validate(length(min = 1, message = "At least one grant type is required"))]
+    pub grant_types: Option<Vec<String>>,
+
+    pub scopes: Option<Vec<String>>,
+
+    pub permissions: Option<Vec<String>>,
+}
+
+/// Secret rotation. The old secret keeps working for `overlap_secs` so clients can switch over.
+#[derive(Debug, Deserialize, Validate)]
+pub struct RotateOAuthClientSecretRequest {
+    #[validate(range(
+        min = 0,
+        max = 2592000,
+        message = "Overlap must be between 0 and 30 days"
+    ))]
+    pub overlap_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OAuthClientResponse {
+    pub id: i64,
+    pub client_id: String,
+    pub name: String,
+    pub redirect_uris: Vec<String>,
+    pub grant_types: Vec<String>,
+    pub scopes: Vec<String>,
+    /// Permissions of tokens the client obtains for itself (`client_credentials`)
+    pub permissions: Vec<String>,
+    /// Set while the secret replaced by the last rotation is still accepted
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
+    /// The client has no secret (its plaintext one was dropped) until the secret is rotated
+    pub secret_rotation_required: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<OAuthClient> for OAuthClientResponse {
+    fn from(client: OAuthClient) -> Self {
+        let previous_secret_expires_at = client
+            .previous_secret_expires_at
+            .map(|t| Utc.from_utc_datetime(&t))
+            .filter(|t| *t > Utc::now());
+        Self {
+            id: client.id,
+            redirect_uris: client
+                .redirect_uri_list()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            grant_types: client
+                .grant_types
+                .as_deref()
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|g| !g.is_empty())
+                .map(str::to_string)
+                .collect(),
+            scopes: client
+                .scope
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            permissions: client
+                .permission_list()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            name: client.name.unwrap_or_else(|| client.client_id.clone()),
+            client_id: client.client_id,
+            previous_secret_expires_at,
+            secret_rotation_required: client.client_secret_hash.is_none(),
+            created_at: Utc.from_utc_datetime(&client.created_at),
+        }
+    }
+}
+
+/// Returned by create and rotate; the only time the plaintext secret is available
+#[derive(Debug, Serialize)]
+pub struct OAuthClientSecretResponse {
+    #[serde(flatten)]
+    pub client: OAuthClientResponse,
+    pub client_secret: String,
+}
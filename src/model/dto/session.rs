@@ -0,0 +1,40 @@
+use crate::model::entity::user_session::UserSession;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A signed-in device of the current user
+#[derive(Debug, Serialize, Clone)]
+pub struct SessionResponse {
+    pub id: String,
+    pub device_label: Option<String>,
+    pub user_agent: Option<String>,
+    pub ip_address: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session the request was made from
+    pub current: bool,
+}
+
+impl From<UserSession> for SessionResponse {
+    fn from(s: UserSession) -> Self {
+        Self {
+            id: s.id,
+            device_label: s.device_label,
+            user_agent: s.user_agent,
+            ip_address: s.ip_address,
+            created_at: Utc.from_utc_datetime(&s.created_at),
+            last_seen_at: Utc.from_utc_datetime(&s.last_seen_at),
+            expires_at: Utc.from_utc_datetime(&s.expires_at),
+            current: false,
+        }
+    }
+}
+
+/// Query parameters for revoking every session
+#[derive(Debug, Deserialize)]
+pub struct RevokeSessionsQuery {
+    /// Keep the session the request was made from
+    #[serde(default)]
+    pub except_current: bool,
+}
@@ -1,4 +1,4 @@
-use crate::model::entity::admin_user::AdminUser;
+use crate::{config::auth::user::AccountState, model::entity::admin_user::AdminUser};
 use chrono::{DateTime, TimeZone, Utc};
 use serde::{Deserialize, Serialize};
 use validator::Validate;
@@ -49,11 +49,39 @@ impl From<AdminUser> for UserResponse {
             email: user.email,
             user_type_id: user.user_type_id,
             is_active: user.is_active,
-            last_login_at: user
-                .last_login_at
-                .map(|ndt| Utc.from_utc_datetime(&ndt.into())),
+            last_login_at: user.last_login_at.map(|ndt| Utc.from_utc_datetime(&ndt)),
             created_at: Utc.from_utc_datetime(&user.created_at),
             updated_at: Utc.from_utc_datetime(&user.updated_at),
         }
     }
 }
+
+#[derive(Debug, Deserialize, Validate)]
+pub struct LockUserRequest {
+    #[validate(length(max = 255, message = "Reason cannot exceed 255 characters"))]
+    pub reason: Option<String>,
+}
+
+// 계정 잠금/세션 상태 응답 DTO
+#[derive(Debug, Serialize, Clone)]
+pub struct AccountStatusResponse {
+    pub id: i64,
+    pub username: String,
+    pub is_active: bool,
+    pub locked: bool,
+    pub locked_at: Option<DateTime<Utc>>,
+    pub locked_reason: Option<String>,
+}
+
+impl From<AccountState> for AccountStatusResponse {
+    fn from(state: AccountState) -> Self {
+        Self {
+            id: state.id,
+            username: state.username,
+            is_active: state.is_active,
+            locked: state.locked_at.is_some(),
+            locked_at: state.locked_at.map(|ndt| Utc.from_utc_datetime(&ndt)),
+            locked_reason: state.locked_reason,
+        }
+    }
+}
@@ -24,6 +24,10 @@ pub struct CreateUserTypeRequest {
 
     #[serde(default = "default_is_active")]
     pub is_active: bool,
+
+    /// Require TOTP two-factor authentication for users of this type
+    #[serde(default)]
+    pub require_mfa: bool,
 }
 
 #[derive(Debug, Deserialize, Validate)]
@@ -46,6 +50,14 @@ pub struct UpdateUserTypeRequest {
     pub description: Option<String>,
 
     pub is_active: Option<bool>,
+
+    pub require_mfa: Option<bool>,
+}
+
+/// Bulk replacement of the permissions assigned to a user type
+#[derive(Debug, Deserialize)]
+pub struct UpdateUserTypePermissionsRequest {
+    pub permission_ids: Vec<i64>,
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -55,6 +67,7 @@ pub struct UserTypeResponse {
     pub name: String,
     pub description: Option<String>,
     pub is_active: bool,
+    pub require_mfa: bool,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -67,6 +80,7 @@ impl From<UserType> for UserTypeResponse {
             name: ut.name,
             description: ut.description,
             is_active: ut.is_active,
+            require_mfa: ut.require_mfa,
             created_at: Utc.from_utc_datetime(&ut.created_at),
             updated_at: Utc.from_utc_datetime(&ut.updated_at),
         }
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{json, Value};
+use sqlx::FromRow;
 
 /// History entry representing user actions in the system
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,10 +10,14 @@ pub struct History {
     pub user_id: Option<i64>,
     /// Action name (e.g., "login", "create_user", "update_permission")
     pub action: String,
+    /// Kind of record `entity_id` refers to (e.g., "user", "user_type")
+    pub entity_type: Option<String>,
     pub entity_id: Option<i64>,
     pub details: Option<String>,
     pub ip_address: Option<String>,
     pub user_agent: Option<String>,
+    /// `x-request-id` of the request that caused the entry
+    pub request_id: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -30,10 +35,12 @@ impl History {
             id: 0, // Will be set by the database
             user_id,
             action: action.into(),
+            entity_type: None,
             entity_id,
             details: details.map(|v| v.to_string()),
             ip_address,
             user_agent,
+            request_id: None,
             created_at: Utc::now(),
         }
     }
@@ -47,3 +54,84 @@ impl History {
         Self::new(None, action, entity_id, details, None, None)
     }
 }
+
+/// Chain columns of a history row together with the content they cover
+#[derive(Debug, Clone, FromRow)]
+pub struct HistoryLink {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub action: String,
+    pub entity_type: Option<String>,
+    pub entity_id: Option<i64>,
+    pub details: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    /// Stored text of `created_at`, hashed exactly as written
+    pub created_at: String,
+    pub prev_hash: Option<String>,
+    pub hash: Option<String>,
+}
+
+impl HistoryLink {
+    /// What the row hash covers: every column plus the link to the previous row
+    pub fn content(&self, prev_hash: &str) -> Value {
+        json!([
+            self.id,
+            self.user_id,
+            self.action,
+            self.entity_type,
+            self.entity_id,
+            self.details,
+            self.ip_address,
+            self.user_agent,
+            self.request_id,
+            self.created_at,
+            prev_hash,
+        ])
+    }
+}
+
+/// An expired row found by retention, with the id of the row right before it (if any left)
+#[derive(Debug, Clone, FromRow)]
+pub struct ExpiredHistory {
+    #[sqlx(flatten)]
+    pub link: HistoryLink,
+    pub previous_id: Option<i64>,
+}
+
+/// Signed anchor left behind when retention removes a run of consecutive history rows
+#[derive(Debug, Clone, FromRow)]
+pub struct HistoryCheckpoint {
+    pub id: i64,
+    /// First removed history row; `None` on checkpoints that removed the oldest rows only
+    pub from_id: Option<i64>,
+    /// Last removed history row
+    pub through_id: i64,
+    /// Hash the first removed row pointed at, where the gap starts
+    pub anchor_hash: Option<String>,
+    /// Hash of the last removed row, the expected `prev_hash` of the row after the gap
+    pub through_hash: String,
+    pub deleted_count: i64,
+    pub created_at: String,
+    pub signature: String,
+}
+
+impl HistoryCheckpoint {
+    /// What the checkpoint signature covers
+    pub fn content(&self) -> Value {
+        let mut content = json!([
+            "checkpoint",
+            self.through_id,
+            self.through_hash,
+            self.deleted_count,
+            self.created_at,
+        ]);
+        if let (Some(from_id), Some(anchor_hash), Value::Array(fields)) =
+            (self.from_id, &self.anchor_hash, &mut content)
+        {
+            fields.extend([json!(from_id), json!(anchor_hash)]);
+        }
+        content
+    }
+}
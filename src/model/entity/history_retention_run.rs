@@ -0,0 +1,19 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// One run of the history retention job
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct HistoryRetentionRun {
+    pub id: i64,
+    /// `scheduled` or `manual`
+    pub triggered_by: String,
+    /// `running`, `succeeded`, `interrupted` (stopped by shutdown) or `failed`
+    pub status: String,
+    pub archived_count: i64,
+    pub deleted_count: i64,
+    pub archive_files: i64,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
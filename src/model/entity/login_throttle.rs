@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Failed login counter for a throttle key (`user:<username>` or `ip:<address>`).
+/// Times are unix timestamps in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct LoginThrottleEntry {
+    pub key: String,
+    pub failures: i64,
+    pub first_failure_at: i64,
+    pub last_failure_at: i64,
+    pub locked_until: Option<i64>,
+}
@@ -1,7 +1,13 @@
 pub mod admin_user;
+pub mod api_key;
 pub mod history;
+pub mod history_retention_run;
+pub mod login_throttle;
 pub mod oauth_client;
 pub mod oauth_code;
 pub mod oauth_token;
 pub mod permission;
+pub mod refresh_token;
+pub mod user_mfa;
+pub mod user_session;
 pub mod user_type;
@@ -1,3 +1,4 @@
+use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 
@@ -5,8 +6,26 @@ use sqlx::FromRow;
 pub struct OAuthClient {
     pub id: i64,
     pub client_id: String,
-    pub client_secret: String,
-    pub redirect_uri: String,
+    pub name: Option<String>,
+    #[serde(skip_serializing)]
+    pub client_secret_hash: Option<String>,
+    #[serde(skip_serializing)]
+    pub previous_secret_hash: Option<String>,
+    pub previous_secret_expires_at: Option<NaiveDateTime>,
+    pub redirect_uris: String, // space-separated
     pub scope: Option<String>,
     pub grant_types: Option<String>, // comma-separated
+    pub permissions: String,         // space-separated permission codes
+    pub created_at: NaiveDateTime,
+}
+
+impl OAuthClient {
+    pub fn redirect_uri_list(&self) -> Vec<&str> {
+        self.redirect_uris.split_whitespace().collect()
+    }
+
+    /// Permissions of tokens the client obtains for itself (`client_credentials`)
+    pub fn permission_list(&self) -> Vec<&str> {
+        self.permissions.split_whitespace().collect()
+    }
 }
@@ -10,4 +10,8 @@ pub struct OAuthCode {
     pub redirect_uri: String,
     pub scope: Option<String>,
     pub expires_at: DateTime<Utc>,
+    pub code_challenge: Option<String>,
+    pub code_challenge_method: Option<String>, // "S256", "plain"
+    pub used_at: Option<DateTime<Utc>>,
+    pub nonce: Option<String>, // OpenID Connect
 }
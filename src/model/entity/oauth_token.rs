@@ -11,4 +11,8 @@ pub struct OAuthToken {
     pub scope: Option<String>,
     pub expires_at: DateTime<Utc>,
     pub created_at: DateTime<Utc>,
+    pub authorization_code: Option<String>,
+    pub refresh_expires_at: Option<DateTime<Utc>>,
+    /// Resource owner's `token_version` at issue time; `None` for `client_credentials` tokens
+    pub token_version: Option<i64>,
 }
@@ -0,0 +1,21 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Second factor state of an admin user
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserMfa {
+    pub user_id: i64,
+    pub username: String,
+    pub totp_secret: Option<String>,
+    pub totp_enabled_at: Option<NaiveDateTime>,
+    pub totp_last_step: Option<i64>,
+    /// Whether the user's type requires a second factor
+    pub require_mfa: bool,
+}
+
+impl UserMfa {
+    pub fn is_enabled(&self) -> bool {
+        self.totp_enabled_at.is_some() && self.totp_secret.is_some()
+    }
+}
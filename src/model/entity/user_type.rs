@@ -9,6 +9,7 @@ pub struct UserType {
     pub name: String,
     pub description: Option<String>,
     pub is_active: bool,
+    pub require_mfa: bool,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -21,6 +22,7 @@ impl Default for UserType {
             name: String::new(),
             description: None,
             is_active: true,
+            require_mfa: false,
             created_at: chrono::Local::now().naive_utc(),
             updated_at: chrono::Local::now().naive_utc(),
         }
@@ -0,0 +1,98 @@
+use crate::{errors::AppError, model::entity::api_key::ApiKey};
+use chrono::NaiveDateTime;
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct ApiKeyRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl ApiKeyRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_user(&self, user_id: i64) -> Result<Vec<ApiKey>, AppError> {
+        let keys = sqlx::query_as!(
+            ApiKey,
+            r#"SELECT id as "id!", user_id, name, key_prefix, key_hash, permissions,
+                expires_at, last_used_at, created_at as "created_at!"
+            FROM api_key
+            WHERE user_id = ?
+            ORDER BY created_at DESC, id DESC"#,
+            user_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    pub async fn find_by_hash(&self, key_hash: &str) -> Result<Option<ApiKey>, AppError> {
+        let key = sqlx::query_as!(
+            ApiKey,
+            r#"SELECT id as "id!", user_id, name, key_prefix, key_hash, permissions,
+                expires_at, last_used_at, created_at as "created_at!"
+            FROM api_key
+            WHERE key_hash = ?"#,
+            key_hash
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    pub async fn create(
+        &self,
+        user_id: i64,
+        name: &str,
+        key_prefix: &str,
+        key_hash: &str,
+        permissions: &str,
+        expires_at: Option<NaiveDateTime>,
+    ) -> Result<ApiKey, AppError> {
+        let key = sqlx::query_as!(
+            ApiKey,
+            r#"INSERT INTO api_key (user_id, name, key_prefix, key_hash, permissions, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            RETURNING id as "id!", user_id, name, key_prefix, key_hash, permissions,
+                expires_at, last_used_at, created_at as "created_at!""#,
+            user_id,
+            name,
+            key_prefix,
+            key_hash,
+            permissions,
+            expires_at
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Deletes one of the user's keys. Returns `false` if the user has no such key.
+    pub async fn delete(&self, id: i64, user_id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"DELETE FROM api_key WHERE id = ? AND user_id = ?"#,
+            id,
+            user_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn touch_last_used(&self, id: i64) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"UPDATE api_key SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?"#,
+            id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
@@ -1,5 +1,11 @@
-use crate::config::auth::user::User;
-use crate::{errors::AppError, model::entity::admin_user::AdminUser};
+use crate::config::auth::user::{AccountState, User};
+use crate::{
+    errors::AppError,
+    model::entity::{
+        admin_user::AdminUser, refresh_token::RefreshToken, user_session::UserSession,
+    },
+};
+use chrono::NaiveDateTime;
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
@@ -20,7 +26,10 @@ impl AuthRepository {
                 admin_user.id as "id!",
                 admin_user.username as "username!",
                 admin_user.password_hash as "password!",
-                user_type.name as "role!"
+                admin_user.user_type_id as "user_type_id!",
+                user_type.code as "role!",
+                admin_user.token_version as "token_version!",
+                admin_user.locked_at IS NOT NULL as "locked!: bool"
             FROM admin_user
             INNER JOIN user_type ON admin_user.user_type_id = user_type.id
             WHERE admin_user.username = ? AND admin_user.is_active = 1"#,
@@ -32,24 +41,107 @@ impl AuthRepository {
         Ok(user)
     }
 
+    /// Same as `find_user_by_username`, used to resume a login after the second factor
+    pub async fn find_user_by_id(&self, user_id: i64) -> Result<Option<User>, AppError> {
+        let user = sqlx::query_as!(
+            User,
+            r#"SELECT
+                admin_user.id as "id!",
+                admin_user.username as "username!",
+                admin_user.password_hash as "password!",
+                admin_user.user_type_id as "user_type_id!",
+                user_type.code as "role!",
+                admin_user.token_version as "token_version!",
+                admin_user.locked_at IS NOT NULL as "locked!: bool"
+            FROM admin_user
+            INNER JOIN user_type ON admin_user.user_type_id = user_type.id
+            WHERE admin_user.id = ? AND admin_user.is_active = 1"#,
+            user_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn find_account_state(&self, user_id: i64) -> Result<Option<AccountState>, AppError> {
+        let state = sqlx::query_as!(
+            AccountState,
+            r#"SELECT id as "id!", username, user_type_id, is_active as "is_active!",
+                token_version, locked_at, locked_reason
+            FROM admin_user
+            WHERE id = ?"#,
+            user_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(state)
+    }
+
+    /// Invalidates every token issued to the user so far. Returns `false` if the user does not exist.
+    pub async fn bump_token_version(&self, user_id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "UPDATE admin_user SET token_version = token_version + 1 WHERE id = ?",
+            user_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Locks (with a reason) or unlocks an account. Locking also bumps the token version.
+    pub async fn set_locked(
+        &self,
+        user_id: i64,
+        locked: bool,
+        reason: Option<String>,
+    ) -> Result<bool, AppError> {
+        let result = if locked {
+            sqlx::query!(
+                r#"UPDATE admin_user
+                SET locked_at = CURRENT_TIMESTAMP,
+                    locked_reason = ?,
+                    token_version = token_version + 1,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE id = ?"#,
+                reason,
+                user_id
+            )
+            .execute(&*self.pool)
+            .await?
+        } else {
+            sqlx::query!(
+                r#"UPDATE admin_user
+                SET locked_at = NULL,
+                    locked_reason = NULL,
+                    updated_at = CURRENT_TIMESTAMP
+                WHERE id = ?"#,
+                user_id
+            )
+            .execute(&*self.pool)
+            .await?
+        };
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stores the hash of a newly issued refresh token in the given token family
     pub async fn save_refresh_token(
         &self,
         user_id: i64,
-        refresh_token: &str,
+        token_hash: &str,
+        family_id: &str,
+        expires_at: NaiveDateTime,
     ) -> Result<(), AppError> {
-        let expires_at = chrono::Utc::now() + chrono::Duration::days(30);
-        let naive_expires_at = expires_at.naive_utc();
-
         sqlx::query!(
-            r#"INSERT INTO user_refresh_token (user_id, refresh_token, expires_at, updated_at)
-               VALUES (?, ?, ?, CURRENT_TIMESTAMP)
-               ON CONFLICT(user_id) DO UPDATE 
-               SET refresh_token = excluded.refresh_token, 
-                   expires_at = excluded.expires_at,
-                   updated_at = CURRENT_TIMESTAMP"#,
+            r#"INSERT INTO user_refresh_token (user_id, token_hash, family_id, expires_at)
+               VALUES (?, ?, ?, ?)"#,
             user_id,
-            refresh_token,
-            naive_expires_at
+            token_hash,
+            family_id,
+            expires_at
         )
         .execute(&*self.pool)
         .await?;
@@ -57,10 +149,17 @@ impl AuthRepository {
         Ok(())
     }
 
-    pub async fn find_refresh_token(&self, user_id: i64) -> Result<Option<String>, AppError> {
-        let token = sqlx::query_scalar!(
-            "SELECT refresh_token FROM user_refresh_token WHERE user_id = ?",
-            user_id
+    pub async fn find_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshToken>, AppError> {
+        let token = sqlx::query_as!(
+            RefreshToken,
+            r#"SELECT id as "id!", user_id, token_hash, family_id, expires_at,
+                used_at, revoked_at, created_at
+            FROM user_refresh_token
+            WHERE token_hash = ?"#,
+            token_hash
         )
         .fetch_optional(&*self.pool)
         .await?;
@@ -68,6 +167,186 @@ impl AuthRepository {
         Ok(token)
     }
 
+    /// Marks a refresh token as used. Returns `false` if it had already been used or revoked,
+    /// so two concurrent refreshes with the same token cannot both succeed.
+    pub async fn mark_refresh_token_used(&self, id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE user_refresh_token
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND used_at IS NULL AND revoked_at IS NULL"#,
+            id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Starts a new session; its id is used as the refresh token family id
+    pub async fn create_session(
+        &self,
+        session_id: &str,
+        user_id: i64,
+        device_label: Option<String>,
+        user_agent: Option<String>,
+        ip_address: Option<String>,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"INSERT INTO user_session (id, user_id, device_label, user_agent, ip_address, expires_at)
+               VALUES (?, ?, ?, ?, ?, ?)"#,
+            session_id,
+            user_id,
+            device_label,
+            user_agent,
+            ip_address,
+            expires_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records activity on a session when its refresh token is rotated
+    pub async fn touch_session(
+        &self,
+        session_id: &str,
+        ip_address: Option<String>,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), AppError> {
+        sqlx::query!(
+            r#"UPDATE user_session
+            SET last_seen_at = CURRENT_TIMESTAMP,
+                ip_address = COALESCE(?, ip_address),
+                expires_at = ?
+            WHERE id = ?"#,
+            ip_address,
+            expires_at,
+            session_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Returns the sessions of a user that are neither revoked nor expired
+    pub async fn find_active_sessions(&self, user_id: i64) -> Result<Vec<UserSession>, AppError> {
+        let now = chrono::Utc::now().naive_utc();
+        let sessions = sqlx::query_as!(
+            UserSession,
+            r#"SELECT id as "id!", user_id, device_label, user_agent, ip_address,
+                expires_at, revoked_at, created_at, last_seen_at
+            FROM user_session
+            WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ?
+            ORDER BY last_seen_at DESC"#,
+            user_id,
+            now
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(sessions)
+    }
+
+    /// Revokes a session together with every refresh token of its family.
+    /// Returns `false` if the session does not exist or was already revoked.
+    pub async fn revoke_session(&self, session_id: &str) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "UPDATE user_session SET revoked_at = CURRENT_TIMESTAMP WHERE id = ? AND revoked_at IS NULL",
+            session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"UPDATE user_refresh_token
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE family_id = ? AND revoked_at IS NULL"#,
+            session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Adds a JWT id to the denylist until the token expires. Entries of tokens that have
+    /// expired by now are pruned on the way. Returns false when the id was already listed,
+    /// which lets single-use tokens be consumed atomically.
+    pub async fn revoke_jti(
+        &self,
+        jti: &str,
+        user_id: i64,
+        expires_at: NaiveDateTime,
+    ) -> Result<bool, AppError> {
+        let now = chrono::Utc::now().naive_utc();
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM revoked_token WHERE expires_at <= ?", now)
+            .execute(&mut *tx)
+            .await?;
+
+        let result = sqlx::query!(
+            "INSERT OR IGNORE INTO revoked_token (jti, user_id, expires_at) VALUES (?, ?, ?)",
+            jti,
+            user_id,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn is_jti_revoked(&self, jti: &str) -> Result<bool, AppError> {
+        let revoked = sqlx::query_scalar!(
+            r#"SELECT EXISTS(SELECT 1 FROM revoked_token WHERE jti = ?) as "revoked!: bool""#,
+            jti
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(revoked)
+    }
+
+    /// Revokes every session of a user, optionally keeping one. Returns the number of revoked sessions.
+    pub async fn revoke_all_sessions(
+        &self,
+        user_id: i64,
+        except_session_id: Option<&str>,
+    ) -> Result<u64, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"UPDATE user_session
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND revoked_at IS NULL AND id IS NOT ?"#,
+            user_id,
+            except_session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"UPDATE user_refresh_token
+            SET revoked_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND revoked_at IS NULL AND family_id IS NOT ?"#,
+            user_id,
+            except_session_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected())
+    }
+
     pub async fn create_user(
         &self,
         username: String,
@@ -100,4 +379,88 @@ impl AuthRepository {
 
         Ok(user.id)
     }
+
+    pub async fn find_active_user_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<AdminUser>, AppError> {
+        let user = sqlx::query_as!(
+            AdminUser,
+            r#"SELECT id as "id!", username, password_hash, email, user_type_id,
+                is_active as "is_active!", last_login_at, created_at, updated_at
+            FROM admin_user
+            WHERE email = ? AND is_active = 1"#,
+            email
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(user)
+    }
+
+    /// Stores a new password reset token hash, invalidating any pending token of the user
+    pub async fn create_password_reset_token(
+        &self,
+        user_id: i64,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE password_reset_token SET is_used = 1 WHERE user_id = ? AND is_used = 0",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "INSERT INTO password_reset_token (user_id, token, expires_at) VALUES (?, ?, ?)",
+            user_id,
+            token_hash,
+            expires_at
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Marks an unused, unexpired reset token as used and sets the new password of its user
+    /// in one transaction, returning the user id. The conditional update makes the token
+    /// single-use even under concurrent requests, and a failed password update leaves it unused.
+    pub async fn reset_password_with_token(
+        &self,
+        token_hash: &str,
+        password_hash: &str,
+    ) -> Result<Option<i64>, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let now = chrono::Utc::now().naive_utc();
+        let Some(user_id) = sqlx::query_scalar!(
+            r#"UPDATE password_reset_token
+            SET is_used = 1
+            WHERE token = ? AND is_used = 0 AND expires_at > ?
+            RETURNING user_id"#,
+            token_hash,
+            now
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            "UPDATE admin_user SET password_hash = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            password_hash,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(user_id))
+    }
 }
@@ -1,22 +1,36 @@
 use crate::{
     errors::AppError,
-    model::{dto::history::HistoryListQuery, entity::history::History},
+    model::{
+        dto::history::{HistoryListQuery, SQLITE_DATETIME_FORMAT},
+        entity::history::{ExpiredHistory, History, HistoryCheckpoint, HistoryLink},
+    },
+    util::hash_chain_util::{ChainHasher, GENESIS_HASH},
 };
 use chrono::{DateTime, Utc};
-use sqlx::{sqlite::SqlitePool, FromRow, Row};
+use sqlx::{sqlite::SqlitePool, FromRow, QueryBuilder, SqliteConnection};
 use std::sync::Arc;
 use tracing::error;
 
+/// Takes the write lock up front: a deferred transaction that reads first cannot wait for a
+/// concurrent writer and fails with `database is locked` instead
+const BEGIN_IMMEDIATE: &str = "BEGIN IMMEDIATE";
+
+const CHECKPOINT_COLUMNS: &str = "id, from_id, through_id, anchor_hash, through_hash, deleted_count, CAST(created_at AS TEXT) AS created_at, signature";
+
+const LINK_COLUMNS: &str = "id, user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id, CAST(created_at AS TEXT) AS created_at, prev_hash, hash";
+
 /// Internal database representation of a history
 #[derive(FromRow)]
 struct HistoryDb {
     id: i64,
     user_id: Option<i64>,
     action: String,
+    entity_type: Option<String>,
     entity_id: Option<i64>,
     details: Option<String>,
     ip_address: Option<String>,
     user_agent: Option<String>,
+    request_id: Option<String>,
     created_at: DateTime<Utc>,
 }
 
@@ -26,10 +40,12 @@ impl From<HistoryDb> for History {
             id: db.id,
             user_id: db.user_id,
             action: db.action,
+            entity_type: db.entity_type,
             entity_id: db.entity_id,
             details: db.details,
             ip_address: db.ip_address,
             user_agent: db.user_agent,
+            request_id: db.request_id,
             created_at: db.created_at,
         }
     }
@@ -39,81 +55,244 @@ impl From<HistoryDb> for History {
 #[derive(Clone)]
 pub struct HistoryRepository {
     pool: Arc<SqlitePool>,
+    hasher: ChainHasher,
 }
 
 impl HistoryRepository {
-    pub fn new(pool: Arc<SqlitePool>) -> Self {
-        Self { pool }
+    pub fn new(pool: Arc<SqlitePool>, hasher: ChainHasher) -> Self {
+        Self { pool, hasher }
     }
 
-    /// Creates a new history entry
-    pub async fn create(
-        &self,
-        user_id: Option<i64>,
-        action: String,
-        entity_id: Option<i64>,
-        details: Option<serde_json::Value>,
-        ip_address: Option<String>,
-        user_agent: Option<String>,
-    ) -> Result<History, AppError> {
-        let details_str = details.as_ref().map(|d| d.to_string());
-
-        let result = sqlx::query(
+    /// Creates a new history entry (`id` and `created_at` of `entry` are assigned by the database)
+    /// and links it to the hash chain. The transaction holds the write lock throughout, so the
+    /// previous row cannot change before the entry is sealed.
+    pub async fn create(&self, entry: History) -> Result<History, AppError> {
+        let mut tx = self.pool.begin_with(BEGIN_IMMEDIATE).await.map_err(|e| {
+            error!("Failed to begin history transaction: {}", e);
+            AppError::DatabaseError(e)
+        })?;
+
+        let result = sqlx::query_as::<_, HistoryDb>(
             r#"
-            INSERT INTO history (user_id, action, entity_id, details, ip_address, user_agent)
-            VALUES (?, ?, ?, ?, ?, ?)
-            RETURNING *
+            INSERT INTO history (user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            RETURNING id, user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id, created_at
             "#,
         )
-        .bind(user_id)
-        .bind(action)
-        .bind(entity_id)
-        .bind(details_str)
-        .bind(ip_address)
-        .bind(user_agent)
-        .map(|row: sqlx::sqlite::SqliteRow| HistoryDb {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            action: row.get("action"),
-            entity_id: row.get("entity_id"),
-            details: row.get("details"),
-            ip_address: row.get("ip_address"),
-            user_agent: row.get("user_agent"),
-            created_at: row.get("created_at"),
-        })
-        .fetch_one(&*self.pool)
+        .bind(entry.user_id)
+        .bind(entry.action)
+        .bind(entry.entity_type)
+        .bind(entry.entity_id)
+        .bind(entry.details)
+        .bind(entry.ip_address)
+        .bind(entry.user_agent)
+        .bind(entry.request_id)
+        .fetch_one(&mut *tx)
         .await
         .map_err(|e| {
             error!("Failed to create history: {}", e);
             AppError::DatabaseError(e)
         })?;
 
+        self.seal(&mut tx, result.id).await?;
+        tx.commit().await.map_err(|e| {
+            error!("Failed to commit history: {}", e);
+            AppError::DatabaseError(e)
+        })?;
+
         Ok(result.into())
     }
 
+    /// Links rows written before the hash chain existed, in id order. Only rows older than the
+    /// first sealed one qualify, so a row slipped in later stays unsealed and fails verification.
+    pub async fn seal_legacy(&self) -> Result<u64, AppError> {
+        let mut tx = self
+            .pool
+            .begin_with(BEGIN_IMMEDIATE)
+            .await
+            .map_err(AppError::DatabaseError)?;
+
+        let ids: Vec<i64> = sqlx::query_scalar(
+            "SELECT id FROM history
+             WHERE hash IS NULL
+               AND id < COALESCE((SELECT MIN(id) FROM history WHERE hash IS NOT NULL), 9223372036854775807)
+             ORDER BY id",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .map_err(|e| {
+            error!("Failed to find unsealed history: {}", e);
+            AppError::DatabaseError(e)
+        })?;
+
+        for id in &ids {
+            self.seal(&mut tx, *id).await?;
+        }
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+
+        Ok(ids.len() as u64)
+    }
+
+    /// Stores `prev_hash` and `hash` of a row, linking it to the row before it
+    async fn seal(&self, conn: &mut SqliteConnection, id: i64) -> Result<(), AppError> {
+        let link = sqlx::query_as::<_, HistoryLink>(&format!(
+            "SELECT {} FROM history WHERE id = ?",
+            LINK_COLUMNS
+        ))
+        .bind(id)
+        .fetch_one(&mut *conn)
+        .await
+        .map_err(AppError::DatabaseError)?;
+
+        let previous: Option<Option<String>> =
+            sqlx::query_scalar("SELECT hash FROM history WHERE id < ? ORDER BY id DESC LIMIT 1")
+                .bind(id)
+                .fetch_optional(&mut *conn)
+                .await
+                .map_err(AppError::DatabaseError)?;
+        let prev_hash = match previous {
+            Some(hash) => hash.unwrap_or_else(|| GENESIS_HASH.to_string()),
+            None => sqlx::query_scalar(
+                "SELECT through_hash FROM history_checkpoint
+                 WHERE through_id < ? ORDER BY through_id DESC, id DESC LIMIT 1",
+            )
+            .bind(id)
+            .fetch_optional(&mut *conn)
+            .await
+            .map_err(AppError::DatabaseError)?
+            .unwrap_or_else(|| GENESIS_HASH.to_string()),
+        };
+
+        let hash = self.hasher.hash(&link.content(&prev_hash));
+        sqlx::query("UPDATE history SET prev_hash = ?, hash = ? WHERE id = ?")
+            .bind(&prev_hash)
+            .bind(hash)
+            .bind(id)
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| {
+                error!("Failed to seal history {}: {}", id, e);
+                AppError::DatabaseError(e)
+            })?;
+
+        Ok(())
+    }
+
+    /// Chain columns of up to `limit` rows after `after_id` (and up to `to_id`), in id order
+    pub async fn find_links(
+        &self,
+        after_id: i64,
+        to_id: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<HistoryLink>, AppError> {
+        sqlx::query_as::<_, HistoryLink>(&format!(
+            "SELECT {} FROM history WHERE id > ? AND id <= ? ORDER BY id LIMIT ?",
+            LINK_COLUMNS
+        ))
+        .bind(after_id)
+        .bind(to_id.unwrap_or(i64::MAX))
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to fetch history chain: {}", e);
+            AppError::DatabaseError(e)
+        })
+    }
+
+    /// Up to `limit` rows matching the filters of `query` after `after_id`, in id order. Exports
+    /// page through with this so no read stays open while a slow client downloads.
+    pub async fn find_matching_links(
+        &self,
+        query: &HistoryListQuery,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<HistoryLink>, AppError> {
+        let mut builder = QueryBuilder::new(format!(
+            "SELECT * FROM (SELECT {} FROM history",
+            LINK_COLUMNS
+        ));
+        query.push_conditions(&mut builder);
+        builder
+            .push(") WHERE id > ")
+            .push_bind(after_id)
+            .push(" ORDER BY id LIMIT ")
+            .push_bind(limit);
+
+        builder
+            .build_query_as::<HistoryLink>()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to fetch history for export: {}", e);
+                AppError::DatabaseError(e)
+            })
+    }
+
+    /// The row right before `id`, if it still exists
+    pub async fn find_link_before(&self, id: i64) -> Result<Option<HistoryLink>, AppError> {
+        sqlx::query_as::<_, HistoryLink>(&format!(
+            "SELECT {} FROM history WHERE id < ? ORDER BY id DESC LIMIT 1",
+            LINK_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(AppError::DatabaseError)
+    }
+
+    /// The latest checkpoint covering rows before `id`
+    pub async fn find_checkpoint_before(
+        &self,
+        id: i64,
+    ) -> Result<Option<HistoryCheckpoint>, AppError> {
+        sqlx::query_as::<_, HistoryCheckpoint>(&format!(
+            "SELECT {} FROM history_checkpoint
+             WHERE through_id < ?
+             ORDER BY through_id DESC, id DESC
+             LIMIT 1",
+            CHECKPOINT_COLUMNS
+        ))
+        .bind(id)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(AppError::DatabaseError)
+    }
+
+    /// Checkpoints of runs removed between two remaining rows, in the order they were removed
+    pub async fn find_checkpoints_between(
+        &self,
+        after_id: i64,
+        before_id: i64,
+    ) -> Result<Vec<HistoryCheckpoint>, AppError> {
+        sqlx::query_as::<_, HistoryCheckpoint>(&format!(
+            "SELECT {} FROM history_checkpoint
+             WHERE through_id > ? AND through_id < ?
+             ORDER BY through_id, id",
+            CHECKPOINT_COLUMNS
+        ))
+        .bind(after_id)
+        .bind(before_id)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(AppError::DatabaseError)
+    }
+
     /// Retrieves a list of history with pagination and filtering
     pub async fn list(&self, query: &HistoryListQuery) -> Result<Vec<History>, AppError> {
-        let (where_clause, _args) = query.to_sql_conditions();
-        let limit = query.limit.unwrap_or(50);
-        let offset = query.offset.unwrap_or(0);
-
-        let query_str = format!(
-            "SELECT id, user_id, action, entity_id, details, ip_address, user_agent, created_at 
-             FROM history
-             {}
-             ORDER BY created_at DESC
-             LIMIT ? OFFSET ?",
-            where_clause
+        let mut builder = QueryBuilder::new(
+            "SELECT id, user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id, created_at
+             FROM history",
         );
+        query.push_conditions(&mut builder);
+        builder
+            .push(" ORDER BY created_at DESC, id DESC LIMIT ")
+            .push_bind(query.get_limit())
+            .push(" OFFSET ")
+            .push_bind(query.get_offset());
 
-        // Build and execute the query with parameters
-        // The parameters are already bound in the SQL query string with ? placeholders
-        // and will be filled in by SQLx when the query is executed
-        let query_builder = sqlx::query_as::<_, HistoryDb>(&query_str)
-            .bind(limit)
-            .bind(offset);
-
-        let logs = query_builder
+        let logs = builder
+            .build_query_as::<HistoryDb>()
             .fetch_all(&*self.pool)
             .await
             .map_err(|e| {
@@ -134,7 +313,7 @@ impl HistoryRepository {
         offset: i64,
     ) -> Result<Vec<History>, AppError> {
         let logs = sqlx::query_as::<_, HistoryDb>(
-            "SELECT id, user_id, action, entity_id, details, ip_address, user_agent, created_at 
+            "SELECT id, user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id, created_at 
              FROM history 
              ORDER BY created_at DESC 
              LIMIT ? OFFSET ?",
@@ -157,7 +336,7 @@ impl HistoryRepository {
     /// Finds a history by its ID
     pub async fn find_by_id(&self, id: i64) -> Result<Option<History>, AppError> {
         let log = sqlx::query_as::<_, HistoryDb>(
-            "SELECT id, user_id, action, entity_id, details, ip_address, user_agent, created_at 
+            "SELECT id, user_id, action, entity_type, entity_id, details, ip_address, user_agent, request_id, created_at 
              FROM history 
              WHERE id = ?",
         )
@@ -173,29 +352,133 @@ impl HistoryRepository {
         Ok(log)
     }
 
-    /// Deletes history older than the specified number of days
-    pub async fn delete_older_than_days(&self, days: i64) -> Result<u64, AppError> {
-        let threshold = Utc::now() - chrono::Duration::days(days);
+    /// Up to `limit` rows after `after_id` that are past their retention cutoff, in id order.
+    /// A row expires when its `created_at` is before the cutoff of its action, or the default
+    /// cutoff for actions without a policy; a `None` cutoff keeps rows forever.
+    pub async fn find_expired(
+        &self,
+        default_cutoff: Option<&str>,
+        action_cutoffs: &[(String, Option<String>)],
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<ExpiredHistory>, AppError> {
+        let mut builder = QueryBuilder::new(format!(
+            "SELECT {}, (SELECT MAX(p.id) FROM history p WHERE p.id < history.id) AS previous_id
+             FROM history
+             WHERE id > ",
+            LINK_COLUMNS
+        ));
+        builder.push_bind(after_id).push(" AND created_at < ");
+        if action_cutoffs.is_empty() {
+            builder.push_bind(default_cutoff);
+        } else {
+            builder.push("CASE action");
+            for (action, cutoff) in action_cutoffs {
+                builder
+                    .push(" WHEN ")
+                    .push_bind(action)
+                    .push(" THEN ")
+                    .push_bind(cutoff);
+            }
+            builder
+                .push(" ELSE ")
+                .push_bind(default_cutoff)
+                .push(" END");
+        }
+        builder.push(" ORDER BY id LIMIT ").push_bind(limit);
+
+        builder
+            .build_query_as::<ExpiredHistory>()
+            .fetch_all(&*self.pool)
+            .await
+            .map_err(|e| {
+                error!("Failed to find expired history: {}", e);
+                AppError::DatabaseError(e)
+            })
+    }
+
+    /// Deletes runs of consecutive rows, leaving a signed checkpoint for each so the chain
+    /// can be verified across the gap. Returns the number of rows deleted.
+    /// Fails without deleting anything if a run is not linked to the chain yet, since its
+    /// checkpoint would have no hashes to bridge the gap with.
+    pub async fn prune(&self, runs: &[Vec<HistoryLink>]) -> Result<u64, AppError> {
+        let mut tx = self
+            .pool
+            .begin_with(BEGIN_IMMEDIATE)
+            .await
+            .map_err(AppError::DatabaseError)?;
+
+        let mut deleted = 0;
+        for run in runs {
+            let (Some(first), Some(last)) = (run.first(), run.last()) else {
+                continue;
+            };
+            // 체인에 연결되지 않은 행을 지우면 빈 해시로 체크포인트를 서명하게 되므로 중단(롤백)
+            let (Some(anchor_hash), Some(through_hash)) = (&first.prev_hash, &last.hash) else {
+                error!(
+                    "Refusing to prune history {}..{}: not linked to the hash chain",
+                    first.id, last.id
+                );
+                return Err(AppError::InternalServerError(format!(
+                    "History {}..{} is not linked to the hash chain yet and cannot be pruned",
+                    first.id, last.id
+                )));
+            };
 
-        let result = sqlx::query("DELETE FROM history WHERE created_at < ?")
-            .bind(threshold)
-            .execute(&*self.pool)
+            let result = sqlx::query("DELETE FROM history WHERE id BETWEEN ? AND ?")
+                .bind(first.id)
+                .bind(last.id)
+                .execute(&mut *tx)
+                .await
+                .map_err(|e| {
+                    error!("Failed to delete expired history: {}", e);
+                    AppError::DatabaseError(e)
+                })?;
+
+            let mut checkpoint = HistoryCheckpoint {
+                id: 0,
+                from_id: Some(first.id),
+                through_id: last.id,
+                anchor_hash: Some(anchor_hash.clone()),
+                through_hash: through_hash.clone(),
+                deleted_count: result.rows_affected() as i64,
+                created_at: Utc::now().format(SQLITE_DATETIME_FORMAT).to_string(),
+                signature: String::new(),
+            };
+            checkpoint.signature = self.hasher.hash(&checkpoint.content());
+            sqlx::query(
+                "INSERT INTO history_checkpoint (from_id, through_id, anchor_hash, through_hash, deleted_count, created_at, signature)
+                 VALUES (?, ?, ?, ?, ?, ?, ?)",
+            )
+            .bind(checkpoint.from_id)
+            .bind(checkpoint.through_id)
+            .bind(&checkpoint.anchor_hash)
+            .bind(&checkpoint.through_hash)
+            .bind(checkpoint.deleted_count)
+            .bind(&checkpoint.created_at)
+            .bind(&checkpoint.signature)
+            .execute(&mut *tx)
             .await
             .map_err(|e| {
-                error!("Failed to delete old history: {}", e);
-                AppError::InternalServerError("Failed to delete old history".to_string())
+                error!("Failed to record history checkpoint: {}", e);
+                AppError::DatabaseError(e)
             })?;
 
-        Ok(result.rows_affected())
+            deleted += result.rows_affected();
+        }
+
+        tx.commit().await.map_err(AppError::DatabaseError)?;
+
+        Ok(deleted)
     }
 
     /// Counts the total number of history matching the query (for pagination)
     pub async fn count(&self, query: &HistoryListQuery) -> Result<i64, AppError> {
-        let (where_clause, _args) = query.to_sql_conditions();
-        let query_str = format!("SELECT COUNT(*) as count FROM history {}", where_clause);
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM history");
+        query.push_conditions(&mut builder);
 
-        let count = sqlx::query(&query_str)
-            .try_map(|row: sqlx::sqlite::SqliteRow| row.try_get::<i64, _>("count"))
+        let count = builder
+            .build_query_scalar::<i64>()
             .fetch_one(&*self.pool)
             .await
             .map_err(|e| {
@@ -206,3 +489,85 @@ impl HistoryRepository {
         Ok(count)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn repository() -> HistoryRepository {
+        let pool = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        sqlx::query("DELETE FROM history")
+            .execute(&pool)
+            .await
+            .unwrap();
+        HistoryRepository::new(Arc::new(pool), ChainHasher::new(None))
+    }
+
+    /// A cutoff every row written so far falls before
+    fn future_cutoff() -> String {
+        (Utc::now() + chrono::Duration::days(1))
+            .format(SQLITE_DATETIME_FORMAT)
+            .to_string()
+    }
+
+    async fn remaining_actions(repo: &HistoryRepository) -> Vec<String> {
+        sqlx::query_scalar("SELECT action FROM history ORDER BY id")
+            .fetch_all(&*repo.pool)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn retention_without_policies_uses_the_default_cutoff() {
+        let repo = repository().await;
+        for action in ["login_success", "login_failed", "user_updated"] {
+            repo.create(History::system(action, None, None))
+                .await
+                .unwrap();
+        }
+
+        let cutoff = future_cutoff();
+        let expired = repo.find_expired(Some(&cutoff), &[], 0, 100).await.unwrap();
+        assert_eq!(expired.len(), 3);
+
+        let run: Vec<HistoryLink> = expired.into_iter().map(|row| row.link).collect();
+        assert_eq!(repo.prune(&[run]).await.unwrap(), 3);
+        assert!(remaining_actions(&repo).await.is_empty());
+
+        let checkpoints: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM history_checkpoint")
+            .fetch_one(&*repo.pool)
+            .await
+            .unwrap();
+        assert_eq!(checkpoints, 1);
+    }
+
+    #[tokio::test]
+    async fn retention_policy_overrides_the_default_cutoff() {
+        let repo = repository().await;
+        for action in ["login_failed", "user_deleted", "login_failed"] {
+            repo.create(History::system(action, None, None))
+                .await
+                .unwrap();
+        }
+
+        // user_deleted은 영구 보관, 나머지는 기본 기간 적용
+        let cutoff = future_cutoff();
+        let policies = [("user_deleted".to_string(), None)];
+        let expired = repo
+            .find_expired(Some(&cutoff), &policies, 0, 100)
+            .await
+            .unwrap();
+        let actions: Vec<&str> = expired.iter().map(|row| row.link.action.as_str()).collect();
+        assert_eq!(actions, ["login_failed", "login_failed"]);
+
+        let runs: Vec<Vec<HistoryLink>> = expired.into_iter().map(|row| vec![row.link]).collect();
+        assert_eq!(repo.prune(&runs).await.unwrap(), 2);
+        assert_eq!(remaining_actions(&repo).await, ["user_deleted"]);
+    }
+}
@@ -0,0 +1,61 @@
+use crate::{errors::AppError, model::entity::history_retention_run::HistoryRetentionRun};
+use sqlx::sqlite::SqlitePool;
+use std::sync::Arc;
+use tracing::error;
+
+/// Repository for the runs of the history retention job
+#[derive(Clone)]
+pub struct HistoryRetentionRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl HistoryRetentionRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    /// Records the start of a run and returns its id
+    pub async fn start_run(&self, triggered_by: &str) -> Result<i64, AppError> {
+        sqlx::query_scalar(
+            "INSERT INTO history_retention_run (triggered_by) VALUES (?) RETURNING id",
+        )
+        .bind(triggered_by)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to record retention run: {}", e);
+            AppError::DatabaseError(e)
+        })
+    }
+
+    /// Records the outcome of a run
+    pub async fn finish_run(
+        &self,
+        id: i64,
+        status: &str,
+        archived_count: i64,
+        deleted_count: i64,
+        archive_files: i64,
+        error: Option<String>,
+    ) -> Result<HistoryRetentionRun, AppError> {
+        sqlx::query_as::<_, HistoryRetentionRun>(
+            "UPDATE history_retention_run
+             SET status = ?, archived_count = ?, deleted_count = ?, archive_files = ?, error = ?,
+                 finished_at = CURRENT_TIMESTAMP
+             WHERE id = ?
+             RETURNING id, triggered_by, status, archived_count, deleted_count, archive_files, error, started_at, finished_at",
+        )
+        .bind(status)
+        .bind(archived_count)
+        .bind(deleted_count)
+        .bind(archive_files)
+        .bind(error)
+        .bind(id)
+        .fetch_one(&*self.pool)
+        .await
+        .map_err(|e| {
+            error!("Failed to finish retention run {}: {}", id, e);
+            AppError::DatabaseError(e)
+        })
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{errors::AppError, model::entity::login_throttle::LoginThrottleEntry};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct LoginThrottleRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl LoginThrottleRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find(&self, key: &str) -> Result<Option<LoginThrottleEntry>, AppError> {
+        let entry = sqlx::query_as!(
+            LoginThrottleEntry,
+            r#"SELECT key as "key!", failures, first_failure_at, last_failure_at, locked_until
+            FROM login_throttle
+            WHERE key = ?"#,
+            key
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Counts one failure with a single upsert, so concurrent failures cannot overwrite each
+    /// other. A counter whose lockout has passed or whose window has elapsed is dropped first
+    /// so that the failure starts it over; a counter reaching `max_failures` is locked out.
+    pub async fn increment_failures(
+        &self,
+        key: &str,
+        now: i64,
+        window_secs: i64,
+        max_failures: i64,
+        lockout_secs: i64,
+    ) -> Result<LoginThrottleEntry, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"DELETE FROM login_throttle
+            WHERE key = ?1
+              AND (locked_until <= ?2 OR (locked_until IS NULL AND ?2 - first_failure_at >= ?3))"#,
+            key,
+            now,
+            window_secs
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let entry = sqlx::query_as!(
+            LoginThrottleEntry,
+            r#"INSERT INTO login_throttle (key, failures, first_failure_at, last_failure_at, locked_until)
+            VALUES (?1, 1, ?2, ?2, CASE WHEN 1 >= ?3 THEN ?2 + ?4 END)
+            ON CONFLICT(key) DO UPDATE
+            SET failures = failures + 1,
+                last_failure_at = excluded.last_failure_at,
+                locked_until = CASE WHEN failures + 1 >= ?3 THEN ?2 + ?4 ELSE locked_until END
+            RETURNING key as "key!", failures, first_failure_at, last_failure_at, locked_until"#,
+            key,
+            now,
+            max_failures,
+            lockout_secs
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(entry)
+    }
+
+    pub async fn delete(&self, key: &str) -> Result<bool, AppError> {
+        let result = sqlx::query!("DELETE FROM login_throttle WHERE key = ?", key)
+            .execute(&*self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_all(&self) -> Result<Vec<LoginThrottleEntry>, AppError> {
+        let entries = sqlx::query_as!(
+            LoginThrottleEntry,
+            r#"SELECT key as "key!", failures, first_failure_at, last_failure_at, locked_until
+            FROM login_throttle
+            ORDER BY last_failure_at DESC"#
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,197 @@
+use crate::{errors::AppError, model::entity::user_mfa::UserMfa};
+use sqlx::SqlitePool;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct MfaRepository {
+    pool: Arc<SqlitePool>,
+}
+
+impl MfaRepository {
+    pub fn new(pool: Arc<SqlitePool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn find_by_user(&self, user_id: i64) -> Result<Option<UserMfa>, AppError> {
+        let mfa = sqlx::query_as!(
+            UserMfa,
+            r#"SELECT
+                admin_user.id as "user_id!",
+                admin_user.username as "username!",
+                admin_user.totp_secret,
+                admin_user.totp_enabled_at,
+                admin_user.totp_last_step,
+                user_type.require_mfa as "require_mfa!: bool"
+            FROM admin_user
+            INNER JOIN user_type ON admin_user.user_type_id = user_type.id
+            WHERE admin_user.id = ?"#,
+            user_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(mfa)
+    }
+
+    /// Stores a new, not yet confirmed TOTP secret. Returns `false` if TOTP is already enabled.
+    pub async fn set_pending_secret(&self, user_id: i64, secret: &str) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE admin_user
+            SET totp_secret = ?, totp_last_step = NULL, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND totp_enabled_at IS NULL"#,
+            secret,
+            user_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Activates the pending secret and replaces the recovery codes in one transaction
+    pub async fn enable(
+        &self,
+        user_id: i64,
+        step: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<bool, AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"UPDATE admin_user
+            SET totp_enabled_at = CURRENT_TIMESTAMP, totp_last_step = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND totp_secret IS NOT NULL AND totp_enabled_at IS NULL"#,
+            step,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!("DELETE FROM user_recovery_code WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+        for hash in recovery_code_hashes {
+            sqlx::query!(
+                "INSERT INTO user_recovery_code (user_id, code_hash) VALUES (?, ?)",
+                user_id,
+                hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Removes the TOTP secret and every recovery code of the user
+    pub async fn disable(&self, user_id: i64) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"UPDATE admin_user
+            SET totp_secret = NULL, totp_enabled_at = NULL, totp_last_step = NULL,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE id = ?"#,
+            user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!("DELETE FROM user_recovery_code WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Records `step` as the last accepted TOTP step. Returns `false` if the same or a later
+    /// step was already used, i.e. the code is being replayed.
+    pub async fn mark_step_used(&self, user_id: i64, step: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE admin_user
+            SET totp_last_step = ?
+            WHERE id = ? AND (totp_last_step IS NULL OR totp_last_step < ?)"#,
+            step,
+            user_id,
+            step
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn replace_recovery_codes(
+        &self,
+        user_id: i64,
+        recovery_code_hashes: &[String],
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM user_recovery_code WHERE user_id = ?", user_id)
+            .execute(&mut *tx)
+            .await?;
+        for hash in recovery_code_hashes {
+            sqlx::query!(
+                "INSERT INTO user_recovery_code (user_id, code_hash) VALUES (?, ?)",
+                user_id,
+                hash
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Marks a recovery code as used. Returns `false` if it does not exist or was already used.
+    pub async fn consume_recovery_code(
+        &self,
+        user_id: i64,
+        code_hash: &str,
+    ) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"UPDATE user_recovery_code
+            SET used_at = CURRENT_TIMESTAMP
+            WHERE user_id = ? AND code_hash = ? AND used_at IS NULL"#,
+            user_id,
+            code_hash
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn find_unused_recovery_code_hashes(
+        &self,
+        user_id: i64,
+    ) -> Result<Vec<String>, AppError> {
+        let hashes = sqlx::query_scalar!(
+            "SELECT code_hash FROM user_recovery_code WHERE user_id = ? AND used_at IS NULL ORDER BY id",
+            user_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    pub async fn count_unused_recovery_codes(&self, user_id: i64) -> Result<i64, AppError> {
+        let count = sqlx::query_scalar!(
+            "SELECT COUNT(*) FROM user_recovery_code WHERE user_id = ? AND used_at IS NULL",
+            user_id
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(count)
+    }
+}
@@ -1,44 +1,21 @@
+pub mod api_key;
 pub mod auth;
 pub mod history;
+pub mod history_retention;
+pub mod login_throttle;
+pub mod mfa;
 pub mod oauth;
 pub mod permission;
 pub mod user;
 pub mod user_type;
 
-use async_trait::async_trait;
+pub use api_key::ApiKeyRepository;
 pub use auth::AuthRepository;
 pub use history::HistoryRepository;
+pub use history_retention::HistoryRetentionRepository;
+pub use login_throttle::LoginThrottleRepository;
+pub use mfa::MfaRepository;
 pub use oauth::OAuthRepository;
 pub use permission::PermissionRepository;
-use sqlx::SqlitePool;
-use std::sync::Arc;
 pub use user::UserRepository;
 pub use user_type::UserTypeRepository;
-
-#[async_trait]
-pub trait Repository: Send + Sync + 'static {
-    type Pool;
-    fn new(pool: Arc<SqlitePool>) -> Self;
-}
-
-// Implement Repository for all repository types
-macro_rules! impl_repository {
-    ($repo:ty) => {
-        #[async_trait::async_trait]
-        impl Repository for $repo {
-            type Pool = SqlitePool;
-
-            fn new(pool: Arc<SqlitePool>) -> Self {
-                Self::new(pool)
-            }
-        }
-    };
-}
-
-// Implement Repository for all repository types
-impl_repository!(AuthRepository);
-impl_repository!(HistoryRepository);
-impl_repository!(OAuthRepository);
-impl_repository!(PermissionRepository);
-impl_repository!(UserRepository);
-impl_repository!(UserTypeRepository);
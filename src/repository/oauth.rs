@@ -1,7 +1,8 @@
+use crate::errors::AppError;
 use crate::model::entity::{
     oauth_client::OAuthClient, oauth_code::OAuthCode, oauth_token::OAuthToken,
 };
-use chrono::TimeZone;
+use chrono::{DateTime, TimeZone, Utc};
 use sqlx::SqlitePool;
 use std::sync::Arc;
 
@@ -18,16 +19,115 @@ impl OAuthRepository {
 
     pub async fn find_client(&self, client_id: &str) -> Option<OAuthClient> {
         sqlx::query_as::<_, OAuthClient>(
-            r#"SELECT id, client_id, client_secret, redirect_uri, scope, grant_types FROM oauth_client WHERE client_id = ?"#,
+            r#"SELECT id, client_id, name, client_secret_hash, previous_secret_hash, previous_secret_expires_at, redirect_uris, scope, grant_types, permissions, created_at FROM oauth_client WHERE client_id = ?"#,
         )
         .bind(client_id)
         .fetch_optional(self.pool.as_ref())
         .await
         .ok()?
     }
+    pub async fn find_client_by_id(&self, id: i64) -> Result<OAuthClient, AppError> {
+        sqlx::query_as::<_, OAuthClient>(
+            r#"SELECT id, client_id, name, client_secret_hash, previous_secret_hash, previous_secret_expires_at, redirect_uris, scope, grant_types, permissions, created_at FROM oauth_client WHERE id = ?"#,
+        )
+        .bind(id)
+        .fetch_optional(self.pool.as_ref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("OAuth client not found".to_string()))
+    }
+    pub async fn find_all_clients(&self) -> Result<Vec<OAuthClient>, AppError> {
+        let clients = sqlx::query_as::<_, OAuthClient>(
+            r#"SELECT id, client_id, name, client_secret_hash, previous_secret_hash, previous_secret_expires_at, redirect_uris, scope, grant_types, permissions, created_at FROM oauth_client ORDER BY id"#,
+        )
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        Ok(clients)
+    }
+    /// Registers a client; `client_secret_hash` is the SHA-256 hash of the generated secret
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_client(
+        &self,
+        client_id: &str,
+        name: &str,
+        redirect_uris: &str,
+        scope: &str,
+        grant_types: &str,
+        permissions: &str,
+        client_secret_hash: &str,
+    ) -> Result<i64, AppError> {
+        let id: i64 = sqlx::query_scalar(
+            r#"INSERT INTO oauth_client (client_id, name, client_secret_hash, redirect_uris, scope, grant_types, permissions) VALUES (?, ?, ?, ?, ?, ?, ?) RETURNING id"#,
+        )
+        .bind(client_id)
+        .bind(name)
+        .bind(client_secret_hash)
+        .bind(redirect_uris)
+        .bind(scope)
+        .bind(grant_types)
+        .bind(permissions)
+        .fetch_one(self.pool.as_ref())
+        .await?;
+        Ok(id)
+    }
+    pub async fn update_client(
+        &self,
+        id: i64,
+        name: &str,
+        redirect_uris: &str,
+        scope: &str,
+        grant_types: &str,
+        permissions: &str,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"UPDATE oauth_client SET name = ?, redirect_uris = ?, scope = ?, grant_types = ?, permissions = ? WHERE id = ?"#,
+        )
+        .bind(name)
+        .bind(redirect_uris)
+        .bind(scope)
+        .bind(grant_types)
+        .bind(permissions)
+        .bind(id)
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
+    /// Deletes a client together with its codes and tokens (ON DELETE CASCADE)
+    pub async fn delete_client(&self, id: i64) -> Result<(), AppError> {
+        let result = sqlx::query(r#"DELETE FROM oauth_client WHERE id = ?"#)
+            .bind(id)
+            .execute(self.pool.as_ref())
+            .await?;
+        if result.rows_affected() == 0 {
+            return Err(AppError::NotFound("OAuth client not found".to_string()));
+        }
+        Ok(())
+    }
+    /// Installs a new secret hash, keeping the current one valid until `previous_expires_at`
+    pub async fn rotate_client_secret(
+        &self,
+        id: i64,
+        client_secret_hash: &str,
+        previous_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), AppError> {
+        sqlx::query(
+            r#"
+            UPDATE oauth_client
+            SET previous_secret_hash = CASE WHEN ? IS NULL THEN NULL ELSE client_secret_hash END,
+                previous_secret_expires_at = ?,
+                client_secret_hash = ?
+            WHERE id = ?"#,
+        )
+        .bind(previous_expires_at.map(|t| t.naive_utc()))
+        .bind(previous_expires_at.map(|t| t.naive_utc()))
+        .bind(client_secret_hash)
+        .bind(id)
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
     pub async fn save_code(&self, code: &OAuthCode) -> anyhow::Result<()> {
         sqlx::query(
-            r#"INSERT INTO oauth_code (code, client_id, user_id, redirect_uri, scope, expires_at) VALUES (?, ?, ?, ?, ?, ?)"#
+            r#"INSERT INTO oauth_code (code, client_id, user_id, redirect_uri, scope, expires_at, code_challenge, code_challenge_method, nonce) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#
         )
         .bind(&code.code)
         .bind(&code.client_id)
@@ -35,10 +135,14 @@ impl OAuthRepository {
         .bind(&code.redirect_uri)
         .bind(&code.scope)
         .bind(code.expires_at)
+        .bind(&code.code_challenge)
+        .bind(&code.code_challenge_method)
+        .bind(&code.nonce)
         .execute(self.pool.as_ref())
         .await?;
         Ok(())
     }
+    /// Looks up a code including used and expired ones, so the caller can tell a replay apart
     pub async fn find_code(&self, code: &str) -> Option<OAuthCode> {
         #[derive(sqlx::FromRow)]
         struct OAuthCodeRow {
@@ -48,6 +152,10 @@ impl OAuthRepository {
             redirect_uri: String,
             scope: Option<String>,
             expires_at: chrono::NaiveDateTime,
+            code_challenge: Option<String>,
+            code_challenge_method: Option<String>,
+            used_at: Option<chrono::NaiveDateTime>,
+            nonce: Option<String>,
         }
         let row: Option<OAuthCodeRow> = sqlx::query_as(
             r#"
@@ -57,9 +165,13 @@ impl OAuthRepository {
                 user_id, 
                 redirect_uri, 
                 scope, 
-                expires_at 
+                expires_at, 
+                code_challenge, 
+                code_challenge_method, 
+                used_at, 
+                nonce 
             FROM oauth_code 
-            WHERE code = ? AND expires_at > CURRENT_TIMESTAMP"#,
+            WHERE code = ?"#,
         )
         .bind(code)
         .fetch_optional(self.pool.as_ref())
@@ -72,11 +184,33 @@ impl OAuthRepository {
             redirect_uri: r.redirect_uri,
             scope: r.scope,
             expires_at: chrono::Utc.from_utc_datetime(&r.expires_at),
+            code_challenge: r.code_challenge,
+            code_challenge_method: r.code_challenge_method,
+            used_at: r.used_at.map(|t| chrono::Utc.from_utc_datetime(&t)),
+            nonce: r.nonce,
         })
     }
+    /// Marks the code as exchanged. Returns false if it was already used (a concurrent replay).
+    pub async fn mark_code_used(&self, code: &str) -> anyhow::Result<bool> {
+        let result = sqlx::query(
+            r#"UPDATE oauth_code SET used_at = CURRENT_TIMESTAMP WHERE code = ? AND used_at IS NULL"#,
+        )
+        .bind(code)
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+    /// Deletes every token issued from the given authorization code
+    pub async fn delete_tokens_by_code(&self, code: &str) -> anyhow::Result<u64> {
+        let result = sqlx::query(r#"DELETE FROM oauth_token WHERE authorization_code = ?"#)
+            .bind(code)
+            .execute(self.pool.as_ref())
+            .await?;
+        Ok(result.rows_affected())
+    }
     pub async fn save_token(&self, token: &OAuthToken) -> anyhow::Result<()> {
         sqlx::query(
-            r#"INSERT INTO oauth_token (access_token, refresh_token, client_id, user_id, scope, expires_at, created_at) VALUES (?, ?, ?, ?, ?, ?, ?)"#
+            r#"INSERT INTO oauth_token (access_token, refresh_token, client_id, user_id, scope, expires_at, created_at, authorization_code, refresh_expires_at, token_version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
         )
         .bind(&token.access_token)
         .bind(&token.refresh_token)
@@ -85,6 +219,9 @@ impl OAuthRepository {
         .bind(&token.scope)
         .bind(token.expires_at)
         .bind(token.created_at)
+        .bind(&token.authorization_code)
+        .bind(token.refresh_expires_at)
+        .bind(token.token_version)
         .execute(self.pool.as_ref())
         .await?;
         Ok(())
@@ -99,6 +236,9 @@ impl OAuthRepository {
             scope: Option<String>,
             expires_at: chrono::NaiveDateTime,
             created_at: chrono::NaiveDateTime,
+            authorization_code: Option<String>,
+            refresh_expires_at: Option<chrono::NaiveDateTime>,
+            token_version: Option<i64>,
         }
 
         let row: Option<OAuthTokenRow> = sqlx::query_as(
@@ -110,7 +250,10 @@ impl OAuthRepository {
                 user_id, 
                 scope, 
                 expires_at, 
-                created_at 
+                created_at, 
+                authorization_code, 
+                refresh_expires_at, 
+                token_version 
             FROM oauth_token 
             WHERE access_token = ? AND expires_at > CURRENT_TIMESTAMP"#,
         )
@@ -127,15 +270,80 @@ impl OAuthRepository {
             scope: r.scope,
             expires_at: chrono::Utc.from_utc_datetime(&r.expires_at),
             created_at: chrono::Utc.from_utc_datetime(&r.created_at),
+            authorization_code: r.authorization_code,
+            refresh_expires_at: r
+                .refresh_expires_at
+                .map(|t| chrono::Utc.from_utc_datetime(&t)),
+            token_version: r.token_version,
         })
     }
     pub async fn find_refresh_token(&self, refresh_token: &str) -> Option<OAuthToken> {
         sqlx::query_as::<_, OAuthToken>(
-            r#"SELECT access_token, refresh_token, client_id, user_id, scope, expires_at, created_at FROM oauth_token WHERE refresh_token = ? AND expires_at > CURRENT_TIMESTAMP"#,
+            r#"SELECT access_token, refresh_token, client_id, user_id, scope, expires_at, created_at, authorization_code, refresh_expires_at, token_version FROM oauth_token WHERE refresh_token = ? AND datetime(refresh_expires_at) > CURRENT_TIMESTAMP"#,
         )
         .bind(refresh_token)
         .fetch_optional(self.pool.as_ref())
         .await
         .ok()?
     }
+    /// Replaces the token row holding `refresh_token` with `token` in one transaction.
+    /// Returns false if the refresh token was already rotated by a concurrent request.
+    pub async fn rotate_refresh_token(
+        &self,
+        refresh_token: &str,
+        client_id: &str,
+        token: &OAuthToken,
+    ) -> anyhow::Result<bool> {
+        let mut tx = self.pool.begin().await?;
+        let deleted =
+            sqlx::query(r#"DELETE FROM oauth_token WHERE refresh_token = ? AND client_id = ?"#)
+                .bind(refresh_token)
+                .bind(client_id)
+                .execute(&mut *tx)
+                .await?;
+        if deleted.rows_affected() == 0 {
+            return Ok(false);
+        }
+        sqlx::query(
+            r#"INSERT INTO oauth_token (access_token, refresh_token, client_id, user_id, scope, expires_at, created_at, authorization_code, refresh_expires_at, token_version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#
+        )
+        .bind(&token.access_token)
+        .bind(&token.refresh_token)
+        .bind(&token.client_id)
+        .bind(token.user_id)
+        .bind(&token.scope)
+        .bind(token.expires_at)
+        .bind(token.created_at)
+        .bind(&token.authorization_code)
+        .bind(token.refresh_expires_at)
+        .bind(token.token_version)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+    /// Revokes an access token. Only tokens of `client_id` are affected.
+    pub async fn delete_token(&self, access_token: &str, client_id: &str) -> anyhow::Result<u64> {
+        let result =
+            sqlx::query(r#"DELETE FROM oauth_token WHERE access_token = ? AND client_id = ?"#)
+                .bind(access_token)
+                .bind(client_id)
+                .execute(self.pool.as_ref())
+                .await?;
+        Ok(result.rows_affected())
+    }
+    /// Revokes a refresh token together with the access tokens issued with it
+    pub async fn delete_refresh_token(
+        &self,
+        refresh_token: &str,
+        client_id: &str,
+    ) -> anyhow::Result<u64> {
+        let result =
+            sqlx::query(r#"DELETE FROM oauth_token WHERE refresh_token = ? AND client_id = ?"#)
+                .bind(refresh_token)
+                .bind(client_id)
+                .execute(self.pool.as_ref())
+                .await?;
+        Ok(result.rows_affected())
+    }
 }
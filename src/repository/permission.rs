@@ -147,6 +147,123 @@ impl PermissionRepository {
         self.find_by_id(id).await
     }
 
+    /// Returns every permission ordered by category, used to build the assignment matrix
+    pub async fn find_all_by_category(&self) -> Result<Vec<PermissionResponse>, AppError> {
+        let permissions = sqlx::query_as!(
+            Permission,
+            "SELECT * FROM permission ORDER BY category, code"
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(permissions
+            .into_iter()
+            .map(PermissionResponse::from)
+            .collect())
+    }
+
+    /// Returns the permissions assigned to a user type, regardless of whether it is active
+    pub async fn find_by_user_type(
+        &self,
+        user_type_id: i64,
+    ) -> Result<Vec<PermissionResponse>, AppError> {
+        let permissions = sqlx::query_as!(
+            Permission,
+            r#"SELECT p.id, p.code, p.name, p.description, p.category, p.created_at, p.updated_at
+            FROM permission p
+            INNER JOIN user_type_permission utp ON utp.permission_id = p.id
+            WHERE utp.user_type_id = ?
+            ORDER BY p.category, p.code"#,
+            user_type_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(permissions
+            .into_iter()
+            .map(PermissionResponse::from)
+            .collect())
+    }
+
+    /// Grants a permission to a user type. Returns `false` if it was already granted.
+    pub async fn grant(&self, user_type_id: i64, permission_id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            r#"INSERT INTO user_type_permission (user_type_id, permission_id)
+            VALUES (?, ?)
+            ON CONFLICT(user_type_id, permission_id) DO NOTHING"#,
+            user_type_id,
+            permission_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revokes a permission from a user type. Returns `false` if it was not granted.
+    pub async fn revoke(&self, user_type_id: i64, permission_id: i64) -> Result<bool, AppError> {
+        let result = sqlx::query!(
+            "DELETE FROM user_type_permission WHERE user_type_id = ? AND permission_id = ?",
+            user_type_id,
+            permission_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Replaces the full permission set of a user type in a single transaction
+    pub async fn replace_for_user_type(
+        &self,
+        user_type_id: i64,
+        permission_ids: &[i64],
+    ) -> Result<(), AppError> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            "DELETE FROM user_type_permission WHERE user_type_id = ?",
+            user_type_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        for permission_id in permission_ids {
+            sqlx::query!(
+                r#"INSERT INTO user_type_permission (user_type_id, permission_id)
+                VALUES (?, ?)
+                ON CONFLICT(user_type_id, permission_id) DO NOTHING"#,
+                user_type_id,
+                permission_id
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Returns the permission codes granted to an active user type
+    pub async fn find_codes_by_user_type(
+        &self,
+        user_type_id: i64,
+    ) -> Result<Vec<String>, AppError> {
+        let codes = sqlx::query_scalar!(
+            r#"SELECT p.code
+            FROM permission p
+            INNER JOIN user_type_permission utp ON utp.permission_id = p.id
+            INNER JOIN user_type ut ON ut.id = utp.user_type_id
+            WHERE utp.user_type_id = ? AND ut.is_active = 1
+            ORDER BY p.code"#,
+            user_type_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(codes)
+    }
+
     pub async fn count(&self) -> Result<i64, AppError> {
         let result = sqlx::query_scalar!(
             r#"
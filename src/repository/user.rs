@@ -157,6 +157,10 @@ impl UserRepository {
         if let Some(is_active) = is_active {
             updates.push("is_active = ?");
             args.add(is_active).expect("Failed to add is_active");
+            if !is_active {
+                // 비활성화 시 발급된 토큰을 모두 무효화
+                updates.push("token_version = token_version + 1");
+            }
         }
 
         if updates.is_empty() {
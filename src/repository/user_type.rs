@@ -38,13 +38,14 @@ impl UserTypeRepository {
         // Insert the new user type
         let result = sqlx::query!(
             r#"
-            INSERT INTO user_type (code, name, description, is_active)
-            VALUES (?, ?, ?, ?)
+            INSERT INTO user_type (code, name, description, is_active, require_mfa)
+            VALUES (?, ?, ?, ?, ?)
             "#,
             req.code,
             req.name,
             req.description,
-            req.is_active
+            req.is_active,
+            req.require_mfa
         )
         .execute(&*self.pool)
         .await?;
@@ -122,7 +123,7 @@ impl UserTypeRepository {
 
     pub async fn find_by_id(&self, type_id: i64) -> Result<UserTypeResponse, AppError> {
         let user_type = sqlx::query_as::<_, UserType>(
-            "SELECT id, code, name, description, is_active, created_at, updated_at FROM user_type WHERE id = ?",
+            "SELECT id, code, name, description, is_active, require_mfa, created_at, updated_at FROM user_type WHERE id = ?",
         )
         .bind(type_id)
         .fetch_optional(&*self.pool)
@@ -160,6 +161,11 @@ impl UserTypeRepository {
             let _ = params.add(is_active);
         }
 
+        if let Some(require_mfa) = req.require_mfa {
+            updates.push("require_mfa = ?");
+            let _ = params.add(require_mfa);
+        }
+
         if updates.is_empty() {
             return self.find_by_id(type_id).await;
         }
@@ -213,7 +219,7 @@ impl UserTypeRepository {
         user_type_id: i64,
     ) -> Result<Option<UserTypeResponse>, AppError> {
         let user_type = sqlx::query_as::<_, UserType>(
-            "SELECT id, code, name, description, is_active, created_at, updated_at FROM user_type WHERE id = ?",
+            "SELECT id, code, name, description, is_active, require_mfa, created_at, updated_at FROM user_type WHERE id = ?",
         )
         .bind(user_type_id)
         .fetch_optional(&*self.pool)
@@ -224,7 +230,7 @@ impl UserTypeRepository {
 
     pub async fn find_by_code(&self, code: &str) -> Result<Option<UserTypeResponse>, AppError> {
         let user_type = sqlx::query_as::<_, UserType>(
-            "SELECT id, code, name, description, is_active, created_at, updated_at FROM user_type WHERE code = ?",
+            "SELECT id, code, name, description, is_active, require_mfa, created_at, updated_at FROM user_type WHERE code = ?",
         )
         .bind(code)
         .fetch_optional(&*self.pool)
@@ -0,0 +1,205 @@
+use crate::{
+    config::auth::{
+        audit_context::AuditContext, authn_api_key::AuthnApiKey, authn_user::AuthnUser,
+    },
+    errors::AppError,
+    model::dto::api_key::{ApiKeyCreatedResponse, ApiKeyResponse, CreateApiKeyRequest},
+    repository::{api_key::ApiKeyRepository, auth::AuthRepository},
+    service::{
+        audit::{AuditEntity, AuditService},
+        permission::PermissionService,
+    },
+    util::token_util::hash_opaque_token,
+};
+use chrono::{Duration, Utc};
+use rand::Rng;
+use std::{collections::HashSet, sync::Arc};
+use tracing::{error, info};
+use validator::Validate;
+
+/// Prefix of every API key, so leaked keys can be found by secret scanners
+pub const API_KEY_PREFIX: &str = "adm_pk_";
+/// Length of the stored prefix shown in the key list (API_KEY_PREFIX + 8 characters)
+const KEY_PREFIX_DISPLAY_LEN: usize = API_KEY_PREFIX.len() + 8;
+
+/// Personal API keys for scripts and automation accounts
+pub struct ApiKeyService {
+    api_key_repo: ApiKeyRepository,
+    auth_repo: AuthRepository,
+    permission: Arc<PermissionService>,
+    audit: Arc<AuditService>,
+}
+
+impl ApiKeyService {
+    pub fn new(
+        api_key_repo: ApiKeyRepository,
+        auth_repo: AuthRepository,
+        permission: Arc<PermissionService>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            api_key_repo,
+            auth_repo,
+            permission,
+            audit,
+        }
+    }
+
+    pub async fn list_keys(&self, user_id: i64) -> Result<Vec<ApiKeyResponse>, AppError> {
+        let keys = self.api_key_repo.find_by_user(user_id).await?;
+        Ok(keys.into_iter().map(ApiKeyResponse::from).collect())
+    }
+
+    /// Creates a key limited to `req.permissions`, each of which the user must hold
+    pub async fn create_key(
+        &self,
+        ctx: &AuditContext,
+        authn_user: &AuthnUser,
+        req: CreateApiKeyRequest,
+    ) -> Result<ApiKeyCreatedResponse, AppError> {
+        req.validate()?;
+        let name = req.name.trim();
+        if name.is_empty() {
+            return Err(AppError::BadRequest("Name cannot be empty".to_string()));
+        }
+
+        let mut permissions: Vec<&str> = Vec::new();
+        for code in req.permissions.iter().map(|p| p.trim()) {
+            if !authn_user.permissions.contains(code) {
+                return Err(AppError::BadRequest(format!(
+                    "Permission '{}' is not granted to you",
+                    code
+                )));
+            }
+            if !permissions.contains(&code) {
+                permissions.push(code);
+            }
+        }
+
+        let api_key = format!(
+            "{}{}",
+            API_KEY_PREFIX,
+            hex::encode(rand::thread_rng().gen::<[u8; 32]>())
+        );
+        let expires_at = req
+            .expires_in_days
+            .map(|days| (Utc::now() + Duration::days(days)).naive_utc());
+        let key = self
+            .api_key_repo
+            .create(
+                authn_user.id,
+                name,
+                &api_key[..KEY_PREFIX_DISPLAY_LEN],
+                &hash_opaque_token(&api_key),
+                &permissions.join(" "),
+                expires_at,
+            )
+            .await?;
+
+        let key = ApiKeyResponse::from(key);
+        self.audit
+            .record_created(ctx, "api_key_created", AuditEntity::ApiKey(key.id), &key)
+            .await;
+        info!("User {} created API key {}", authn_user.id, key.id);
+
+        Ok(ApiKeyCreatedResponse { key, api_key })
+    }
+
+    pub async fn revoke_key(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        id: i64,
+    ) -> Result<(), AppError> {
+        let key = self
+            .api_key_repo
+            .find_by_user(user_id)
+            .await?
+            .into_iter()
+            .find(|key| key.id == id)
+            .map(ApiKeyResponse::from);
+        let (Some(key), true) = (key, self.api_key_repo.delete(id, user_id).await?) else {
+            return Err(AppError::NotFound("API key not found".to_string()));
+        };
+        self.audit
+            .record_deleted(ctx, "api_key_revoked", AuditEntity::ApiKey(id), &key)
+            .await;
+        info!("User {} revoked API key {}", user_id, id);
+        Ok(())
+    }
+
+    /// Resolves a presented key to its owner, with the owner's permissions narrowed to the
+    /// ones granted to the key
+    pub async fn authenticate(&self, api_key: &str) -> Result<(AuthnUser, AuthnApiKey), AppError> {
+        let key = self
+            .api_key_repo
+            .find_by_hash(&hash_opaque_token(api_key))
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))?;
+        if key.is_expired() {
+            return Err(AppError::Unauthorized("API key has expired".to_string()));
+        }
+
+        let account = self
+            .auth_repo
+            .find_account_state(key.user_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+        if !account.is_active || account.locked_at.is_some() {
+            return Err(AppError::Unauthorized("Account is disabled".to_string()));
+        }
+
+        // 사용자 유형에서 권한이 회수되면 키에서도 즉시 회수
+        let user_permissions = self
+            .permission
+            .get_permission_set(account.user_type_id)
+            .await?;
+        let permissions: HashSet<String> = key
+            .permission_list()
+            .into_iter()
+            .filter(|code| user_permissions.contains(*code))
+            .map(str::to_string)
+            .collect();
+
+        if let Err(e) = self.api_key_repo.touch_last_used(key.id).await {
+            error!("Failed to update API key last use: {}", e);
+        }
+
+        Ok((
+            AuthnUser {
+                id: account.id,
+                user_type_id: account.user_type_id,
+                username: account.username,
+                permissions: Arc::new(permissions),
+            },
+            AuthnApiKey {
+                id: key.id,
+                name: key.name,
+            },
+        ))
+    }
+
+    /// Records a request made with an API key in history
+    pub async fn log_request(
+        &self,
+        ctx: &AuditContext,
+        api_key: &AuthnApiKey,
+        method: &str,
+        path: &str,
+        status: u16,
+    ) {
+        self.audit
+            .record_event(
+                ctx,
+                "api_key_used",
+                AuditEntity::ApiKey(api_key.id),
+                serde_json::json!({
+                    "name": api_key.name,
+                    "method": method,
+                    "path": path,
+                    "status": status,
+                }),
+            )
+            .await;
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    config::auth::audit_context::AuditContext, model::entity::history::History,
+    repository::history::HistoryRepository,
+};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::BTreeSet;
+use tracing::error;
+
+/// Fields whose values are never copied into the audit trail, only the fact they changed
+const REDACTED_FIELDS: &[&str] = &[
+    "password",
+    "password_hash",
+    "client_secret_hash",
+    "previous_secret_hash",
+    "recovery_code_hashes",
+];
+/// Fields that change with every update and carry no information of their own
+const IGNORED_FIELDS: &[&str] = &["updated_at"];
+
+/// Record an audited change is about
+#[derive(Debug, Clone, Copy)]
+pub enum AuditEntity {
+    User(i64),
+    UserType(i64),
+    Permission(i64),
+    OAuthClient(i64),
+    ApiKey(i64),
+    /// Login throttle counter; its key (`user:<name>` or `ip:<address>`) goes in the details
+    LoginLockout,
+}
+
+impl AuditEntity {
+    pub fn entity_type(&self) -> &'static str {
+        match self {
+            AuditEntity::User(_) => "user",
+            AuditEntity::UserType(_) => "user_type",
+            AuditEntity::Permission(_) => "permission",
+            AuditEntity::OAuthClient(_) => "oauth_client",
+            AuditEntity::ApiKey(_) => "api_key",
+            AuditEntity::LoginLockout => "login_lockout",
+        }
+    }
+
+    pub fn id(&self) -> Option<i64> {
+        match self {
+            AuditEntity::User(id)
+            | AuditEntity::UserType(id)
+            | AuditEntity::Permission(id)
+            | AuditEntity::OAuthClient(id)
+            | AuditEntity::ApiKey(id) => Some(*id),
+            AuditEntity::LoginLockout => None,
+        }
+    }
+}
+
+/// Audit layer for mutating service methods. Every change is written to history with the
+/// entity it touched, a field-level before/after diff and the actor context of the request.
+/// Like other history writes, a failure is logged and does not fail the change itself.
+#[derive(Clone)]
+pub struct AuditService {
+    history_repo: HistoryRepository,
+}
+
+impl AuditService {
+    pub fn new(history_repo: HistoryRepository) -> Self {
+        Self { history_repo }
+    }
+
+    pub async fn record_created<T: Serialize>(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        after: &T,
+    ) {
+        self.record(ctx, action, entity, Map::new(), fields(after))
+            .await;
+    }
+
+    /// Updates that leave every field as it was are not recorded
+    pub async fn record_updated<T: Serialize>(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        before: &T,
+        after: &T,
+    ) {
+        let (before, after) = (fields(before), fields(after));
+        if before == after {
+            return;
+        }
+        self.record(ctx, action, entity, before, after).await;
+    }
+
+    pub async fn record_deleted<T: Serialize>(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        before: &T,
+    ) {
+        self.record(ctx, action, entity, fields(before), Map::new())
+            .await;
+    }
+
+    /// Something that happened to an entity without changing its fields, e.g. an API key
+    /// being used. `details` is recorded as given.
+    pub async fn record_event(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        details: Value,
+    ) {
+        self.write(ctx, action, entity, details).await;
+    }
+
+    async fn record(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        before: Map<String, Value>,
+        after: Map<String, Value>,
+    ) {
+        let changes = diff(&before, &after);
+        if changes.is_empty() {
+            return;
+        }
+        self.write(ctx, action, entity, json!({ "changes": changes }))
+            .await;
+    }
+
+    async fn write(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        entity: AuditEntity,
+        mut details: Value,
+    ) {
+        if let Some(client_id) = &ctx.client_id {
+            details["client_id"] = json!(client_id);
+        }
+        if let Some(api_key_id) = ctx.api_key_id {
+            details["api_key_id"] = json!(api_key_id);
+        }
+
+        let entry = History {
+            entity_type: Some(entity.entity_type().to_string()),
+            request_id: ctx.request_id.clone(),
+            ..History::new(
+                ctx.actor_id,
+                action,
+                entity.id(),
+                Some(details),
+                ctx.ip_address.clone(),
+                ctx.user_agent.clone(),
+            )
+        };
+        if let Err(e) = self.history_repo.create(entry).await {
+            error!(
+                "Failed to audit {} of {} {:?}: {}",
+                action,
+                entity.entity_type(),
+                entity.id(),
+                e
+            );
+        }
+    }
+}
+
+fn fields<T: Serialize>(value: &T) -> Map<String, Value> {
+    match serde_json::to_value(value) {
+        Ok(Value::Object(map)) => map,
+        _ => Map::new(),
+    }
+}
+
+/// `{field: {"before": .., "after": ..}}` for every field whose value differs
+fn diff(before: &Map<String, Value>, after: &Map<String, Value>) -> Map<String, Value> {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    keys.into_iter()
+        .filter(|key| !IGNORED_FIELDS.contains(&key.as_str()))
+        .filter_map(|key| {
+            let old = before.get(key).unwrap_or(&Value::Null);
+            let new = after.get(key).unwrap_or(&Value::Null);
+            if old == new {
+                return None;
+            }
+            let redact = |value: &Value| {
+                if value.is_null() {
+                    Value::Null
+                } else {
+                    json!("[redacted]")
+                }
+            };
+            let change = if REDACTED_FIELDS.contains(&key.as_str()) {
+                json!({ "before": redact(old), "after": redact(new) })
+            } else {
+                json!({ "before": old, "after": new })
+            };
+            Some((key.clone(), change))
+        })
+        .collect()
+}
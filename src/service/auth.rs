@@ -1,34 +1,99 @@
 use crate::{
-    config::{auth::authn_user::AuthnUser, env_loader::AppConfig},
+    config::{
+        auth::{
+            audit_context::AuditContext,
+            authn_client::AuthnClient,
+            authn_user::AuthnUser,
+            user::{AccountState, User},
+        },
+        env_loader::AppConfig,
+    },
     errors::AppError,
-    model::{dto::auth::CurrentUserResponse, dto::auth::LoginRequest, dto::auth::RegisterRequest},
-    repository::{auth::AuthRepository, user::UserRepository, user_type::UserTypeRepository},
-    service::history::HistoryService,
-    util::{password_util, token_util},
+    model::dto::{
+        auth::{
+            CurrentUserResponse, ForgotPasswordRequest, LoginRequest, RegisterRequest,
+            ResetPasswordRequest,
+        },
+        mfa::{MfaLoginRequest, MfaTokenRequest, TotpSetupResponse},
+        session::SessionResponse,
+        user::AccountStatusResponse,
+    },
+    repository::{
+        auth::AuthRepository, oauth::OAuthRepository, user::UserRepository,
+        user_type::UserTypeRepository,
+    },
+    service::{
+        audit::{AuditEntity, AuditService},
+        history::HistoryService,
+        login_throttle::LoginThrottleService,
+        mail::{MailMessage, MailSender},
+        mfa::MfaService,
+        permission::PermissionService,
+    },
+    util::{
+        header_util, password_util,
+        token_util::{self, MfaClaims, TokenType},
+    },
 };
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 use tracing::{error, info, warn};
 use validator::Validate;
 
+/// `user_type.code` every self-registered account gets
+const SELF_REGISTERED_USER_TYPE: &str = "user";
+
 pub struct AuthService {
     auth_repo: AuthRepository,
     user_repo: UserRepository,
     user_type_repo: UserTypeRepository,
+    oauth_repo: OAuthRepository,
+    permission: Arc<PermissionService>,
     history: Arc<HistoryService>,
+    audit: Arc<AuditService>,
+    mail: Arc<dyn MailSender>,
+    throttle: Arc<LoginThrottleService>,
+    mfa: Arc<MfaService>,
+}
+
+/// Result of the password step of a login
+pub enum LoginOutcome {
+    Authenticated {
+        access_token: String,
+        refresh_token: String,
+    },
+    /// A second factor is needed first. With `enrollment_required` the user has no
+    /// authenticator yet and must enroll one as part of this login.
+    MfaRequired {
+        mfa_token: String,
+        enrollment_required: bool,
+    },
 }
 
 impl AuthService {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         auth_repo: AuthRepository,
         user_repo: UserRepository,
         user_type_repo: UserTypeRepository,
+        oauth_repo: OAuthRepository,
+        permission: Arc<PermissionService>,
         history: Arc<HistoryService>,
+        audit: Arc<AuditService>,
+        mail: Arc<dyn MailSender>,
+        throttle: Arc<LoginThrottleService>,
+        mfa: Arc<MfaService>,
     ) -> Self {
         Self {
             auth_repo,
             user_repo,
             user_type_repo,
+            oauth_repo,
+            permission,
             history,
+            audit,
+            mail,
+            throttle,
+            mfa,
         }
     }
 
@@ -37,11 +102,16 @@ impl AuthService {
         config: &AppConfig,
         req: LoginRequest,
         ip_address: Option<String>,
-        _user_agent: Option<String>,
-    ) -> Result<(String, String), AppError> {
+        user_agent: Option<String>,
+    ) -> Result<LoginOutcome, AppError> {
         req.validate()?;
         info!("Login attempt for username: {}", req.username);
 
+        // 비밀번호 검증 전에 backoff / lockout 상태를 먼저 확인
+        self.throttle
+            .check(&req.username, ip_address.as_deref())
+            .await?;
+
         let user = match self.auth_repo.find_user_by_username(&req.username).await? {
             Some(user) => user,
             None => {
@@ -57,6 +127,9 @@ impl AuthService {
                 {
                     error!("Failed to log failed login attempt: {}", e);
                 }
+                self.throttle
+                    .record_failure(&req.username, ip_address.as_deref())
+                    .await?;
 
                 return Err(AppError::Unauthorized(
                     "Invalid username or password".to_string(),
@@ -77,34 +150,253 @@ impl AuthService {
             {
                 error!("Failed to log failed login attempt: {}", e);
             }
+            self.throttle
+                .record_failure(&req.username, ip_address.as_deref())
+                .await?;
 
             return Err(AppError::Unauthorized(
                 "Invalid username or password".to_string(),
             ));
         }
 
+        if user.locked {
+            if let Err(e) = self
+                .history
+                .log_login_failed(
+                    &req.username,
+                    "account_locked".to_string(),
+                    ip_address.clone(),
+                )
+                .await
+            {
+                error!("Failed to log failed login attempt: {}", e);
+            }
+
+            return Err(AppError::Forbidden("Account is locked".to_string()));
+        }
+
+        // 2단계 인증이 남아 있으면 실패 횟수를 유지해 TOTP 코드 대입을 막는다
+        let mfa = self.mfa.get_state(user.id).await?;
+        if mfa.is_enabled() || mfa.require_mfa {
+            info!(
+                "User {} passed the password step, second factor pending",
+                user.id
+            );
+            let mfa_token = token_util::generate_mfa_token(config, user.id, user.token_version)?;
+            return Ok(LoginOutcome::MfaRequired {
+                mfa_token,
+                enrollment_required: !mfa.is_enabled(),
+            });
+        }
+        self.throttle.record_success(&req.username).await?;
+
+        let (access_token, refresh_token) = self
+            .complete_login(config, &user, ip_address, user_agent)
+            .await?;
+        Ok(LoginOutcome::Authenticated {
+            access_token,
+            refresh_token,
+        })
+    }
+
+    /// Second login step: checks a TOTP or recovery code against the pending token.
+    /// The token is consumed by the attempt, so each password login allows one code.
+    pub async fn verify_mfa_login(
+        &self,
+        config: &AppConfig,
+        req: MfaLoginRequest,
+        ctx: &AuditContext,
+    ) -> Result<(String, String), AppError> {
+        req.validate()?;
+        let user = self
+            .consume_mfa_pending_user(config, &req.mfa_token)
+            .await?;
+        let (ip_address, user_agent) = (ctx.ip_address.clone(), ctx.user_agent.clone());
+        self.throttle
+            .check(&user.username, ip_address.as_deref())
+            .await?;
+
+        if !self
+            .mfa
+            .verify(&ctx.acting_as(user.id), user.id, &req.code)
+            .await?
+        {
+            self.reject_mfa_code(&user, ip_address.clone()).await?;
+            return Err(AppError::Unauthorized(
+                "Invalid verification code".to_string(),
+            ));
+        }
+        self.throttle.record_success(&user.username).await?;
+
+        self.complete_login(config, &user, ip_address, user_agent)
+            .await
+    }
+
+    /// Starts TOTP enrollment for a user whose type requires it but who has none yet
+    pub async fn begin_mfa_enrollment(
+        &self,
+        config: &AppConfig,
+        req: MfaTokenRequest,
+    ) -> Result<TotpSetupResponse, AppError> {
+        req.validate()?;
+        let user = self.find_mfa_pending_user(config, &req.mfa_token).await?;
+        self.mfa.begin_enrollment(user.id).await
+    }
+
+    /// Confirms the enrollment started with `begin_mfa_enrollment` and finishes the login.
+    /// Returns the tokens and the newly generated recovery codes.
+    pub async fn complete_mfa_enrollment(
+        &self,
+        config: &AppConfig,
+        req: MfaLoginRequest,
+        ctx: &AuditContext,
+    ) -> Result<(String, String, Vec<String>), AppError> {
+        req.validate()?;
+        let user = self
+            .consume_mfa_pending_user(config, &req.mfa_token)
+            .await?;
+        let (ip_address, user_agent) = (ctx.ip_address.clone(), ctx.user_agent.clone());
+        self.throttle
+            .check(&user.username, ip_address.as_deref())
+            .await?;
+
+        let recovery_codes = match self
+            .mfa
+            .confirm_enrollment(&ctx.acting_as(user.id), user.id, &req.code)
+            .await
+        {
+            Ok(codes) => codes,
+            Err(AppError::BadRequest(message)) => {
+                self.reject_mfa_code(&user, ip_address.clone()).await?;
+                return Err(AppError::BadRequest(message));
+            }
+            Err(e) => return Err(e),
+        };
+        self.throttle.record_success(&user.username).await?;
+
+        let (access_token, refresh_token) = self
+            .complete_login(config, &user, ip_address, user_agent)
+            .await?;
+        Ok((access_token, refresh_token, recovery_codes))
+    }
+
+    /// Resolves the user of an "mfa pending" token, rejecting it once the account was
+    /// locked, deactivated or force-logged-out in the meantime
+    async fn find_mfa_pending_user(
+        &self,
+        config: &AppConfig,
+        mfa_token: &str,
+    ) -> Result<User, AppError> {
+        let claims = token_util::validate_mfa_token(config, mfa_token)?;
+        if self.auth_repo.is_jti_revoked(&claims.jti).await? {
+            return Err(mfa_session_expired());
+        }
+        self.mfa_pending_user(&claims).await
+    }
+
+    /// Like `find_mfa_pending_user`, but denylists the token on the way so that it can be
+    /// used for a single code attempt only, even under concurrent requests
+    async fn consume_mfa_pending_user(
+        &self,
+        config: &AppConfig,
+        mfa_token: &str,
+    ) -> Result<User, AppError> {
+        let claims = token_util::validate_mfa_token(config, mfa_token)?;
+        // 검증 시 기본 leeway(60초)만큼 만료 후에도 토큰이 통과하므로 그때까지 보관
+        let expires_at = chrono::DateTime::from_timestamp(claims.exp as i64 + 60, 0)
+            .expect("valid timestamp")
+            .naive_utc();
+        if !self
+            .auth_repo
+            .revoke_jti(&claims.jti, claims.sub, expires_at)
+            .await?
+        {
+            return Err(mfa_session_expired());
+        }
+        self.mfa_pending_user(&claims).await
+    }
+
+    async fn mfa_pending_user(&self, claims: &MfaClaims) -> Result<User, AppError> {
+        match self.auth_repo.find_user_by_id(claims.sub).await? {
+            Some(user) if !user.locked && user.token_version == claims.ver => Ok(user),
+            _ => Err(mfa_session_expired()),
+        }
+    }
+
+    async fn reject_mfa_code(
+        &self,
+        user: &User,
+        ip_address: Option<String>,
+    ) -> Result<(), AppError> {
+        if let Err(e) = self
+            .history
+            .log_login_failed(
+                &user.username,
+                "invalid_mfa_code".to_string(),
+                ip_address.clone(),
+            )
+            .await
+        {
+            error!("Failed to log failed login attempt: {}", e);
+        }
+        self.throttle
+            .record_failure(&user.username, ip_address.as_deref())
+            .await
+    }
+
+    /// Issues the tokens and the session once every login step has passed
+    async fn complete_login(
+        &self,
+        config: &AppConfig,
+        user: &User,
+        ip_address: Option<String>,
+        user_agent: Option<String>,
+    ) -> Result<(String, String), AppError> {
         // Clone username before moving it
         let username = user.username.clone();
 
         // Log successful login
         if let Err(e) = self
             .history
-            .log_login_success(user.id, Some(username.clone()), ip_address.clone())
+            .log_login_success(user.id, ip_address.clone(), user_agent.clone())
             .await
         {
             error!("Failed to log successful login: {}", e);
         }
 
         info!("User {} logged in successfully", user.id);
-        let access_token =
-            token_util::generate_access_token(config, user.id, &user.role, &username)?;
-
-        let refresh_token =
-            token_util::generate_refresh_token(config, user.id, &user.role, &username)?;
+        let access_token = self
+            .issue_access_token(
+                config,
+                user.id,
+                user.user_type_id,
+                &user.role,
+                &username,
+                user.token_version,
+            )
+            .await?;
 
-        // Save refresh token to database
+        // 로그인마다 새 세션(= refresh token 패밀리)을 시작
+        let session_id = uuid::Uuid::new_v4().to_string();
         self.auth_repo
-            .save_refresh_token(user.id, &refresh_token)
+            .create_session(
+                &session_id,
+                user.id,
+                user_agent.as_deref().map(header_util::describe_user_agent),
+                user_agent,
+                ip_address,
+                refresh_expires_at(config),
+            )
+            .await?;
+        let refresh_token = self
+            .issue_refresh_token(
+                config,
+                user.id,
+                &user.role,
+                &username,
+                user.token_version,
+                &session_id,
+            )
             .await?;
 
         // Update last login time
@@ -115,9 +407,8 @@ impl AuthService {
 
     pub async fn register(
         &self,
+        ctx: &AuditContext,
         req: RegisterRequest,
-        ip_address: Option<String>,
-        _user_agent: Option<String>,
     ) -> Result<i64, AppError> {
         req.validate()?;
         info!("Register request for username: {}", req.username);
@@ -132,12 +423,18 @@ impl AuthService {
             return Err(AppError::BadRequest("Username already exists".to_string()));
         }
 
-        // Check if user type exists and get its name
-        let _user_type_info = self
+        // 공개 가입은 항상 기본 유형으로 생성 (유형 지정은 user:create 권한이 필요한 사용자 API에서)
+        let user_type = self
             .user_type_repo
-            .find_by_id(req.user_type_id)
-            .await
-            .map_err(|_| AppError::BadRequest("Invalid user type".to_string()))?;
+            .find_by_code(SELF_REGISTERED_USER_TYPE)
+            .await?
+            .ok_or_else(|| {
+                error!(
+                    "User type '{}' for self-registration is missing",
+                    SELF_REGISTERED_USER_TYPE
+                );
+                AppError::InternalServerError("Registration is unavailable".to_string())
+            })?;
 
         let hashed_password = password_util::hash_password(&req.password).await?;
 
@@ -150,22 +447,20 @@ impl AuthService {
                 username,
                 req.email,
                 hashed_password,
-                req.user_type_id,
+                user_type.id,
                 true, // is_active
             )
             .await?;
 
-        // Log the registration
-        self.history
-            .create_log(
-                Some(user_id),
+        let after = self.find_account_state(user_id).await?;
+        self.audit
+            .record_created(
+                &ctx.acting_as(user_id),
                 "user_created",
-                Some(user_id),
-                Some(serde_json::json!({ "username": &req.username })),
-                ip_address,
-                None, // user_agent
+                AuditEntity::User(user_id),
+                &after,
             )
-            .await?;
+            .await;
 
         info!("User registered successfully: {}", user_id);
         Ok(user_id)
@@ -175,10 +470,9 @@ impl AuthService {
         &self,
         config: &AppConfig,
         refresh_token: String,
-        ip_address: Option<String>,
-        _user_agent: Option<String>,
+        ctx: &AuditContext,
     ) -> Result<(String, String), AppError> {
-        let claims = match token_util::validate_token(&refresh_token) {
+        let claims = match token_util::validate_token(&refresh_token, TokenType::Refresh) {
             Ok(claims) => claims,
             Err(e) => {
                 warn!("Invalid refresh token: {}", e);
@@ -192,52 +486,507 @@ impl AuthService {
             return Err(AppError::Unauthorized("Refresh token expired".to_string()));
         }
 
-        // Get the user from the database
-        let user = self.user_repo.find_by_id(claims.sub).await.map_err(|e| {
-            warn!("User not found for refresh token: {} - {}", claims.sub, e);
-            AppError::Unauthorized("User not found".to_string())
-        })?;
+        // The token must be one we issued and still hold
+        let stored = self
+            .auth_repo
+            .find_refresh_token(&token_util::hash_opaque_token(&refresh_token))
+            .await?
+            .filter(|stored| stored.user_id == claims.sub)
+            .ok_or_else(|| {
+                warn!("Unknown refresh token presented for user: {}", claims.sub);
+                AppError::Unauthorized("Invalid refresh token".to_string())
+            })?;
 
-        // Get user type info
-        let user_type_info = self
+        if stored.revoked_at.is_some() {
+            warn!(
+                "Revoked refresh token presented for user: {} (family {})",
+                stored.user_id, stored.family_id
+            );
+            return Err(AppError::Unauthorized(
+                "Refresh token has been revoked".to_string(),
+            ));
+        }
+
+        // Tokens issued before a force-logout, lock or deactivation are no longer honoured
+        let account = match self.auth_repo.find_account_state(claims.sub).await? {
+            Some(account) if account.accepts(claims.ver) => account,
+            _ => {
+                self.auth_repo.revoke_session(&stored.family_id).await?;
+                warn!(
+                    "Refresh token of an invalidated account presented for user: {}",
+                    claims.sub
+                );
+                return Err(AppError::Unauthorized(
+                    "Refresh token has been revoked".to_string(),
+                ));
+            }
+        };
+
+        // A token that was already rotated is being replayed: assume it leaked and
+        // revoke the whole family so neither party can keep using it.
+        if stored.used_at.is_some() || !self.auth_repo.mark_refresh_token_used(stored.id).await? {
+            self.auth_repo.revoke_session(&stored.family_id).await?;
+            warn!(
+                "Refresh token reuse detected for user: {} (family {})",
+                stored.user_id, stored.family_id
+            );
+            self.audit
+                .record_event(
+                    &ctx.acting_as(stored.user_id),
+                    "refresh_token_reuse_detected",
+                    AuditEntity::User(stored.user_id),
+                    serde_json::json!({ "session_id": &stored.family_id }),
+                )
+                .await;
+            return Err(AppError::Unauthorized(
+                "Refresh token has been revoked".to_string(),
+            ));
+        }
+
+        // 사용자 유형과 권한은 갱신 시점 기준으로 다시 담는다
+        let user_type = self
             .user_type_repo
-            .get_user_type_info(user.user_type_id)
+            .get_user_type_info(account.user_type_id)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User type not found".to_string()))?;
+
+        // Generate new tokens
+        let access_token = self
+            .issue_access_token(
+                config,
+                account.id,
+                account.user_type_id,
+                &user_type.code,
+                &account.username,
+                account.token_version,
+            )
             .await?;
 
-        // Get user type name or default to "user" if not found
-        let user_type_name = user_type_info
-            .as_ref()
-            .map(|ut| ut.name.as_str())
-            .unwrap_or("user");
+        let refresh_token = self
+            .issue_refresh_token(
+                config,
+                account.id,
+                &user_type.code,
+                &account.username,
+                account.token_version,
+                &stored.family_id,
+            )
+            .await?;
+        self.auth_repo
+            .touch_session(
+                &stored.family_id,
+                ctx.ip_address.clone(),
+                refresh_expires_at(config),
+            )
+            .await?;
 
-        // Clone username to avoid moving it
-        let username = user.username.clone();
+        self.audit
+            .record_event(
+                &ctx.acting_as(account.id),
+                "token_refresh",
+                AuditEntity::User(account.id),
+                serde_json::json!({ "session_id": &stored.family_id }),
+            )
+            .await;
 
-        // Generate new tokens
-        let access_token =
-            token_util::generate_access_token(&config, user.id, user_type_name, &username)?;
+        info!("Refreshed tokens for user: {}", account.id);
+        Ok((access_token, refresh_token))
+    }
 
+    /// Revokes the token family of the presented refresh token and denylists the access
+    /// token, so neither can be used again even if a copy was kept
+    pub async fn logout(
+        &self,
+        config: &AppConfig,
+        access_token: Option<String>,
+        refresh_token: Option<String>,
+        ctx: &AuditContext,
+    ) -> Result<(), AppError> {
+        if let Some(claims) = access_token
+            .and_then(|token| token_util::validate_token(&token, TokenType::Access).ok())
+        {
+            // leeway만큼 만료 후에도 토큰이 통과하므로 그때까지 보관
+            let expires_at =
+                chrono::DateTime::from_timestamp(claims.exp as i64 + config.token.leeway as i64, 0)
+                    .expect("valid timestamp")
+                    .naive_utc();
+            self.auth_repo
+                .revoke_jti(&claims.jti, claims.sub, expires_at)
+                .await?;
+        }
+
+        let Some(refresh_token) = refresh_token else {
+            return Ok(());
+        };
+
+        let Some(stored) = self
+            .auth_repo
+            .find_refresh_token(&token_util::hash_opaque_token(&refresh_token))
+            .await?
+        else {
+            return Ok(());
+        };
+
+        self.auth_repo.revoke_session(&stored.family_id).await?;
+
+        self.audit
+            .record_event(
+                &ctx.acting_as(stored.user_id),
+                "user_logout",
+                AuditEntity::User(stored.user_id),
+                serde_json::json!({ "session_id": &stored.family_id }),
+            )
+            .await;
+
+        info!("User {} logged out", stored.user_id);
+        Ok(())
+    }
+
+    /// Lists the active sessions of a user, flagging the one the refresh token belongs to
+    pub async fn list_sessions(
+        &self,
+        user_id: i64,
+        refresh_token: Option<String>,
+    ) -> Result<Vec<SessionResponse>, AppError> {
+        let current = self.find_session_id(refresh_token).await?;
+
+        Ok(self
+            .auth_repo
+            .find_active_sessions(user_id)
+            .await?
+            .into_iter()
+            .map(|session| {
+                let is_current = current.as_deref() == Some(session.id.as_str());
+                SessionResponse {
+                    current: is_current,
+                    ..SessionResponse::from(session)
+                }
+            })
+            .collect())
+    }
+
+    /// Revokes one of the user's own sessions
+    pub async fn revoke_session(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        session_id: &str,
+    ) -> Result<(), AppError> {
+        let owned = self
+            .auth_repo
+            .find_active_sessions(user_id)
+            .await?
+            .iter()
+            .any(|session| session.id == session_id);
+        if !owned || !self.auth_repo.revoke_session(session_id).await? {
+            return Err(AppError::NotFound("Session not found".to_string()));
+        }
+
+        self.audit
+            .record_event(
+                ctx,
+                "session_revoked",
+                AuditEntity::User(user_id),
+                serde_json::json!({ "session_id": session_id }),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Revokes every session of the user, keeping the one `keep_refresh_token` belongs to if given
+    pub async fn revoke_all_sessions(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        keep_refresh_token: Option<String>,
+    ) -> Result<u64, AppError> {
+        let keep = self.find_session_id(keep_refresh_token).await?;
+        let revoked = self
+            .auth_repo
+            .revoke_all_sessions(user_id, keep.as_deref())
+            .await?;
+
+        self.audit
+            .record_event(
+                ctx,
+                "sessions_revoked",
+                AuditEntity::User(user_id),
+                serde_json::json!({ "count": revoked, "kept_current": keep.is_some() }),
+            )
+            .await;
+
+        Ok(revoked)
+    }
+
+    /// Resolves the session (token family) a refresh token belongs to
+    async fn find_session_id(
+        &self,
+        refresh_token: Option<String>,
+    ) -> Result<Option<String>, AppError> {
+        let Some(refresh_token) = refresh_token else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .auth_repo
+            .find_refresh_token(&token_util::hash_opaque_token(&refresh_token))
+            .await?
+            .map(|stored| stored.family_id))
+    }
+
+    /// Generates an access token carrying the user type code and its current permissions
+    async fn issue_access_token(
+        &self,
+        config: &AppConfig,
+        user_id: i64,
+        user_type_id: i64,
+        user_type_code: &str,
+        username: &str,
+        token_version: i64,
+    ) -> Result<String, AppError> {
+        let mut permissions: Vec<String> = self
+            .permission
+            .get_permission_set(user_type_id)
+            .await?
+            .iter()
+            .cloned()
+            .collect();
+        permissions.sort();
+
+        token_util::generate_access_token(
+            config,
+            user_id,
+            user_type_code,
+            permissions,
+            username,
+            token_version,
+        )
+    }
+
+    /// Generates a refresh token and stores its hash in the given family
+    async fn issue_refresh_token(
+        &self,
+        config: &AppConfig,
+        user_id: i64,
+        role: &str,
+        username: &str,
+        token_version: i64,
+        family_id: &str,
+    ) -> Result<String, AppError> {
         let refresh_token =
-            token_util::generate_refresh_token(&config, user.id, user_type_name, &username)?;
+            token_util::generate_refresh_token(config, user_id, role, username, token_version)?;
+        let expires_at =
+            (chrono::Utc::now() + chrono::Duration::seconds(config.token.refresh_exp)).naive_utc();
 
-        // Log token refresh
-        if let Err(e) = self
-            .history
-            .create_log(
-                Some(user.id),
-                "token_refresh",
-                Some(user.id),
-                None,
-                ip_address,
-                None, // user_agent
+        self.auth_repo
+            .save_refresh_token(
+                user_id,
+                &token_util::hash_opaque_token(&refresh_token),
+                family_id,
+                expires_at,
             )
+            .await?;
+
+        Ok(refresh_token)
+    }
+
+    /// Validates an access token and resolves the authenticated principal it belongs to.
+    /// Tokens of deactivated or locked accounts, or issued before the user's token version
+    /// was bumped, are rejected.
+    pub async fn authenticate(&self, token: &str) -> Result<AuthnUser, AppError> {
+        let claims = token_util::validate_token(token, TokenType::Access)?;
+        if self.auth_repo.is_jti_revoked(&claims.jti).await? {
+            return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+        }
+        let account = self
+            .auth_repo
+            .find_account_state(claims.sub)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+
+        if !account.accepts(claims.ver) {
+            return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+        }
+
+        let permissions = self
+            .permission
+            .get_permission_set(account.user_type_id)
+            .await?;
+
+        Ok(AuthnUser {
+            id: account.id,
+            user_type_id: account.user_type_id,
+            username: account.username,
+            permissions,
+        })
+    }
+
+    /// Resolves an OAuth access token to its client, scopes and (if any) resource owner
+    pub async fn authenticate_oauth_token(&self, token: &str) -> Result<AuthnClient, AppError> {
+        let oauth_token = self
+            .oauth_repo
+            .find_token(token)
             .await
-        {
-            error!("Failed to log token refresh: {}", e);
+            .ok_or_else(|| AppError::Unauthorized("Invalid or expired token".to_string()))?;
+
+        let user = match oauth_token.user_id {
+            Some(user_id) => {
+                let account = self
+                    .auth_repo
+                    .find_account_state(user_id)
+                    .await?
+                    .ok_or_else(|| AppError::Unauthorized("User not found".to_string()))?;
+                // 비활성/잠긴 계정, 발급 후 강제 로그아웃된 계정의 OAuth 토큰은 거부
+                if !oauth_token
+                    .token_version
+                    .is_some_and(|version| account.accepts(version))
+                {
+                    return Err(AppError::Unauthorized("Token has been revoked".to_string()));
+                }
+                let permissions = self
+                    .permission
+                    .get_permission_set(account.user_type_id)
+                    .await?;
+                Some(AuthnUser {
+                    id: account.id,
+                    user_type_id: account.user_type_id,
+                    username: account.username,
+                    permissions,
+                })
+            }
+            None => None,
+        };
+        // 사용자 없는 토큰은 클라이언트에 부여된 권한만 가짐
+        let permissions = match user {
+            Some(_) => HashSet::new(),
+            None => self
+                .oauth_repo
+                .find_client(&oauth_token.client_id)
+                .await
+                .map(|client| {
+                    client
+                        .permission_list()
+                        .into_iter()
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+
+        Ok(AuthnClient {
+            client_id: oauth_token.client_id,
+            scopes: oauth_token
+                .scope
+                .as_deref()
+                .unwrap_or_default()
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+            user,
+            permissions,
+            scope_checked: false,
+        })
+    }
+
+    /// Ends every session of a user immediately by bumping their token version
+    pub async fn force_logout(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+    ) -> Result<AccountStatusResponse, AppError> {
+        let before = self.find_account_state(user_id).await?;
+        self.auth_repo.bump_token_version(user_id).await?;
+        let revoked = self.auth_repo.revoke_all_sessions(user_id, None).await?;
+
+        let after = self.find_account_state(user_id).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "user_force_logout",
+                AuditEntity::User(user_id),
+                &before,
+                &after,
+            )
+            .await;
+
+        info!(
+            "User {} was force-logged out by {:?} ({} sessions revoked)",
+            user_id, ctx.actor_id, revoked
+        );
+        Ok(after.into())
+    }
+
+    /// Locks an account and ends its sessions
+    pub async fn lock_user(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        reason: Option<String>,
+    ) -> Result<AccountStatusResponse, AppError> {
+        if ctx.actor_id == Some(user_id) {
+            return Err(AppError::BadRequest(
+                "You cannot lock your own account".to_string(),
+            ));
         }
+        let before = self.find_account_state(user_id).await?;
+        self.auth_repo.set_locked(user_id, true, reason).await?;
+        let revoked = self.auth_repo.revoke_all_sessions(user_id, None).await?;
 
-        info!("Refreshed tokens for user: {}", user.id);
-        Ok((access_token, refresh_token))
+        let after = self.find_account_state(user_id).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "user_locked",
+                AuditEntity::User(user_id),
+                &before,
+                &after,
+            )
+            .await;
+
+        info!(
+            "User {} was locked by {:?} ({} sessions revoked)",
+            user_id, ctx.actor_id, revoked
+        );
+        Ok(after.into())
+    }
+
+    /// Unlocks an account so the user can log in again
+    pub async fn unlock_user(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+    ) -> Result<AccountStatusResponse, AppError> {
+        let before = self.find_account_state(user_id).await?;
+        self.auth_repo.set_locked(user_id, false, None).await?;
+
+        let after = self.find_account_state(user_id).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "user_unlocked",
+                AuditEntity::User(user_id),
+                &before,
+                &after,
+            )
+            .await;
+
+        info!("User {} was unlocked by {:?}", user_id, ctx.actor_id);
+        Ok(after.into())
+    }
+
+    pub async fn get_account_status(
+        &self,
+        user_id: i64,
+    ) -> Result<AccountStatusResponse, AppError> {
+        Ok(self.find_account_state(user_id).await?.into())
+    }
+
+    async fn find_account_state(&self, user_id: i64) -> Result<AccountState, AppError> {
+        self.auth_repo
+            .find_account_state(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
     }
 
     pub async fn get_current_user(
@@ -257,4 +1006,110 @@ impl AuthService {
             permissions: current_user.permissions.iter().cloned().collect(),
         })
     }
+
+    /// Issues a password reset token and mails the reset link to the user.
+    /// Unknown emails and mail failures are only logged, so the result is the same whether or not
+    /// the account exists and the endpoint cannot be used to probe accounts.
+    pub async fn request_password_reset(
+        &self,
+        config: &AppConfig,
+        req: ForgotPasswordRequest,
+        ctx: &AuditContext,
+    ) -> Result<(), AppError> {
+        req.validate()?;
+
+        let Some(user) = self.auth_repo.find_active_user_by_email(&req.email).await? else {
+            warn!("Password reset requested for unknown email");
+            return Ok(());
+        };
+
+        let token = token_util::generate_opaque_token();
+        let expires_at = (chrono::Utc::now()
+            + chrono::Duration::seconds(config.token.password_reset_exp))
+        .naive_utc();
+        self.auth_repo
+            .create_password_reset_token(
+                user.id,
+                &token_util::hash_opaque_token(&token),
+                expires_at,
+            )
+            .await?;
+
+        let reset_url = format!(
+            "{}/auth/password/reset?token={}",
+            config.base_url.trim_end_matches('/'),
+            token
+        );
+        let message = MailMessage {
+            to: req.email,
+            subject: format!("[{}] 비밀번호 재설정 안내", config.app_name),
+            body: format!(
+                "{}님, 아래 링크에서 비밀번호를 재설정하세요.\n\n{}\n\n링크는 {}분 동안 한 번만 사용할 수 있습니다. 요청하지 않았다면 이 메일을 무시하세요.",
+                user.username,
+                reset_url,
+                config.token.password_reset_exp / 60
+            ),
+        };
+        // A mail failure must not change the response, or it would reveal that the account exists
+        if let Err(e) = self.mail.send(&message).await {
+            error!(
+                "Failed to send password reset mail to user {}: {}",
+                user.id, e
+            );
+            return Ok(());
+        }
+
+        self.audit
+            .record_event(
+                &ctx.acting_as(user.id),
+                "password_reset_requested",
+                AuditEntity::User(user.id),
+                serde_json::json!({ "expires_at": expires_at }),
+            )
+            .await;
+
+        info!("Password reset token issued for user: {}", user.id);
+        Ok(())
+    }
+
+    /// Consumes a password reset token and sets the new password atomically, then revokes the
+    /// user's refresh tokens
+    pub async fn reset_password(
+        &self,
+        ctx: &AuditContext,
+        req: ResetPasswordRequest,
+    ) -> Result<(), AppError> {
+        req.validate()?;
+
+        let hashed_password = password_util::hash_password(&req.password).await?;
+        let user_id = self
+            .auth_repo
+            .reset_password_with_token(&token_util::hash_opaque_token(&req.token), &hashed_password)
+            .await?
+            .ok_or_else(|| {
+                AppError::BadRequest("Invalid or expired password reset token".to_string())
+            })?;
+        let revoked = self.auth_repo.revoke_all_sessions(user_id, None).await?;
+
+        self.audit
+            .record_event(
+                &ctx.acting_as(user_id),
+                "password_reset",
+                AuditEntity::User(user_id),
+                serde_json::json!({ "revoked_sessions": revoked }),
+            )
+            .await;
+
+        info!("Password reset completed for user: {}", user_id);
+        Ok(())
+    }
+}
+
+fn mfa_session_expired() -> AppError {
+    AppError::Unauthorized("MFA session expired, please log in again".to_string())
+}
+
+/// Expiry of a refresh token (and its session) issued now
+fn refresh_expires_at(config: &AppConfig) -> chrono::NaiveDateTime {
+    (chrono::Utc::now() + chrono::Duration::seconds(config.token.refresh_exp)).naive_utc()
 }
@@ -1,23 +1,39 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
     model::{
-        dto::history::HistoryListQuery, dto::history::HistoryResponse, entity::history::History,
+        dto::history::{
+            BrokenLink, ChainAnchor, ChainBreak, ExportFormat, HistoryExportRow, HistoryListQuery,
+            HistoryResponse, HistoryVerifyQuery, HistoryVerifyResponse,
+        },
+        entity::history::{History, HistoryCheckpoint},
     },
     repository::history::HistoryRepository,
+    util::hash_chain_util::{ChainHasher, GENESIS_HASH},
 };
+use futures_util::{stream, Stream, StreamExt};
 use serde_json::json;
 use tracing::info;
 
+/// Rows fetched per query while verifying the hash chain
+const VERIFY_BATCH_SIZE: i64 = 1000;
+/// Rows fetched per query while streaming an export
+const EXPORT_BATCH_SIZE: i64 = 500;
+
 /// Service for managing history
 #[derive(Clone)]
 pub struct HistoryService {
     history_repo: HistoryRepository,
+    hasher: ChainHasher,
 }
 
 impl HistoryService {
     /// Creates a new history service instance
-    pub fn new(history_repo: HistoryRepository) -> Self {
-        Self { history_repo }
+    pub fn new(history_repo: HistoryRepository, hasher: ChainHasher) -> Self {
+        Self {
+            history_repo,
+            hasher,
+        }
     }
 
     /// Creates a new history entry
@@ -30,11 +46,10 @@ impl HistoryService {
         ip_address: Option<String>,
         user_agent: Option<String>,
     ) -> Result<History, AppError> {
-        let action_str = action.into();
         self.history_repo
-            .create(
-                user_id, action_str, entity_id, details, ip_address, user_agent,
-            )
+            .create(History::new(
+                user_id, action, entity_id, details, ip_address, user_agent,
+            ))
             .await
     }
 
@@ -152,20 +167,200 @@ impl HistoryService {
         self.history_repo.count(query).await
     }
 
-    /// Cleans up history older than the specified number of days
-    pub async fn cleanup_old_logs(&self, days: i64) -> Result<u64, AppError> {
-        if days <= 0 {
-            return Err(AppError::BadRequest(
-                "Days must be greater than 0".to_string(),
-            ));
+    /// Seals history rows written before the hash chain existed
+    pub async fn seal_legacy_history(&self) -> Result<u64, AppError> {
+        let sealed = self.history_repo.seal_legacy().await?;
+        if sealed > 0 {
+            info!("Sealed {} history entries into the hash chain", sealed);
+        }
+        Ok(sealed)
+    }
+
+    /// Walks the hash chain over a range of history and reports the first broken link
+    pub async fn verify_chain(
+        &self,
+        query: HistoryVerifyQuery,
+    ) -> Result<HistoryVerifyResponse, AppError> {
+        let after_id = query.from_id.map_or(0, |id| id - 1);
+        let mut response = HistoryVerifyResponse {
+            valid: true,
+            signed: self.hasher.is_signed(),
+            anchor: ChainAnchor::Genesis,
+            checked: 0,
+            first_id: None,
+            last_id: None,
+            last_hash: None,
+            broken: None,
+        };
+
+        let mut links = self
+            .history_repo
+            .find_links(after_id, query.to_id, VERIFY_BATCH_SIZE)
+            .await?;
+        let Some(first) = links.first() else {
+            return Ok(response);
+        };
+
+        // The hash the first row of the range must point at, and the id it belongs to
+        let (mut expected_prev, mut previous_id) =
+            match self.history_repo.find_link_before(first.id).await? {
+                Some(previous) => {
+                    response.anchor = ChainAnchor::History;
+                    (previous.hash.unwrap_or_default(), previous.id)
+                }
+                None => match self.history_repo.find_checkpoint_before(first.id).await? {
+                    Some(checkpoint) => {
+                        response.anchor = ChainAnchor::Checkpoint;
+                        if let Some(broken) = self.check_checkpoint(&checkpoint) {
+                            response.valid = false;
+                            response.broken = Some(broken);
+                            return Ok(response);
+                        }
+                        (checkpoint.through_hash, checkpoint.through_id)
+                    }
+                    None => (GENESIS_HASH.to_string(), 0),
+                },
+            };
+
+        while !links.is_empty() {
+            let last_id = links.last().map(|link| link.id).unwrap_or_default();
+            for link in links {
+                // Rows removed by retention leave a gap that signed checkpoints bridge
+                if link.hash.is_some() && link.prev_hash.as_deref() != Some(expected_prev.as_str())
+                {
+                    match self
+                        .bridge_gap(previous_id, link.id, &expected_prev)
+                        .await?
+                    {
+                        Ok(bridged) => expected_prev = bridged,
+                        Err(broken) => {
+                            response.valid = false;
+                            response.broken = Some(broken);
+                            return Ok(response);
+                        }
+                    }
+                }
+
+                let broken = match (link.prev_hash.as_deref(), link.hash.as_deref()) {
+                    (_, None) => Some((ChainBreak::MissingHash, None, None)),
+                    (Some(prev_hash), Some(hash)) if prev_hash == expected_prev => {
+                        let computed = self.hasher.hash(&link.content(prev_hash));
+                        (computed != hash)
+                            .then(|| (ChainBreak::HashMismatch, Some(computed), Some(hash.into())))
+                    }
+                    (prev_hash, Some(_)) => Some((
+                        ChainBreak::PrevHashMismatch,
+                        Some(expected_prev.clone()),
+                        prev_hash.map(str::to_string),
+                    )),
+                };
+                if let Some((reason, expected, actual)) = broken {
+                    response.valid = false;
+                    response.broken = Some(BrokenLink {
+                        id: link.id,
+                        reason,
+                        expected,
+                        actual,
+                    });
+                    return Ok(response);
+                }
+
+                response.checked += 1;
+                response.first_id.get_or_insert(link.id);
+                response.last_id = Some(link.id);
+                previous_id = link.id;
+                expected_prev = link.hash.unwrap_or_default();
+                response.last_hash = Some(expected_prev.clone());
+            }
+
+            links = self
+                .history_repo
+                .find_links(last_id, query.to_id, VERIFY_BATCH_SIZE)
+                .await?;
         }
 
-        info!("Cleaning up history older than {} days", days);
-        let deleted = self.history_repo.delete_older_than_days(days).await?;
+        Ok(response)
+    }
 
-        if deleted > 0 {
-            info!("Deleted {} old history entries", deleted);
+    /// Follows the checkpoints of rows deleted between `after_id` and `before_id` from
+    /// `expected`, returning the hash the next remaining row must point at
+    async fn bridge_gap(
+        &self,
+        after_id: i64,
+        before_id: i64,
+        expected: &str,
+    ) -> Result<Result<String, BrokenLink>, AppError> {
+        let mut expected = expected.to_string();
+        let checkpoints = self
+            .history_repo
+            .find_checkpoints_between(after_id, before_id)
+            .await?;
+        for checkpoint in checkpoints {
+            if checkpoint.anchor_hash.as_deref() != Some(expected.as_str()) {
+                continue;
+            }
+            if let Some(broken) = self.check_checkpoint(&checkpoint) {
+                return Ok(Err(broken));
+            }
+            expected = checkpoint.through_hash;
         }
-        Ok(deleted)
+        Ok(Ok(expected))
+    }
+
+    fn check_checkpoint(&self, checkpoint: &HistoryCheckpoint) -> Option<BrokenLink> {
+        let signature = self.hasher.hash(&checkpoint.content());
+        (signature != checkpoint.signature).then(|| BrokenLink {
+            id: checkpoint.id,
+            reason: ChainBreak::CheckpointSignatureMismatch,
+            expected: Some(signature),
+            actual: Some(checkpoint.signature.clone()),
+        })
+    }
+
+    /// Records an export and returns the file as a stream of chunks, one per batch of rows,
+    /// oldest first. The whole result is never held in memory.
+    pub async fn export_history(
+        &self,
+        query: HistoryListQuery,
+        format: ExportFormat,
+        ctx: &AuditContext,
+    ) -> Result<impl Stream<Item = Result<String, AppError>> + Send + 'static, AppError> {
+        let entry = History {
+            request_id: ctx.request_id.clone(),
+            ..History::new(
+                ctx.actor_id,
+                "history_exported",
+                None,
+                Some(json!({ "format": format, "filters": query.filters() })),
+                ctx.ip_address.clone(),
+                ctx.user_agent.clone(),
+            )
+        };
+        self.history_repo.create(entry).await?;
+
+        let history_repo = self.history_repo.clone();
+        let rows = stream::try_unfold(Some(0), move |after_id| {
+            let history_repo = history_repo.clone();
+            let query = query.clone();
+            async move {
+                let Some(after_id) = after_id else {
+                    return Ok(None);
+                };
+                let links = history_repo
+                    .find_matching_links(&query, after_id, EXPORT_BATCH_SIZE)
+                    .await?;
+                let Some(last_id) = links.last().map(|link| link.id) else {
+                    return Ok(None);
+                };
+                let next = (links.len() as i64 == EXPORT_BATCH_SIZE).then_some(last_id);
+                let chunk: String = links
+                    .into_iter()
+                    .map(|link| format.encode(HistoryExportRow::from(link)))
+                    .collect();
+                Ok(Some((chunk, next)))
+            }
+        });
+
+        Ok(stream::once(async move { Ok(format.header().to_string()) }).chain(rows))
     }
 }
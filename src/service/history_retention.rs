@@ -0,0 +1,323 @@
+use crate::{
+    config::{auth::audit_context::AuditContext, env_loader::HistoryLog},
+    errors::AppError,
+    model::{
+        dto::history::{ExportFormat, HistoryExportRow, SQLITE_DATETIME_FORMAT},
+        entity::{
+            history::{History, HistoryLink},
+            history_retention_run::HistoryRetentionRun,
+        },
+    },
+    repository::{history::HistoryRepository, history_retention::HistoryRetentionRepository},
+};
+use chrono::{NaiveDate, Utc};
+use flate2::{write::GzEncoder, Compression};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    sync::{watch, Mutex},
+    task::JoinHandle,
+    time::{self, MissedTickBehavior},
+};
+use tracing::{error, info};
+
+/// Expired rows archived and deleted per transaction
+const RETENTION_BATCH_SIZE: i64 = 1000;
+/// Shortest allowed schedule, so a misconfigured interval cannot spin
+const MIN_RETENTION_INTERVAL_SECS: u64 = 60;
+
+/// What started a retention run
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetentionTrigger {
+    Scheduled,
+    Manual,
+}
+
+impl RetentionTrigger {
+    fn as_str(&self) -> &'static str {
+        match self {
+            RetentionTrigger::Scheduled => "scheduled",
+            RetentionTrigger::Manual => "manual",
+        }
+    }
+}
+
+#[derive(Default)]
+struct RunStats {
+    archived: i64,
+    deleted: i64,
+    files: BTreeSet<PathBuf>,
+}
+
+/// Applies the history retention policies: expired rows are written to a compressed archive
+/// partitioned by date, then deleted with a signed checkpoint bridging each gap in the chain.
+pub struct HistoryRetentionService {
+    history_repo: HistoryRepository,
+    retention_repo: HistoryRetentionRepository,
+    config: HistoryLog,
+    /// Held for the length of a run so scheduled and manual runs never overlap
+    running: Mutex<()>,
+}
+
+impl HistoryRetentionService {
+    pub fn new(
+        history_repo: HistoryRepository,
+        retention_repo: HistoryRetentionRepository,
+        config: HistoryLog,
+    ) -> Self {
+        Self {
+            history_repo,
+            retention_repo,
+            config,
+            running: Mutex::new(()),
+        }
+    }
+
+    /// Runs retention on the configured interval until `shutdown` fires. A run in progress
+    /// stops after its current batch, so nothing is deleted without being archived.
+    pub fn spawn_scheduler(self: Arc<Self>, mut shutdown: watch::Receiver<bool>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            if !self.config.retention_enabled {
+                info!("History retention schedule is disabled");
+                return;
+            }
+
+            let period = self
+                .config
+                .retention_interval_secs
+                .max(MIN_RETENTION_INTERVAL_SECS);
+            let mut interval = time::interval(Duration::from_secs(period));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            info!("History retention scheduled every {} seconds", period);
+
+            loop {
+                tokio::select! {
+                    _ = interval.tick() => {}
+                    _ = shutdown.changed() => break,
+                }
+                if let Err(e) = self
+                    .run(RetentionTrigger::Scheduled, None, None, Some(&shutdown))
+                    .await
+                {
+                    error!("History retention run failed: {}", e);
+                }
+                if *shutdown.borrow() {
+                    break;
+                }
+            }
+
+            info!("History retention scheduler stopped");
+        })
+    }
+
+    /// Archives and deletes expired history. `default_days` replaces the configured default
+    /// for actions without their own policy (0 keeps them); per-action policies always apply.
+    /// The run is attributed to `ctx` when started by a request.
+    pub async fn run(
+        &self,
+        trigger: RetentionTrigger,
+        default_days: Option<i64>,
+        ctx: Option<&AuditContext>,
+        shutdown: Option<&watch::Receiver<bool>>,
+    ) -> Result<HistoryRetentionRun, AppError> {
+        let Ok(_running) = self.running.try_lock() else {
+            return Err(AppError::Conflict(
+                "A history retention run is already in progress".to_string(),
+            ));
+        };
+
+        let now = Utc::now();
+        let cutoff = |days: i64| {
+            (days > 0).then(|| {
+                (now - chrono::Duration::days(days))
+                    .format(SQLITE_DATETIME_FORMAT)
+                    .to_string()
+            })
+        };
+        let default_cutoff = cutoff(default_days.unwrap_or(self.config.retention_days));
+        let action_cutoffs: Vec<(String, Option<String>)> = self
+            .config
+            .retention_policies
+            .iter()
+            .map(|(action, days)| (action.clone(), cutoff(*days)))
+            .collect();
+
+        let run_id = self.retention_repo.start_run(trigger.as_str()).await?;
+        info!(
+            "History retention run {} started ({})",
+            run_id,
+            trigger.as_str()
+        );
+
+        let mut stats = RunStats::default();
+        let result = self
+            .archive_and_prune(
+                run_id,
+                default_cutoff.as_deref(),
+                &action_cutoffs,
+                shutdown,
+                &mut stats,
+            )
+            .await;
+        let (status, error) = match &result {
+            Ok(true) => ("succeeded", None),
+            Ok(false) => ("interrupted", None),
+            Err(e) => ("failed", Some(e.to_string())),
+        };
+
+        let run = self
+            .retention_repo
+            .finish_run(
+                run_id,
+                status,
+                stats.archived,
+                stats.deleted,
+                stats.files.len() as i64,
+                error,
+            )
+            .await?;
+        info!(
+            "History retention run {} {}: archived {}, deleted {}",
+            run_id, status, run.archived_count, run.deleted_count
+        );
+
+        let ctx = ctx.cloned().unwrap_or_default();
+        let mut details = json!({
+            "triggered_by": run.triggered_by,
+            "status": run.status,
+            "archived": run.archived_count,
+            "deleted": run.deleted_count,
+            "archive_files": run.archive_files,
+        });
+        if let Some(client_id) = &ctx.client_id {
+            details["client_id"] = json!(client_id);
+        }
+        let entry = History {
+            entity_type: Some("history_retention_run".to_string()),
+            request_id: ctx.request_id,
+            ..History::new(
+                ctx.actor_id,
+                "history_retention",
+                Some(run_id),
+                Some(details),
+                ctx.ip_address,
+                ctx.user_agent,
+            )
+        };
+        if let Err(e) = self.history_repo.create(entry).await {
+            error!("Failed to log history retention run {}: {}", run_id, e);
+        }
+
+        result?;
+        Ok(run)
+    }
+
+    /// Works through expired rows batch by batch. Returns `false` when stopped by shutdown.
+    async fn archive_and_prune(
+        &self,
+        run_id: i64,
+        default_cutoff: Option<&str>,
+        action_cutoffs: &[(String, Option<String>)],
+        shutdown: Option<&watch::Receiver<bool>>,
+        stats: &mut RunStats,
+    ) -> Result<bool, AppError> {
+        let mut after_id = 0;
+        loop {
+            if shutdown.is_some_and(|shutdown| *shutdown.borrow()) {
+                return Ok(false);
+            }
+
+            let expired = self
+                .history_repo
+                .find_expired(
+                    default_cutoff,
+                    action_cutoffs,
+                    after_id,
+                    RETENTION_BATCH_SIZE,
+                )
+                .await?;
+            let Some(last) = expired.last() else {
+                return Ok(true);
+            };
+            after_id = last.link.id;
+
+            // Rows with nothing kept between them are removed as one run with one checkpoint
+            let mut runs: Vec<Vec<HistoryLink>> = Vec::new();
+            for row in expired {
+                match runs.last_mut() {
+                    Some(run) if run.last().map(|link| link.id) == row.previous_id => {
+                        run.push(row.link)
+                    }
+                    _ => runs.push(vec![row.link]),
+                }
+            }
+
+            let rows: Vec<HistoryExportRow> = runs
+                .iter()
+                .flatten()
+                .cloned()
+                .map(HistoryExportRow::from)
+                .collect();
+            let archived = rows.len() as i64;
+            let root = PathBuf::from(&self.config.archive_path);
+            let files = tokio::task::spawn_blocking(move || write_archive(&root, run_id, rows))
+                .await
+                .map_err(|e| AppError::InternalServerError(e.to_string()))?
+                .map_err(|e| {
+                    error!("Failed to archive history: {}", e);
+                    AppError::InternalServerError("Failed to archive history".to_string())
+                })?;
+            stats.archived += archived;
+            stats.files.extend(files);
+
+            stats.deleted += self.history_repo.prune(&runs).await? as i64;
+        }
+    }
+}
+
+/// Appends rows as gzip compressed JSON Lines to `<root>/<yyyy>/<mm>/<dd>/history-<run>.jsonl.gz`,
+/// by the date each row was created. Every call adds a complete gzip member and syncs the file
+/// before returning, so rows are on disk before they are deleted.
+fn write_archive(
+    root: &Path,
+    run_id: i64,
+    rows: Vec<HistoryExportRow>,
+) -> io::Result<Vec<PathBuf>> {
+    let mut partitions: BTreeMap<PathBuf, Vec<HistoryExportRow>> = BTreeMap::new();
+    for row in rows {
+        let date = row
+            .created_at
+            .get(..10)
+            .and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+            .map_or("undated".to_string(), |date| {
+                date.format("%Y/%m/%d").to_string()
+            });
+        partitions
+            .entry(root.join(date).join(format!("history-{}.jsonl.gz", run_id)))
+            .or_default()
+            .push(row);
+    }
+
+    let mut files = Vec::with_capacity(partitions.len());
+    for (path, rows) in partitions {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        for row in rows {
+            encoder.write_all(ExportFormat::Jsonl.encode(row).as_bytes())?;
+        }
+        encoder.finish()?.sync_all()?;
+        files.push(path);
+    }
+
+    Ok(files)
+}
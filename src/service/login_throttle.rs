@@ -0,0 +1,359 @@
+use crate::{
+    config::{auth::audit_context::AuditContext, env_loader::LoginThrottle},
+    errors::AppError,
+    model::{dto::login_throttle::LockoutResponse, entity::login_throttle::LoginThrottleEntry},
+    repository::login_throttle::LoginThrottleRepository,
+    service::audit::{AuditEntity, AuditService},
+};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tracing::warn;
+
+const USER_KEY_PREFIX: &str = "user:";
+const IP_KEY_PREFIX: &str = "ip:";
+
+/// Entries idle for longer than this are dropped by the in-memory store
+const MEMORY_STORE_MAX_IDLE_SECS: i64 = 24 * 60 * 60;
+
+/// How a failed attempt updates a counter: when it starts over and when it locks out
+#[derive(Debug, Clone, Copy)]
+pub struct FailurePolicy {
+    pub window_secs: i64,
+    pub max_failures: i64,
+    pub lockout_secs: i64,
+}
+
+impl FailurePolicy {
+    /// A counter starts over once its lockout has passed or its window has elapsed
+    pub fn is_expired(&self, entry: &LoginThrottleEntry, now: i64) -> bool {
+        match entry.locked_until {
+            Some(until) => until <= now,
+            None => now - entry.first_failure_at >= self.window_secs,
+        }
+    }
+
+    /// Counts one failure at `now` on top of the current counter
+    fn apply(
+        &self,
+        key: &str,
+        current: Option<LoginThrottleEntry>,
+        now: i64,
+    ) -> LoginThrottleEntry {
+        let mut entry = match current {
+            Some(entry) if !self.is_expired(&entry, now) => entry,
+            _ => LoginThrottleEntry {
+                key: key.to_string(),
+                failures: 0,
+                first_failure_at: now,
+                last_failure_at: now,
+                locked_until: None,
+            },
+        };
+
+        entry.failures += 1;
+        entry.last_failure_at = now;
+        if entry.failures >= self.max_failures {
+            entry.locked_until = Some(now + self.lockout_secs);
+        }
+        entry
+    }
+}
+
+/// Storage backend for failed login counters
+#[async_trait]
+pub trait ThrottleStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<LoginThrottleEntry>, AppError>;
+    /// Atomically counts one failure against `key` and returns the updated counter, so that
+    /// concurrent failures can never overwrite each other's counts
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        policy: FailurePolicy,
+    ) -> Result<LoginThrottleEntry, AppError>;
+    async fn remove(&self, key: &str) -> Result<bool, AppError>;
+    async fn list(&self) -> Result<Vec<LoginThrottleEntry>, AppError>;
+}
+
+/// Process-local store. Counters are lost on restart and not shared between instances.
+#[derive(Default)]
+pub struct MemoryThrottleStore {
+    entries: Mutex<HashMap<String, LoginThrottleEntry>>,
+}
+
+#[async_trait]
+impl ThrottleStore for MemoryThrottleStore {
+    async fn get(&self, key: &str) -> Result<Option<LoginThrottleEntry>, AppError> {
+        Ok(self
+            .entries
+            .lock()
+            .expect("throttle store lock poisoned")
+            .get(key)
+            .cloned())
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        policy: FailurePolicy,
+    ) -> Result<LoginThrottleEntry, AppError> {
+        let mut entries = self.entries.lock().expect("throttle store lock poisoned");
+        entries.retain(|_, e| {
+            now - e.last_failure_at < MEMORY_STORE_MAX_IDLE_SECS
+                || e.locked_until.is_some_and(|until| until > now)
+        });
+        let entry = policy.apply(key, entries.remove(key), now);
+        entries.insert(entry.key.clone(), entry.clone());
+        Ok(entry)
+    }
+
+    async fn remove(&self, key: &str) -> Result<bool, AppError> {
+        Ok(self
+            .entries
+            .lock()
+            .expect("throttle store lock poisoned")
+            .remove(key)
+            .is_some())
+    }
+
+    async fn list(&self) -> Result<Vec<LoginThrottleEntry>, AppError> {
+        let mut entries: Vec<LoginThrottleEntry> = self
+            .entries
+            .lock()
+            .expect("throttle store lock poisoned")
+            .values()
+            .cloned()
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.last_failure_at));
+        Ok(entries)
+    }
+}
+
+/// Store backed by the `login_throttle` table, shared across restarts and instances
+pub struct SqliteThrottleStore {
+    repo: LoginThrottleRepository,
+}
+
+impl SqliteThrottleStore {
+    pub fn new(repo: LoginThrottleRepository) -> Self {
+        Self { repo }
+    }
+}
+
+#[async_trait]
+impl ThrottleStore for SqliteThrottleStore {
+    async fn get(&self, key: &str) -> Result<Option<LoginThrottleEntry>, AppError> {
+        self.repo.find(key).await
+    }
+
+    async fn record_failure(
+        &self,
+        key: &str,
+        now: i64,
+        policy: FailurePolicy,
+    ) -> Result<LoginThrottleEntry, AppError> {
+        self.repo
+            .increment_failures(
+                key,
+                now,
+                policy.window_secs,
+                policy.max_failures,
+                policy.lockout_secs,
+            )
+            .await
+    }
+
+    async fn remove(&self, key: &str) -> Result<bool, AppError> {
+        self.repo.delete(key).await
+    }
+
+    async fn list(&self) -> Result<Vec<LoginThrottleEntry>, AppError> {
+        self.repo.find_all().await
+    }
+}
+
+/// Throttles login attempts per username (exponential backoff, then a temporary lockout)
+/// and per IP address (temporary lockout only)
+pub struct LoginThrottleService {
+    config: LoginThrottle,
+    store: Arc<dyn ThrottleStore>,
+    audit: Arc<AuditService>,
+}
+
+impl LoginThrottleService {
+    pub fn new(
+        config: LoginThrottle,
+        store: Arc<dyn ThrottleStore>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            config,
+            store,
+            audit,
+        }
+    }
+
+    /// Rejects the attempt with `TooManyRequests` while the username or IP is backing off or locked out
+    pub async fn check(&self, username: &str, ip_address: Option<&str>) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let now = Utc::now().timestamp();
+        let mut retry_after = 0;
+        for key in throttle_keys(username, ip_address) {
+            if let Some(entry) = self.store.get(&key).await? {
+                retry_after = retry_after.max(self.retry_after(&entry, now));
+            }
+        }
+
+        if retry_after > 0 {
+            warn!(username, ip_address, retry_after, "Login attempt throttled");
+            return Err(AppError::TooManyRequests {
+                message: format!(
+                    "Too many failed login attempts. Try again in {} seconds",
+                    retry_after
+                ),
+                retry_after,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Counts a failed attempt against both the username and the IP address
+    pub async fn record_failure(
+        &self,
+        username: &str,
+        ip_address: Option<&str>,
+    ) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        let now = Utc::now().timestamp();
+        for key in throttle_keys(username, ip_address) {
+            let policy = self.policy(&key);
+            let entry = self.store.record_failure(&key, now, policy).await?;
+            if entry.failures >= policy.max_failures {
+                warn!(key = %entry.key, failures = entry.failures, "Login locked out");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Clears the username counter after a successful login. The IP counter is kept so that
+    /// one valid account cannot be used to reset guessing from the same address.
+    pub async fn record_success(&self, username: &str) -> Result<(), AppError> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+
+        self.store.remove(&user_key(username)).await?;
+        Ok(())
+    }
+
+    /// Lists usernames and IP addresses that currently have failed attempts on record
+    pub async fn list_lockouts(&self) -> Result<Vec<LockoutResponse>, AppError> {
+        let now = Utc::now().timestamp();
+        Ok(self
+            .store
+            .list()
+            .await?
+            .into_iter()
+            .filter(|entry| !self.is_expired(entry, now))
+            .map(|entry| {
+                let retry_after = self.retry_after(&entry, now);
+                LockoutResponse::new(entry, retry_after)
+            })
+            .collect())
+    }
+
+    /// Removes the counter of a username (`user:<name>`) or IP address (`ip:<address>`)
+    pub async fn clear(&self, ctx: &AuditContext, key: &str) -> Result<(), AppError> {
+        if !self.store.remove(key).await? {
+            return Err(AppError::NotFound(format!("No lockout for '{}'", key)));
+        }
+
+        self.audit
+            .record_event(
+                ctx,
+                "login_lockout_cleared",
+                AuditEntity::LoginLockout,
+                serde_json::json!({ "key": key }),
+            )
+            .await;
+
+        Ok(())
+    }
+
+    /// Seconds the key still has to wait before the next attempt
+    fn retry_after(&self, entry: &LoginThrottleEntry, now: i64) -> i64 {
+        if let Some(until) = entry.locked_until {
+            if until > now {
+                return until - now;
+            }
+        }
+        // IP 주소는 여러 사용자가 공유할 수 있으므로 backoff 없이 lockout만 적용
+        if entry.key.starts_with(IP_KEY_PREFIX)
+            || self.is_expired(entry, now)
+            || entry.failures <= 0
+        {
+            return 0;
+        }
+
+        let exponent = (entry.failures - 1).clamp(0, 30) as u32;
+        let delay = self
+            .config
+            .backoff_base_secs
+            .saturating_mul(2_i64.saturating_pow(exponent))
+            .min(self.config.backoff_max_secs);
+        (entry.last_failure_at + delay - now).max(0)
+    }
+
+    fn policy(&self, key: &str) -> FailurePolicy {
+        let max_failures = if key.starts_with(IP_KEY_PREFIX) {
+            self.config.ip_max_failures
+        } else {
+            self.config.max_failures
+        };
+        FailurePolicy {
+            window_secs: self.config.window_secs,
+            max_failures: i64::from(max_failures),
+            lockout_secs: self.config.lockout_secs,
+        }
+    }
+
+    fn is_expired(&self, entry: &LoginThrottleEntry, now: i64) -> bool {
+        self.policy(&entry.key).is_expired(entry, now)
+    }
+}
+
+/// Builds the throttle store selected by `LOGIN_THROTTLE_STORE`
+pub fn build_throttle_store(
+    config: &LoginThrottle,
+    repo: LoginThrottleRepository,
+) -> Arc<dyn ThrottleStore> {
+    match config.store.as_str() {
+        "sqlite" => Arc::new(SqliteThrottleStore::new(repo)),
+        _ => Arc::new(MemoryThrottleStore::default()),
+    }
+}
+
+fn user_key(username: &str) -> String {
+    format!("{}{}", USER_KEY_PREFIX, username.trim().to_lowercase())
+}
+
+fn throttle_keys(username: &str, ip_address: Option<&str>) -> Vec<String> {
+    let mut keys = vec![user_key(username)];
+    if let Some(ip) = ip_address {
+        keys.push(format!("{}{}", IP_KEY_PREFIX, ip));
+    }
+    keys
+}
@@ -0,0 +1,101 @@
+use crate::{config::env_loader::Mail, errors::AppError};
+use async_trait::async_trait;
+use chrono::Utc;
+use std::{path::PathBuf, sync::Arc};
+use tracing::info;
+
+/// A plain-text mail to be delivered to a single recipient
+#[derive(Debug, Clone)]
+pub struct MailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Delivery backend for outgoing mail
+#[async_trait]
+pub trait MailSender: Send + Sync {
+    async fn send(&self, message: &MailMessage) -> Result<(), AppError>;
+}
+
+/// Writes mails to the application log. Useful for local development only.
+pub struct LogMailSender {
+    from: String,
+}
+
+impl LogMailSender {
+    pub fn new(from: String) -> Self {
+        Self { from }
+    }
+}
+
+#[async_trait]
+impl MailSender for LogMailSender {
+    async fn send(&self, message: &MailMessage) -> Result<(), AppError> {
+        info!(
+            from = %self.from,
+            to = %message.to,
+            subject = %message.subject,
+            "Mail (log sender):\n{}",
+            message.body
+        );
+        Ok(())
+    }
+}
+
+/// Writes each mail as a `.eml` file into an outbox directory
+pub struct FileMailSender {
+    from: String,
+    outbox: PathBuf,
+}
+
+impl FileMailSender {
+    pub fn new(from: String, outbox: impl Into<PathBuf>) -> Self {
+        Self {
+            from,
+            outbox: outbox.into(),
+        }
+    }
+}
+
+#[async_trait]
+impl MailSender for FileMailSender {
+    async fn send(&self, message: &MailMessage) -> Result<(), AppError> {
+        tokio::fs::create_dir_all(&self.outbox)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Mail outbox error: {}", e)))?;
+
+        let now = Utc::now();
+        let path = self.outbox.join(format!(
+            "{}-{}.eml",
+            now.format("%Y%m%d%H%M%S"),
+            uuid::Uuid::new_v4()
+        ));
+        let content = format!(
+            "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nContent-Type: text/plain; charset=utf-8\r\n\r\n{}\r\n",
+            self.from,
+            message.to,
+            message.subject,
+            now.to_rfc2822(),
+            message.body
+        );
+
+        tokio::fs::write(&path, content)
+            .await
+            .map_err(|e| AppError::InternalServerError(format!("Mail outbox error: {}", e)))?;
+
+        info!(to = %message.to, path = %path.display(), "Mail written to outbox");
+        Ok(())
+    }
+}
+
+/// Builds the mail sender selected by `MAIL_SENDER`
+pub fn build_mail_sender(config: &Mail) -> Arc<dyn MailSender> {
+    match config.sender.as_str() {
+        "file" => Arc::new(FileMailSender::new(
+            config.from.clone(),
+            &config.outbox_path,
+        )),
+        _ => Arc::new(LogMailSender::new(config.from.clone())),
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    config::auth::audit_context::AuditContext,
+    errors::AppError,
+    model::{
+        dto::mfa::{MfaStatusResponse, TotpSetupResponse},
+        entity::user_mfa::UserMfa,
+    },
+    repository::mfa::MfaRepository,
+    service::audit::{AuditEntity, AuditService},
+    util::token_util,
+};
+use chrono::NaiveDateTime;
+use rand::Rng;
+use serde::Serialize;
+use std::sync::Arc;
+use totp_rs::{Builder, Secret, Totp};
+use tracing::{error, info};
+
+const RECOVERY_CODE_COUNT: usize = 10;
+const RECOVERY_CODE_LEN: usize = 10;
+// 혼동되기 쉬운 문자(0/O, 1/I/L)는 제외
+const RECOVERY_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKMNPQRSTUVWXYZ23456789";
+
+/// Second factor fields recorded in the audit trail of a user. Recovery codes only show
+/// that they changed.
+#[derive(Debug, Serialize)]
+struct MfaAuditState {
+    totp_enabled_at: Option<NaiveDateTime>,
+    recovery_codes_remaining: usize,
+    recovery_code_hashes: Vec<String>,
+}
+
+/// TOTP (RFC 6238) second factor and one-time recovery codes
+pub struct MfaService {
+    mfa_repo: MfaRepository,
+    audit: Arc<AuditService>,
+    issuer: String,
+}
+
+impl MfaService {
+    pub fn new(mfa_repo: MfaRepository, audit: Arc<AuditService>, issuer: String) -> Self {
+        Self {
+            mfa_repo,
+            audit,
+            issuer,
+        }
+    }
+
+    pub async fn get_state(&self, user_id: i64) -> Result<UserMfa, AppError> {
+        self.mfa_repo
+            .find_by_user(user_id)
+            .await?
+            .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+    }
+
+    pub async fn get_status(&self, user_id: i64) -> Result<MfaStatusResponse, AppError> {
+        let mfa = self.get_state(user_id).await?;
+        let recovery_codes_remaining = if mfa.is_enabled() {
+            self.mfa_repo.count_unused_recovery_codes(user_id).await?
+        } else {
+            0
+        };
+
+        Ok(MfaStatusResponse {
+            enabled: mfa.is_enabled(),
+            pending: !mfa.is_enabled() && mfa.totp_secret.is_some(),
+            required: mfa.require_mfa,
+            recovery_codes_remaining,
+        })
+    }
+
+    /// Generates a new secret for the user to add to an authenticator app. The secret only
+    /// takes effect after `confirm_enrollment` receives a valid code for it.
+    pub async fn begin_enrollment(&self, user_id: i64) -> Result<TotpSetupResponse, AppError> {
+        let mfa = self.get_state(user_id).await?;
+        if mfa.is_enabled() {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let secret = Secret::generate().to_base32();
+        let totp = self.build_totp(&secret, &mfa.username)?;
+        let otpauth_uri = totp
+            .to_url()
+            .map_err(|e| AppError::InternalServerError(format!("TOTP error: {}", e)))?;
+        let qr_code = match totp.to_qr_base64() {
+            Ok(png) => Some(format!("data:image/png;base64,{}", png)),
+            Err(e) => {
+                error!("Failed to render TOTP QR code: {}", e);
+                None
+            }
+        };
+
+        if !self.mfa_repo.set_pending_secret(user_id, &secret).await? {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        Ok(TotpSetupResponse {
+            secret,
+            otpauth_uri,
+            qr_code,
+        })
+    }
+
+    /// Activates the pending secret and returns a fresh set of recovery codes
+    pub async fn confirm_enrollment(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Vec<String>, AppError> {
+        let before = self.audit_state(user_id).await?;
+        let mfa = self.get_state(user_id).await?;
+        if mfa.is_enabled() {
+            return Err(AppError::Conflict(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+        let secret = mfa.totp_secret.as_deref().ok_or_else(|| {
+            AppError::BadRequest("Two-factor enrollment has not been started".to_string())
+        })?;
+
+        let step = self
+            .build_totp(secret, &mfa.username)?
+            .check_current(&normalize_totp_code(code))
+            .ok_or_else(|| AppError::BadRequest("Invalid verification code".to_string()))?;
+
+        let (codes, hashes) = generate_recovery_codes();
+        if !self.mfa_repo.enable(user_id, step as i64, &hashes).await? {
+            return Err(AppError::Conflict(
+                "Two-factor enrollment has changed, please start again".to_string(),
+            ));
+        }
+
+        info!("User {} enabled two-factor authentication", user_id);
+        self.record(ctx, "mfa_enabled", user_id, &before).await?;
+
+        Ok(codes)
+    }
+
+    /// Checks a TOTP code or, failing that, a recovery code. Each code is accepted only once.
+    pub async fn verify(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        code: &str,
+    ) -> Result<bool, AppError> {
+        let mfa = self.get_state(user_id).await?;
+        let secret = match (mfa.is_enabled(), mfa.totp_secret.as_deref()) {
+            (true, Some(secret)) => secret,
+            _ => {
+                return Err(AppError::BadRequest(
+                    "Two-factor authentication is not enabled".to_string(),
+                ))
+            }
+        };
+
+        let totp_code = normalize_totp_code(code);
+        if totp_code.len() == 6 && totp_code.chars().all(|c| c.is_ascii_digit()) {
+            return match self
+                .build_totp(secret, &mfa.username)?
+                .check_current(&totp_code)
+            {
+                Some(step) => self.mfa_repo.mark_step_used(user_id, step as i64).await,
+                None => Ok(false),
+            };
+        }
+
+        let before = self.audit_state(user_id).await?;
+        let hash = hash_recovery_code(code);
+        if !self.mfa_repo.consume_recovery_code(user_id, &hash).await? {
+            return Ok(false);
+        }
+        self.record(ctx, "mfa_recovery_code_used", user_id, &before)
+            .await?;
+
+        Ok(true)
+    }
+
+    /// Turns the second factor off after checking a current code
+    pub async fn disable(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        code: &str,
+    ) -> Result<(), AppError> {
+        let mfa = self.get_state(user_id).await?;
+        if mfa.require_mfa {
+            return Err(AppError::BadRequest(
+                "Two-factor authentication is required for your user type".to_string(),
+            ));
+        }
+        if !mfa.is_enabled() {
+            // 등록만 시작하고 확인하지 않은 경우: 대기 중인 secret만 정리
+            self.mfa_repo.disable(user_id).await?;
+            return Ok(());
+        }
+        if !self.verify(ctx, user_id, code).await? {
+            return Err(AppError::BadRequest(
+                "Invalid verification code".to_string(),
+            ));
+        }
+
+        let before = self.audit_state(user_id).await?;
+        self.mfa_repo.disable(user_id).await?;
+        info!("User {} disabled two-factor authentication", user_id);
+        self.record(ctx, "mfa_disabled", user_id, &before).await?;
+        Ok(())
+    }
+
+    /// Replaces every recovery code of the user after checking a current code
+    pub async fn regenerate_recovery_codes(
+        &self,
+        ctx: &AuditContext,
+        user_id: i64,
+        code: &str,
+    ) -> Result<Vec<String>, AppError> {
+        if !self.verify(ctx, user_id, code).await? {
+            return Err(AppError::BadRequest(
+                "Invalid verification code".to_string(),
+            ));
+        }
+
+        let before = self.audit_state(user_id).await?;
+        let (codes, hashes) = generate_recovery_codes();
+        self.mfa_repo
+            .replace_recovery_codes(user_id, &hashes)
+            .await?;
+        self.record(ctx, "mfa_recovery_codes_regenerated", user_id, &before)
+            .await?;
+
+        Ok(codes)
+    }
+
+    /// Removes another user's second factor, e.g. after they lost their device and recovery codes
+    pub async fn reset(&self, ctx: &AuditContext, user_id: i64) -> Result<(), AppError> {
+        self.get_state(user_id).await?;
+        let before = self.audit_state(user_id).await?;
+        self.mfa_repo.disable(user_id).await?;
+        self.record(ctx, "mfa_reset", user_id, &before).await
+    }
+
+    fn build_totp(&self, secret: &str, username: &str) -> Result<Totp, AppError> {
+        let secret = Secret::try_from_base32(secret)
+            .map_err(|e| AppError::InternalServerError(format!("Invalid TOTP secret: {}", e)))?;
+        // otpauth URI에서 ':'는 issuer와 계정명을 구분하는 문자라 사용할 수 없음
+        Builder::new()
+            .with_secret(secret)
+            .with_account_name(username.replace(':', ""))
+            .with_issuer(Some(self.issuer.replace(':', "")))
+            .build()
+            .map_err(|e| AppError::InternalServerError(format!("TOTP error: {}", e)))
+    }
+
+    async fn audit_state(&self, user_id: i64) -> Result<MfaAuditState, AppError> {
+        let mfa = self.get_state(user_id).await?;
+        let recovery_code_hashes = self
+            .mfa_repo
+            .find_unused_recovery_code_hashes(user_id)
+            .await?;
+        Ok(MfaAuditState {
+            totp_enabled_at: mfa.totp_enabled_at,
+            recovery_codes_remaining: recovery_code_hashes.len(),
+            recovery_code_hashes,
+        })
+    }
+
+    /// Audits the change from `before` to the current second factor state of the user
+    async fn record(
+        &self,
+        ctx: &AuditContext,
+        action: &str,
+        user_id: i64,
+        before: &MfaAuditState,
+    ) -> Result<(), AppError> {
+        let after = self.audit_state(user_id).await?;
+        self.audit
+            .record_updated(ctx, action, AuditEntity::User(user_id), before, &after)
+            .await;
+        Ok(())
+    }
+}
+
+fn normalize_totp_code(code: &str) -> String {
+    code.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Recovery codes are compared case-insensitively and without separators
+fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    token_util::hash_opaque_token(&normalized)
+}
+
+/// Returns the codes to show to the user (formatted `XXXXX-XXXXX`) and their hashes
+fn generate_recovery_codes() -> (Vec<String>, Vec<String>) {
+    let mut rng = rand::thread_rng();
+    let codes: Vec<String> = (0..RECOVERY_CODE_COUNT)
+        .map(|_| {
+            let raw: String = (0..RECOVERY_CODE_LEN)
+                .map(|_| {
+                    RECOVERY_CODE_ALPHABET[rng.gen_range(0..RECOVERY_CODE_ALPHABET.len())] as char
+                })
+                .collect();
+            format!(
+                "{}-{}",
+                &raw[..RECOVERY_CODE_LEN / 2],
+                &raw[RECOVERY_CODE_LEN / 2..]
+            )
+        })
+        .collect();
+    let hashes = codes.iter().map(|code| hash_recovery_code(code)).collect();
+    (codes, hashes)
+}
@@ -1,6 +1,13 @@
+pub mod api_key;
+pub mod audit;
 pub mod auth;
 pub mod history;
+pub mod history_retention;
+pub mod login_throttle;
+pub mod mail;
+pub mod mfa;
 pub mod oauth;
+pub mod oauth_client;
 pub mod permission;
 pub mod user;
 pub mod user_type;
@@ -1,173 +1,1038 @@
 use crate::{
+    config::{auth::audit_context::AuditContext, env_loader::Oidc},
+    errors::AppError,
     model::{
-        dto::oauth::OAuthAuthorizeRequest, dto::oauth::OAuthAuthorizeResponse,
-        dto::oauth::OAuthTokenRequest, dto::oauth::OAuthTokenResponse,
+        dto::oauth::OAuthAuthorizeRequest, dto::oauth::OAuthIntrospectResponse,
+        dto::oauth::OAuthTokenLookupRequest, dto::oauth::OAuthTokenRequest,
+        dto::oauth::OAuthTokenResponse, dto::oauth::OidcDiscoveryResponse,
+        dto::oauth::OidcUserInfoResponse, entity::oauth_client::OAuthClient,
         entity::oauth_code::OAuthCode, entity::oauth_token::OAuthToken,
     },
-    repository::oauth::OAuthRepository,
+    repository::{auth::AuthRepository, oauth::OAuthRepository, user::UserRepository},
+    service::{
+        audit::{AuditEntity, AuditService},
+        oauth_client::SUPPORTED_GRANT_TYPES,
+    },
+    util::{
+        jwk_util,
+        token_util::{self, hash_opaque_token, IdTokenClaims},
+    },
 };
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::jwk::JwkSet;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tracing::{error, warn};
+use url::Url;
 
 // OAuth 관련 서비스 (authorize, token 등)
 
+/// Prefix of opaque OAuth access tokens, which tells them apart from our own JWTs
+pub const ACCESS_TOKEN_PREFIX: &str = "at_";
+const AUTHORIZATION_CODE_TTL_MINUTES: i64 = 10;
+const ACCESS_TOKEN_TTL_SECS: i64 = 3600;
+const REFRESH_TOKEN_TTL_DAYS: i64 = 30;
+const OPENID_SCOPE: &str = "openid";
+const OIDC_SCOPES: [&str; 3] = [OPENID_SCOPE, "profile", "email"];
+
+/// An authorization request that passed every check against the client record
+pub struct AuthorizeContext {
+    pub client: OAuthClient,
+    pub scopes: Vec<String>,
+}
+
+/// Why an authorization request was rejected
+pub enum AuthorizeError {
+    /// client_id or redirect_uri cannot be trusted, so the user must not be redirected
+    InvalidClient(String),
+    /// RFC 6749 4.1.2.1 error response, already encoded into the client's redirect_uri
+    Redirect(String),
+}
+
+/// RFC 6749 5.2 error of the token, introspection and revocation endpoints
+#[derive(Debug)]
+pub struct OAuthError {
+    pub error: &'static str,
+    pub description: Option<String>,
+}
+
+impl OAuthError {
+    pub fn new(error: &'static str, description: impl Into<String>) -> Self {
+        Self {
+            error,
+            description: Some(description.into()),
+        }
+    }
+}
+
+impl From<anyhow::Error> for OAuthError {
+    fn from(e: anyhow::Error) -> Self {
+        error!("OAuth request failed: {}", e);
+        Self {
+            error: "server_error",
+            description: None,
+        }
+    }
+}
+
+impl From<AppError> for OAuthError {
+    fn from(e: AppError) -> Self {
+        anyhow::Error::from(e).into()
+    }
+}
+
 pub struct OAuthService {
     oauth_repo: OAuthRepository,
+    auth_repo: AuthRepository,
+    user_repo: UserRepository,
+    audit: Arc<AuditService>,
+    base_url: String,
+    oidc: Oidc,
 }
 
 impl OAuthService {
-    pub fn new(oauth_repo: OAuthRepository) -> Self {
-        Self { oauth_repo }
+    pub fn new(
+        oauth_repo: OAuthRepository,
+        auth_repo: AuthRepository,
+        user_repo: UserRepository,
+        audit: Arc<AuditService>,
+        base_url: String,
+        oidc: Oidc,
+    ) -> Self {
+        Self {
+            oauth_repo,
+            auth_repo,
+            user_repo,
+            audit,
+            base_url,
+            oidc,
+        }
     }
 
-    pub async fn authorize(
+    /// Validates an authorization request before the consent screen is shown
+    pub async fn validate_authorize(
         &self,
-        req: OAuthAuthorizeRequest,
-    ) -> Result<OAuthAuthorizeResponse, anyhow::Error> {
-        // 1. client_id, redirect_uri 등 검증
+        req: &OAuthAuthorizeRequest,
+    ) -> Result<AuthorizeContext, AuthorizeError> {
+        // 1. client_id, redirect_uri 검증: 실패 시 redirect_uri를 신뢰할 수 없으므로 리다이렉트하지 않음
         let client = self
             .oauth_repo
             .find_client(&req.client_id)
             .await
-            .ok_or_else(|| anyhow::anyhow!("invalid_client_id"))?;
-        if client.redirect_uri != req.redirect_uri {
-            return Err(anyhow::anyhow!("invalid_redirect_uri"));
+            .ok_or_else(|| AuthorizeError::InvalidClient("Unknown client_id".to_string()))?;
+        if !client
+            .redirect_uri_list()
+            .contains(&req.redirect_uri.as_str())
+            || Url::parse(&req.redirect_uri).is_err()
+        {
+            return Err(AuthorizeError::InvalidClient(
+                "redirect_uri is not registered for this client".to_string(),
+            ));
+        }
+
+        // 2. 나머지 오류는 redirect_uri로 전달
+        let redirect_error = |error: &str, description: &str| {
+            AuthorizeError::Redirect(authorization_error_url(
+                &req.redirect_uri,
+                error,
+                description,
+                req.state.as_deref(),
+            ))
+        };
+
+        match req.response_type.as_deref() {
+            Some("code") => {}
+            Some(_) => {
+                return Err(redirect_error(
+                    "unsupported_response_type",
+                    "Only response_type=code is supported",
+                ))
+            }
+            None => {
+                return Err(redirect_error(
+                    "invalid_request",
+                    "response_type is required",
+                ))
+            }
         }
-        // 2. 권한 부여 코드 생성 및 저장
+
+        if !client_allows_grant(&client, "authorization_code") {
+            return Err(redirect_error(
+                "unauthorized_client",
+                "The client may not use the authorization code grant",
+            ));
+        }
+
+        let allowed: Vec<&str> = client
+            .scope
+            .as_deref()
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect();
+        let scopes: Vec<String> = match req.scope.as_deref().map(str::trim) {
+            Some(scope) if !scope.is_empty() => {
+                let requested: Vec<String> = scope.split_whitespace().map(str::to_string).collect();
+                if let Some(unknown) = requested.iter().find(|s| !allowed.contains(&s.as_str())) {
+                    return Err(redirect_error(
+                        "invalid_scope",
+                        &format!("Scope '{}' is not allowed for this client", unknown),
+                    ));
+                }
+                requested
+            }
+            // scope를 생략하면 클라이언트에 허용된 기본 scope 사용 (OIDC scope는 명시적으로 요청해야 함)
+            _ => allowed
+                .iter()
+                .filter(|s| !OIDC_SCOPES.contains(s))
+                .map(|s| s.to_string())
+                .collect(),
+        };
+        if scopes.iter().any(|s| s == OPENID_SCOPE) && !self.oidc.enabled() {
+            return Err(redirect_error(
+                "invalid_scope",
+                "OpenID Connect is not enabled on this server",
+            ));
+        }
+
+        // PKCE (RFC 7636 4.3): code_challenge_method 생략 시 plain
+        match (
+            req.code_challenge.as_deref(),
+            req.code_challenge_method.as_deref(),
+        ) {
+            (None, Some(_)) => {
+                return Err(redirect_error(
+                    "invalid_request",
+                    "code_challenge_method requires a code_challenge",
+                ))
+            }
+            (Some(challenge), method) => {
+                if !matches!(method, None | Some("S256") | Some("plain")) {
+                    return Err(redirect_error(
+                        "invalid_request",
+                        "code_challenge_method must be S256 or plain",
+                    ));
+                }
+                if !is_valid_pkce_value(challenge) {
+                    return Err(redirect_error(
+                        "invalid_request",
+                        "code_challenge is malformed",
+                    ));
+                }
+            }
+            (None, None) => {}
+        }
+
+        Ok(AuthorizeContext { client, scopes })
+    }
+
+    /// Issues an authorization code bound to the approving user and returns the redirect URL
+    pub async fn approve(
+        &self,
+        audit: &AuditContext,
+        req: &OAuthAuthorizeRequest,
+        user_id: i64,
+    ) -> Result<String, AuthorizeError> {
+        let ctx = self.validate_authorize(req).await?;
+
         use chrono::{Duration, Utc};
         use rand::Rng;
         let code = format!("{:x}", rand::thread_rng().gen::<u128>());
-        let expires_at = Utc::now() + Duration::minutes(10);
+        let scope = ctx.scopes.join(" ");
         let oauth_code = OAuthCode {
             code: code.clone(),
-            client_id: req.client_id.clone(),
-            user_id: None, // 실제 서비스라면 인증된 user_id 필요
+            client_id: ctx.client.client_id.clone(),
+            user_id: Some(user_id),
             redirect_uri: req.redirect_uri.clone(),
-            scope: req.scope.clone(),
-            expires_at,
+            scope: (!scope.is_empty()).then_some(scope.clone()),
+            expires_at: Utc::now() + Duration::minutes(AUTHORIZATION_CODE_TTL_MINUTES),
+            code_challenge: req.code_challenge.clone(),
+            code_challenge_method: req.code_challenge.as_ref().map(|_| {
+                req.code_challenge_method
+                    .clone()
+                    .unwrap_or_else(|| "plain".to_string())
+            }),
+            used_at: None,
+            nonce: req.nonce.clone(),
         };
-        self.oauth_repo.save_code(&oauth_code).await?;
-        // 3. 응답 반환
-        Ok(OAuthAuthorizeResponse {
-            code,
-            state: req.state,
-        })
+        if let Err(e) = self.oauth_repo.save_code(&oauth_code).await {
+            error!("Failed to save authorization code: {}", e);
+            return Err(AuthorizeError::Redirect(authorization_error_url(
+                &req.redirect_uri,
+                "server_error",
+                "Failed to issue an authorization code",
+                req.state.as_deref(),
+            )));
+        }
+
+        self.log_consent(audit, "oauth_consent_granted", &ctx.client, &scope)
+            .await;
+
+        let mut url = Url::parse(&req.redirect_uri)
+            .map_err(|_| AuthorizeError::InvalidClient("Invalid redirect_uri".to_string()))?;
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("code", &code);
+            if let Some(state) = &req.state {
+                query.append_pair("state", state);
+            }
+        }
+        Ok(url.into())
     }
 
-    pub async fn token(&self, req: OAuthTokenRequest) -> Result<OAuthTokenResponse, anyhow::Error> {
-        match req.grant_type.as_str() {
-            "authorization_code" => {
-                // 1. 코드 검증 및 토큰 발급
-                let code = req
-                    .code
-                    .as_ref()
-                    .ok_or_else(|| anyhow::anyhow!("missing_code"))?;
-                let oauth_code = self
-                    .oauth_repo
-                    .find_code(code)
-                    .await
-                    .ok_or_else(|| anyhow::anyhow!("invalid_code"))?;
-                if let Some(ref redirect_uri) = req.redirect_uri {
-                    if oauth_code.redirect_uri != *redirect_uri {
-                        return Err(anyhow::anyhow!("invalid_redirect_uri"));
-                    }
+    /// Sends the user back to the client with `access_denied`
+    pub async fn deny(
+        &self,
+        audit: &AuditContext,
+        req: &OAuthAuthorizeRequest,
+    ) -> Result<String, AuthorizeError> {
+        let ctx = self.validate_authorize(req).await?;
+        self.log_consent(
+            audit,
+            "oauth_consent_denied",
+            &ctx.client,
+            &ctx.scopes.join(" "),
+        )
+        .await;
+
+        Ok(authorization_error_url(
+            &req.redirect_uri,
+            "access_denied",
+            "The user denied the request",
+            req.state.as_deref(),
+        ))
+    }
+
+    async fn log_consent(
+        &self,
+        audit: &AuditContext,
+        action: &str,
+        client: &OAuthClient,
+        scope: &str,
+    ) {
+        self.audit
+            .record_event(
+                audit,
+                action,
+                AuditEntity::OAuthClient(client.id),
+                serde_json::json!({ "client_id": client.client_id, "scope": scope }),
+            )
+            .await;
+    }
+
+    /// Authenticates the client with client_secret_basic (`basic_credentials`) or
+    /// client_secret_post (`client_id`/`client_secret` form fields), never both
+    async fn authenticate_client(
+        &self,
+        form_client_id: Option<&str>,
+        form_client_secret: Option<&str>,
+        basic_credentials: Option<(String, String)>,
+    ) -> Result<OAuthClient, OAuthError> {
+        let (client_id, client_secret) = match basic_credentials {
+            Some(_) if form_client_secret.is_some() => {
+                return Err(OAuthError::new(
+                    "invalid_request",
+                    "Use only one client authentication method",
+                ))
+            }
+            Some((client_id, client_secret)) => {
+                if form_client_id.is_some_and(|id| id != client_id) {
+                    return Err(OAuthError::new("invalid_request", "client_id mismatch"));
                 }
-                // 토큰 생성
-                use chrono::{Duration, Utc};
-                use rand::Rng;
-                let access_token = format!("at_{:x}", rand::thread_rng().gen::<u128>());
-                let refresh_token = format!("rt_{:x}", rand::thread_rng().gen::<u128>());
-                let expires_at = Utc::now() + Duration::hours(1);
-                let token = OAuthToken {
-                    access_token: access_token.clone(),
-                    refresh_token: Some(refresh_token.clone()),
-                    client_id: oauth_code.client_id.clone(),
-                    user_id: oauth_code.user_id,
-                    scope: oauth_code.scope.clone(),
-                    expires_at,
-                    created_at: Utc::now(),
-                };
-                self.oauth_repo.save_token(&token).await?;
-                Ok(OAuthTokenResponse {
-                    access_token,
-                    token_type: "bearer".to_string(),
-                    expires_in: 3600,
-                    refresh_token: Some(refresh_token),
-                    scope: oauth_code.scope,
-                })
+                (client_id, client_secret)
             }
-            "client_credentials" => {
-                // 1. 클라이언트 인증 및 토큰 발급
-                let client_id = req
-                    .client_id
-                    .as_ref()
-                    .ok_or_else(|| anyhow::anyhow!("missing_client_id"))?;
-                let client_secret = req
-                    .client_secret
-                    .as_ref()
-                    .ok_or_else(|| anyhow::anyhow!("missing_client_secret"))?;
-                let client = self
-                    .oauth_repo
-                    .find_client(client_id)
-                    .await
-                    .ok_or_else(|| anyhow::anyhow!("invalid_client_id"))?;
-                if client.client_secret != *client_secret {
-                    return Err(anyhow::anyhow!("invalid_client_secret"));
+            None => match (form_client_id, form_client_secret) {
+                (Some(client_id), Some(client_secret)) => {
+                    (client_id.to_string(), client_secret.to_string())
+                }
+                _ => {
+                    return Err(OAuthError::new(
+                        "invalid_client",
+                        "Missing client credentials",
+                    ))
+                }
+            },
+        };
+
+        let client = self
+            .oauth_repo
+            .find_client(&client_id)
+            .await
+            .ok_or_else(|| OAuthError::new("invalid_client", "Client authentication failed"))?;
+        if !self.verify_client_secret(&client, &client_secret).await {
+            return Err(OAuthError::new(
+                "invalid_client",
+                "Client authentication failed",
+            ));
+        }
+        Ok(client)
+    }
+
+    /// Compares the presented secret with the current one, or with the previous one while its
+    /// rotation overlap lasts. Hashes are compared in constant time.
+    async fn verify_client_secret(&self, client: &OAuthClient, client_secret: &str) -> bool {
+        let presented = hash_opaque_token(client_secret);
+        let matches = |hash: &str| bool::from(hash.as_bytes().ct_eq(presented.as_bytes()));
+
+        // 비밀값이 없는 클라이언트(평문 비밀값 폐기 후 미교체)는 인증할 수 없음
+        if client.client_secret_hash.as_deref().is_some_and(matches) {
+            return true;
+        }
+
+        let previous_valid = client
+            .previous_secret_expires_at
+            .is_some_and(|expires_at| expires_at > chrono::Utc::now().naive_utc());
+        previous_valid && client.previous_secret_hash.as_deref().is_some_and(matches)
+    }
+
+    /// Signs the ID token for the user who approved `oauth_code` (OIDC Core 3.1.3.3)
+    async fn issue_id_token(&self, oauth_code: &OAuthCode) -> Result<String, OAuthError> {
+        let key = self
+            .oidc
+            .signing_key
+            .as_ref()
+            .ok_or_else(|| OAuthError::new("invalid_scope", "OpenID Connect is not enabled"))?;
+        let user_id = oauth_code
+            .user_id
+            .ok_or_else(|| OAuthError::new("invalid_grant", "The code was not issued to a user"))?;
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|e| match e {
+                AppError::NotFound(_) => {
+                    OAuthError::new("invalid_grant", "The approving user no longer exists")
                 }
-                use chrono::{Duration, Utc};
-                use rand::Rng;
-                let access_token = format!("at_{:x}", rand::thread_rng().gen::<u128>());
-                let expires_at = Utc::now() + Duration::hours(1);
-                let token = OAuthToken {
-                    access_token: access_token.clone(),
-                    refresh_token: None,
-                    client_id: client_id.clone(),
-                    user_id: None,
-                    scope: req.scope.clone().or(client.scope.clone()),
-                    expires_at,
-                    created_at: Utc::now(),
+                e => e.into(),
+            })?;
+
+        let scope = oauth_code.scope.as_deref();
+        let now = chrono::Utc::now().timestamp();
+        let claims = IdTokenClaims {
+            iss: self.oidc.issuer.clone(),
+            sub: user.id.to_string(),
+            aud: oauth_code.client_id.clone(),
+            exp: (now + self.oidc.id_token_exp) as usize,
+            iat: now as usize,
+            nonce: oauth_code.nonce.clone(),
+            preferred_username: has_scope(scope, "profile").then(|| user.username.clone()),
+            email: has_scope(scope, "email").then_some(user.email).flatten(),
+        };
+        Ok(token_util::generate_id_token(key, &claims)?)
+    }
+
+    /// OpenID Connect UserInfo (OIDC Core 5.3) for an access token with the `openid` scope
+    pub async fn userinfo(&self, access_token: &str) -> Result<OidcUserInfoResponse, AppError> {
+        let token = self
+            .oauth_repo
+            .find_token(access_token)
+            .await
+            .ok_or_else(|| AppError::Unauthorized("invalid_token".to_string()))?;
+        if !self.owner_accepts(&token).await? {
+            return Err(AppError::Unauthorized("invalid_token".to_string()));
+        }
+        let scope = token.scope.as_deref();
+        if !has_scope(scope, OPENID_SCOPE) {
+            return Err(AppError::Forbidden("insufficient_scope".to_string()));
+        }
+        // client_credentials 토큰에는 사용자가 없음
+        let user_id = token
+            .user_id
+            .ok_or_else(|| AppError::Forbidden("insufficient_scope".to_string()))?;
+        let user = self
+            .user_repo
+            .find_by_id(user_id)
+            .await
+            .map_err(|_| AppError::Unauthorized("invalid_token".to_string()))?;
+
+        let profile = has_scope(scope, "profile");
+        Ok(OidcUserInfoResponse {
+            sub: user.id.to_string(),
+            preferred_username: profile.then(|| user.username.clone()),
+            email: has_scope(scope, "email").then_some(user.email).flatten(),
+            updated_at: profile.then(|| user.updated_at.timestamp()),
+        })
+    }
+
+    /// OpenID Provider metadata, or None while OpenID Connect is disabled
+    pub fn discovery(&self) -> Option<OidcDiscoveryResponse> {
+        let key = self.oidc.signing_key.as_ref()?;
+        let endpoint = |path: &str| format!("{}{}", self.base_url.trim_end_matches('/'), path);
+        Some(OidcDiscoveryResponse {
+            issuer: self.oidc.issuer.clone(),
+            authorization_endpoint: endpoint("/api/oauth/authorize"),
+            token_endpoint: endpoint("/api/oauth/token"),
+            userinfo_endpoint: endpoint("/api/oauth/userinfo"),
+            jwks_uri: endpoint("/api/oauth/jwks"),
+            introspection_endpoint: endpoint("/api/oauth/introspect"),
+            revocation_endpoint: endpoint("/api/oauth/revoke"),
+            response_types_supported: vec!["code"],
+            grant_types_supported: vec![
+                "authorization_code",
+                "client_credentials",
+                "refresh_token",
+            ],
+            subject_types_supported: vec!["public"],
+            id_token_signing_alg_values_supported: vec![key.algorithm],
+            scopes_supported: OIDC_SCOPES.to_vec(),
+            claims_supported: vec![
+                "iss",
+                "sub",
+                "aud",
+                "exp",
+                "iat",
+                "nonce",
+                "preferred_username",
+                "email",
+            ],
+            token_endpoint_auth_methods_supported: vec![
+                "client_secret_basic",
+                "client_secret_post",
+            ],
+            code_challenge_methods_supported: vec!["S256", "plain"],
+        })
+    }
+
+    /// Public keys for verifying ID tokens, or None while OpenID Connect is disabled
+    pub fn jwks(&self) -> Option<JwkSet> {
+        self.oidc
+            .signing_key
+            .as_ref()
+            .map(|key| jwk_util::jwk_set(&[key]))
+    }
+
+    /// Token introspection (RFC 7662), used by resource servers to validate opaque tokens
+    pub async fn introspect(
+        &self,
+        req: OAuthTokenLookupRequest,
+        basic_credentials: Option<(String, String)>,
+    ) -> Result<OAuthIntrospectResponse, OAuthError> {
+        self.authenticate_client(
+            req.client_id.as_deref(),
+            req.client_secret.as_deref(),
+            basic_credentials,
+        )
+        .await?;
+
+        // 존재하지 않거나 만료된 토큰은 active: false만 반환
+        let Some((token, _)) = self
+            .find_token_by_hint(&req.token, req.token_type_hint.as_deref())
+            .await
+        else {
+            return Ok(OAuthIntrospectResponse::default());
+        };
+        if !self.owner_accepts(&token).await? {
+            return Ok(OAuthIntrospectResponse::default());
+        }
+        Ok(OAuthIntrospectResponse {
+            active: true,
+            scope: token.scope,
+            client_id: Some(token.client_id),
+            token_type: Some("bearer".to_string()),
+            sub: token.user_id.map(|id| id.to_string()),
+            exp: Some(token.expires_at.timestamp()),
+            iat: Some(token.created_at.timestamp()),
+        })
+    }
+
+    /// Token revocation (RFC 7009). Unknown tokens and tokens of other clients are ignored,
+    /// since the response must not reveal whether a token exists.
+    pub async fn revoke(
+        &self,
+        audit: &AuditContext,
+        req: OAuthTokenLookupRequest,
+        basic_credentials: Option<(String, String)>,
+    ) -> Result<(), OAuthError> {
+        let client = self
+            .authenticate_client(
+                req.client_id.as_deref(),
+                req.client_secret.as_deref(),
+                basic_credentials,
+            )
+            .await?;
+
+        let Some((token, token_type)) = self
+            .find_token_by_hint(&req.token, req.token_type_hint.as_deref())
+            .await
+        else {
+            return Ok(());
+        };
+        if token.client_id != client.client_id {
+            warn!(
+                "Client {} tried to revoke a token of client {}",
+                client.client_id, token.client_id
+            );
+            return Ok(());
+        }
+
+        // 리프레시 토큰을 폐기하면 함께 발급된 액세스 토큰도 폐기
+        let revoked = if token_type == "refresh_token" {
+            self.oauth_repo
+                .delete_refresh_token(&req.token, &client.client_id)
+                .await?
+        } else {
+            self.oauth_repo
+                .delete_token(&req.token, &client.client_id)
+                .await?
+        };
+
+        self.audit
+            .record_event(
+                &audit.via_client(&client.client_id, token.user_id),
+                "oauth_token_revoked",
+                AuditEntity::OAuthClient(client.id),
+                serde_json::json!({
+                    "token_type": token_type,
+                    "revoked_tokens": revoked,
+                }),
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Looks the token up as the hinted type first, then as the other type (RFC 7009 2.1)
+    async fn find_token_by_hint(
+        &self,
+        token: &str,
+        token_type_hint: Option<&str>,
+    ) -> Option<(OAuthToken, &'static str)> {
+        let as_access = async {
+            self.oauth_repo
+                .find_token(token)
+                .await
+                .map(|t| (t, "access_token"))
+        };
+        let as_refresh = async {
+            self.oauth_repo
+                .find_refresh_token(token)
+                .await
+                .map(|t| (t, "refresh_token"))
+        };
+        if token_type_hint == Some("refresh_token") {
+            match as_refresh.await {
+                Some(found) => Some(found),
+                None => as_access.await,
+            }
+        } else {
+            match as_access.await {
+                Some(found) => Some(found),
+                None => as_refresh.await,
+            }
+        }
+    }
+
+    /// Revokes every token issued from a code that was presented a second time (RFC 6749 4.1.2)
+    async fn revoke_replayed_code(
+        &self,
+        audit: &AuditContext,
+        client: &OAuthClient,
+        oauth_code: &OAuthCode,
+    ) {
+        let revoked = match self
+            .oauth_repo
+            .delete_tokens_by_code(&oauth_code.code)
+            .await
+        {
+            Ok(revoked) => revoked,
+            Err(e) => {
+                error!("Failed to revoke tokens of a replayed code: {}", e);
+                return;
+            }
+        };
+        warn!(
+            "Authorization code replayed for client {}, revoked {} token(s)",
+            oauth_code.client_id, revoked
+        );
+        self.audit
+            .record_event(
+                &audit.via_client(&client.client_id, oauth_code.user_id),
+                "oauth_code_replayed",
+                AuditEntity::OAuthClient(client.id),
+                serde_json::json!({ "revoked_tokens": revoked }),
+            )
+            .await;
+    }
+
+    /// Token endpoint (RFC 6749 3.2). The grant type must be allowed for the client, and the
+    /// issued scope never exceeds what the client record allows.
+    pub async fn token(
+        &self,
+        audit: &AuditContext,
+        req: OAuthTokenRequest,
+        basic_credentials: Option<(String, String)>,
+    ) -> Result<OAuthTokenResponse, OAuthError> {
+        let client = self
+            .authenticate_client(
+                req.client_id.as_deref(),
+                req.client_secret.as_deref(),
+                basic_credentials,
+            )
+            .await?;
+
+        if !SUPPORTED_GRANT_TYPES.contains(&req.grant_type.as_str()) {
+            return Err(OAuthError::new(
+                "unsupported_grant_type",
+                format!("Grant type '{}' is not supported", req.grant_type),
+            ));
+        }
+        if !client_allows_grant(&client, &req.grant_type) {
+            return Err(OAuthError::new(
+                "unauthorized_client",
+                format!("The client may not use the {} grant", req.grant_type),
+            ));
+        }
+
+        match req.grant_type.as_str() {
+            "authorization_code" => self.exchange_code(audit, &client, &req).await,
+            "client_credentials" => {
+                // 사용자 없는 토큰이므로 OIDC scope는 제외
+                let allowed: Vec<&str> = scope_list(client.scope.as_deref())
+                    .into_iter()
+                    .filter(|s| !OIDC_SCOPES.contains(s))
+                    .collect();
+                let granted: Vec<&str> = match non_empty_scope(&req) {
+                    Some(requested) => {
+                        let granted: Vec<&str> = scope_list(Some(requested))
+                            .into_iter()
+                            .filter(|s| allowed.contains(s))
+                            .collect();
+                        if granted.is_empty() {
+                            return Err(OAuthError::new(
+                                "invalid_scope",
+                                "None of the requested scopes are allowed for this client",
+                            ));
+                        }
+                        granted
+                    }
+                    None => allowed,
                 };
+
+                let token = new_token(
+                    &client.client_id,
+                    None,
+                    None,
+                    join_scopes(&granted),
+                    None,
+                    false,
+                );
                 self.oauth_repo.save_token(&token).await?;
-                Ok(OAuthTokenResponse {
-                    access_token,
-                    token_type: "bearer".to_string(),
-                    expires_in: 3600,
-                    refresh_token: None,
-                    scope: token.scope,
-                })
+                Ok(token_response(token, None))
             }
             "refresh_token" => {
-                // 1. 리프레시 토큰 검증 및 액세스 토큰 재발급
-                let refresh_token = req
-                    .refresh_token
-                    .as_ref()
-                    .ok_or_else(|| anyhow::anyhow!("missing_refresh_token"))?;
+                let refresh_token = req.refresh_token.as_deref().ok_or_else(|| {
+                    OAuthError::new("invalid_request", "refresh_token is required")
+                })?;
                 let old_token = self
                     .oauth_repo
                     .find_refresh_token(refresh_token)
                     .await
-                    .ok_or_else(|| anyhow::anyhow!("invalid_refresh_token"))?;
-                use chrono::{Duration, Utc};
-                use rand::Rng;
-                let access_token = format!("at_{:x}", rand::thread_rng().gen::<u128>());
-                let expires_at = Utc::now() + Duration::hours(1);
-                let token = OAuthToken {
-                    access_token: access_token.clone(),
-                    refresh_token: Some(refresh_token.clone()),
-                    client_id: old_token.client_id.clone(),
-                    user_id: old_token.user_id,
-                    scope: old_token.scope.clone(),
-                    expires_at,
-                    created_at: Utc::now(),
+                    .ok_or_else(|| {
+                        OAuthError::new("invalid_grant", "Refresh token is invalid or expired")
+                    })?;
+                if old_token.client_id != client.client_id {
+                    warn!(
+                        "Client {} presented a refresh token of client {}",
+                        client.client_id, old_token.client_id
+                    );
+                    return Err(OAuthError::new(
+                        "invalid_grant",
+                        "Refresh token is invalid or expired",
+                    ));
+                }
+                // 강제 로그아웃·잠금·비활성화된 사용자의 리프레시 토큰은 더 이상 사용할 수 없음
+                if !self.owner_accepts(&old_token).await? {
+                    return Err(OAuthError::new(
+                        "invalid_grant",
+                        "Refresh token has been revoked",
+                    ));
+                }
+
+                // 원래 승인된 scope 중 현재 클라이언트에 허용된 것만 유지, 요청 시 축소만 가능 (RFC 6749 6)
+                let allowed = scope_list(client.scope.as_deref());
+                let original: Vec<&str> = scope_list(old_token.scope.as_deref())
+                    .into_iter()
+                    .filter(|s| allowed.contains(s))
+                    .collect();
+                let granted = match non_empty_scope(&req) {
+                    Some(requested) => {
+                        let requested = scope_list(Some(requested));
+                        if let Some(extra) = requested.iter().find(|s| !original.contains(s)) {
+                            return Err(OAuthError::new(
+                                "invalid_scope",
+                                format!("Scope '{}' exceeds the original grant", extra),
+                            ));
+                        }
+                        requested
+                    }
+                    None => original,
                 };
-                self.oauth_repo.save_token(&token).await?;
-                Ok(OAuthTokenResponse {
-                    access_token,
-                    token_type: "bearer".to_string(),
-                    expires_in: 3600,
-                    refresh_token: Some(refresh_token.clone()),
-                    scope: old_token.scope,
-                })
+
+                // 리프레시 토큰은 1회용: 새 토큰으로 교체하고 기존 토큰은 폐기
+                let token = new_token(
+                    &client.client_id,
+                    old_token.user_id,
+                    old_token.token_version,
+                    join_scopes(&granted),
+                    // 코드 재사용 시 재발급된 토큰까지 함께 폐기되도록 유지
+                    old_token.authorization_code.clone(),
+                    true,
+                );
+                if !self
+                    .oauth_repo
+                    .rotate_refresh_token(refresh_token, &client.client_id, &token)
+                    .await?
+                {
+                    return Err(OAuthError::new(
+                        "invalid_grant",
+                        "Refresh token was already used",
+                    ));
+                }
+                Ok(token_response(token, None))
+            }
+            _ => unreachable!("grant type checked against SUPPORTED_GRANT_TYPES"),
+        }
+    }
+
+    /// Whether the token's resource owner still accepts it: active, not locked, and not logged
+    /// out since it was issued. `client_credentials` tokens have no owner and always pass.
+    async fn owner_accepts(&self, token: &OAuthToken) -> Result<bool, AppError> {
+        let Some(user_id) = token.user_id else {
+            return Ok(true);
+        };
+        let account = self.auth_repo.find_account_state(user_id).await?;
+        Ok(account
+            .zip(token.token_version)
+            .is_some_and(|(account, version)| account.accepts(version)))
+    }
+
+    /// Authorization code grant (RFC 6749 4.1.3)
+    async fn exchange_code(
+        &self,
+        audit: &AuditContext,
+        client: &OAuthClient,
+        req: &OAuthTokenRequest,
+    ) -> Result<OAuthTokenResponse, OAuthError> {
+        // 1. 코드 검증 (클라이언트, 재사용, 만료)
+        let code = req
+            .code
+            .as_deref()
+            .ok_or_else(|| OAuthError::new("invalid_request", "code is required"))?;
+        let invalid_code = || OAuthError::new("invalid_grant", "Authorization code is invalid");
+        let oauth_code = self
+            .oauth_repo
+            .find_code(code)
+            .await
+            .ok_or_else(invalid_code)?;
+        if oauth_code.client_id != client.client_id {
+            return Err(invalid_code());
+        }
+        if oauth_code.used_at.is_some() {
+            self.revoke_replayed_code(audit, client, &oauth_code).await;
+            return Err(OAuthError::new(
+                "invalid_grant",
+                "Authorization code was already used",
+            ));
+        }
+        if oauth_code.expires_at <= chrono::Utc::now() {
+            return Err(OAuthError::new(
+                "invalid_grant",
+                "Authorization code expired",
+            ));
+        }
+        // 동시에 같은 코드로 요청한 경우 먼저 표시한 쪽만 통과
+        if !self.oauth_repo.mark_code_used(code).await? {
+            self.revoke_replayed_code(audit, client, &oauth_code).await;
+            return Err(OAuthError::new(
+                "invalid_grant",
+                "Authorization code was already used",
+            ));
+        }
+
+        // 2. redirect_uri는 authorize 요청과 동일해야 함
+        if req.redirect_uri.as_deref() != Some(oauth_code.redirect_uri.as_str()) {
+            return Err(OAuthError::new("invalid_grant", "redirect_uri mismatch"));
+        }
+
+        // 3. PKCE code_verifier 검증
+        if let Some(ref challenge) = oauth_code.code_challenge {
+            let verifier = req
+                .code_verifier
+                .as_deref()
+                .ok_or_else(|| OAuthError::new("invalid_grant", "code_verifier is required"))?;
+            let method = oauth_code
+                .code_challenge_method
+                .as_deref()
+                .unwrap_or("plain");
+            if !verify_pkce(challenge, method, verifier) {
+                return Err(OAuthError::new("invalid_grant", "code_verifier mismatch"));
+            }
+        }
+
+        // 4. 승인한 사용자의 현재 토큰 버전으로 발급 (강제 로그아웃 시 함께 폐기)
+        let token_version = match oauth_code.user_id {
+            Some(user_id) => {
+                let account = self
+                    .auth_repo
+                    .find_account_state(user_id)
+                    .await?
+                    .filter(|account| account.accepts(account.token_version))
+                    .ok_or_else(|| {
+                        OAuthError::new("invalid_grant", "The approving user is disabled")
+                    })?;
+                Some(account.token_version)
+            }
+            None => None,
+        };
+
+        // openid scope가 승인된 경우 ID 토큰 함께 발급
+        let id_token = if has_scope(oauth_code.scope.as_deref(), OPENID_SCOPE) {
+            Some(self.issue_id_token(&oauth_code).await?)
+        } else {
+            None
+        };
+        // refresh_token grant가 허용된 클라이언트에만 리프레시 토큰 발급
+        let token = new_token(
+            &client.client_id,
+            oauth_code.user_id,
+            token_version,
+            oauth_code.scope.clone(),
+            Some(oauth_code.code.clone()),
+            client_allows_grant(client, "refresh_token"),
+        );
+        self.oauth_repo.save_token(&token).await?;
+        Ok(token_response(token, id_token))
+    }
+}
+
+/// Creates a random access token, with a refresh token if `with_refresh`
+fn new_token(
+    client_id: &str,
+    user_id: Option<i64>,
+    token_version: Option<i64>,
+    scope: Option<String>,
+    authorization_code: Option<String>,
+    with_refresh: bool,
+) -> OAuthToken {
+    use chrono::{Duration, Utc};
+    use rand::Rng;
+    let now = Utc::now();
+    OAuthToken {
+        access_token: format!(
+            "{}{:x}",
+            ACCESS_TOKEN_PREFIX,
+            rand::thread_rng().gen::<u128>()
+        ),
+        refresh_token: with_refresh.then(|| format!("rt_{:x}", rand::thread_rng().gen::<u128>())),
+        client_id: client_id.to_string(),
+        user_id,
+        scope,
+        expires_at: now + Duration::seconds(ACCESS_TOKEN_TTL_SECS),
+        created_at: now,
+        authorization_code,
+        refresh_expires_at: with_refresh.then(|| now + Duration::days(REFRESH_TOKEN_TTL_DAYS)),
+        token_version,
+    }
+}
+
+fn token_response(token: OAuthToken, id_token: Option<String>) -> OAuthTokenResponse {
+    OAuthTokenResponse {
+        access_token: token.access_token,
+        token_type: "bearer".to_string(),
+        expires_in: ACCESS_TOKEN_TTL_SECS,
+        refresh_token: token.refresh_token,
+        scope: token.scope,
+        id_token,
+    }
+}
+
+/// The `scope` parameter of a token request, if present and not blank
+fn non_empty_scope(req: &OAuthTokenRequest) -> Option<&str> {
+    req.scope.as_deref().filter(|s| !s.trim().is_empty())
+}
+
+/// Splits a space-separated scope string, dropping duplicates
+fn scope_list(scope: Option<&str>) -> Vec<&str> {
+    let mut scopes: Vec<&str> = Vec::new();
+    for s in scope.unwrap_or_default().split_whitespace() {
+        if !scopes.contains(&s) {
+            scopes.push(s);
+        }
+    }
+    scopes
+}
+
+fn join_scopes(scopes: &[&str]) -> Option<String> {
+    (!scopes.is_empty()).then(|| scopes.join(" "))
+}
+
+fn has_scope(scope: Option<&str>, name: &str) -> bool {
+    scope
+        .unwrap_or_default()
+        .split_whitespace()
+        .any(|s| s == name)
+}
+
+fn client_allows_grant(client: &OAuthClient, grant_type: &str) -> bool {
+    client
+        .grant_types
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .any(|g| g.trim() == grant_type)
+}
+
+/// code_challenge / code_verifier: 43-128 characters of [A-Z] / [a-z] / [0-9] / "-" / "." / "_" / "~"
+fn is_valid_pkce_value(value: &str) -> bool {
+    (43..=128).contains(&value.len())
+        && value
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~'))
+}
+
+/// Checks a code_verifier against the stored code_challenge (RFC 7636 4.6)
+fn verify_pkce(challenge: &str, method: &str, verifier: &str) -> bool {
+    if !is_valid_pkce_value(verifier) {
+        return false;
+    }
+    let computed = match method {
+        "S256" => URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes())),
+        "plain" => verifier.to_string(),
+        _ => return false,
+    };
+    hash_opaque_token(&computed) == hash_opaque_token(challenge)
+}
+
+/// Builds `redirect_uri?error=...&error_description=...&state=...`
+fn authorization_error_url(
+    redirect_uri: &str,
+    error: &str,
+    description: &str,
+    state: Option<&str>,
+) -> String {
+    match Url::parse(redirect_uri) {
+        Ok(mut url) => {
+            {
+                let mut query = url.query_pairs_mut();
+                query.append_pair("error", error);
+                query.append_pair("error_description", description);
+                if let Some(state) = state {
+                    query.append_pair("state", state);
+                }
             }
-            _ => return Err(anyhow::anyhow!("unsupported grant_type")),
+            url.into()
         }
+        Err(_) => redirect_uri.to_string(),
+    }
+}
+
+/// Human readable description of a scope for the consent screen
+pub fn describe_scope(scope: &str) -> &'static str {
+    match scope {
+        "read" => "관리자 데이터 조회",
+        "write" => "관리자 데이터 생성 및 변경",
+        "openid" => "로그인 (사용자 식별자 제공)",
+        "profile" => "사용자 이름",
+        "email" => "이메일 주소",
+        _ => "애플리케이션 정의 권한",
     }
 }
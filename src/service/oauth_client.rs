@@ -0,0 +1,348 @@
+use crate::{
+    config::auth::{audit_context::AuditContext, authn_user::AuthnUser},
+    errors::AppError,
+    model::{
+        dto::oauth_client::{
+            CreateOAuthClientRequest, OAuthClientResponse, OAuthClientSecretResponse,
+            RotateOAuthClientSecretRequest, UpdateOAuthClientRequest,
+        },
+        entity::oauth_client::OAuthClient,
+    },
+    repository::oauth::OAuthRepository,
+    service::audit::{AuditEntity, AuditService},
+    util::token_util,
+};
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+use url::Url;
+use validator::Validate;
+
+// OAuth 클라이언트 등록/수정/삭제 및 비밀값 교체
+
+pub const SUPPORTED_GRANT_TYPES: [&str; 3] =
+    ["authorization_code", "client_credentials", "refresh_token"];
+const DEFAULT_SECRET_OVERLAP_SECS: i64 = 86400;
+
+pub struct OAuthClientService {
+    oauth_repo: OAuthRepository,
+    audit: Arc<AuditService>,
+}
+
+/// Audited fields of a client. Unlike the entity this includes the secret hashes, so
+/// rotations show up in the diff (redacted).
+#[derive(Debug, Serialize)]
+struct OAuthClientAuditState {
+    client_id: String,
+    name: Option<String>,
+    redirect_uris: String,
+    scope: Option<String>,
+    grant_types: Option<String>,
+    permissions: String,
+    client_secret_hash: Option<String>,
+    previous_secret_hash: Option<String>,
+    previous_secret_expires_at: Option<NaiveDateTime>,
+}
+
+impl From<OAuthClient> for OAuthClientAuditState {
+    fn from(client: OAuthClient) -> Self {
+        Self {
+            client_id: client.client_id,
+            name: client.name,
+            redirect_uris: client.redirect_uris,
+            scope: client.scope,
+            grant_types: client.grant_types,
+            permissions: client.permissions,
+            client_secret_hash: client.client_secret_hash,
+            previous_secret_hash: client.previous_secret_hash,
+            previous_secret_expires_at: client.previous_secret_expires_at,
+        }
+    }
+}
+
+impl OAuthClientService {
+    pub fn new(oauth_repo: OAuthRepository, audit: Arc<AuditService>) -> Self {
+        Self { oauth_repo, audit }
+    }
+
+    pub async fn get_clients(&self) -> Result<Vec<OAuthClientResponse>, AppError> {
+        let clients = self.oauth_repo.find_all_clients().await?;
+        Ok(clients.into_iter().map(OAuthClientResponse::from).collect())
+    }
+
+    pub async fn get_client(&self, id: i64) -> Result<OAuthClientResponse, AppError> {
+        Ok(self.oauth_repo.find_client_by_id(id).await?.into())
+    }
+
+    /// Registers a client and returns its secret. Only the hash is stored, so this is
+    /// the only time the secret can be shown.
+    pub async fn create_client(
+        &self,
+        ctx: &AuditContext,
+        req: CreateOAuthClientRequest,
+        actor: &AuthnUser,
+    ) -> Result<OAuthClientSecretResponse, AppError> {
+        req.validate()?;
+
+        let client_id = match req.client_id.as_deref().map(str::trim) {
+            Some(client_id) if !client_id.is_empty() => {
+                if !client_id
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.'))
+                {
+                    return Err(AppError::BadRequest(
+                        "Client ID may only contain letters, digits, '-', '_' and '.'".to_string(),
+                    ));
+                }
+                client_id.to_string()
+            }
+            _ => uuid::Uuid::new_v4().simple().to_string(),
+        };
+        if self.oauth_repo.find_client(&client_id).await.is_some() {
+            return Err(AppError::Conflict(
+                "OAuth client with this client ID already exists".to_string(),
+            ));
+        }
+
+        let grant_types = normalize_grant_types(&req.grant_types)?;
+        let redirect_uris = normalize_redirect_uris(&req.redirect_uris, &grant_types)?;
+        let scope = normalize_scopes(&req.scopes)?;
+        let permissions = normalize_permissions(&req.permissions, &[], actor)?;
+        let name = req.name.trim();
+
+        let client_secret = token_util::generate_opaque_token();
+        let id = self
+            .oauth_repo
+            .create_client(
+                &client_id,
+                name,
+                &redirect_uris,
+                &scope,
+                &grant_types,
+                &permissions,
+                &token_util::hash_opaque_token(&client_secret),
+            )
+            .await?;
+
+        let after = self.audit_state(id).await?;
+        self.audit
+            .record_created(
+                ctx,
+                "oauth_client_created",
+                AuditEntity::OAuthClient(id),
+                &after,
+            )
+            .await;
+
+        Ok(OAuthClientSecretResponse {
+            client: self.get_client(id).await?,
+            client_secret,
+        })
+    }
+
+    pub async fn update_client(
+        &self,
+        ctx: &AuditContext,
+        id: i64,
+        req: UpdateOAuthClientRequest,
+        actor: &AuthnUser,
+    ) -> Result<OAuthClientResponse, AppError> {
+        req.validate()?;
+        let client = self.oauth_repo.find_client_by_id(id).await?;
+        let current = OAuthClientResponse::from(client.clone());
+
+        let name = req.name.as_deref().map(str::trim).unwrap_or(&current.name);
+        let grant_types =
+            normalize_grant_types(req.grant_types.as_ref().unwrap_or(&current.grant_types))?;
+        let redirect_uris = normalize_redirect_uris(
+            req.redirect_uris.as_ref().unwrap_or(&current.redirect_uris),
+            &grant_types,
+        )?;
+        let scope = normalize_scopes(req.scopes.as_ref().unwrap_or(&current.scopes))?;
+        let permissions = normalize_permissions(
+            req.permissions.as_ref().unwrap_or(&current.permissions),
+            &current.permissions,
+            actor,
+        )?;
+
+        self.oauth_repo
+            .update_client(id, name, &redirect_uris, &scope, &grant_types, &permissions)
+            .await?;
+
+        let after = self.audit_state(id).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "oauth_client_updated",
+                AuditEntity::OAuthClient(id),
+                &OAuthClientAuditState::from(client),
+                &after,
+            )
+            .await;
+
+        self.get_client(id).await
+    }
+
+    pub async fn delete_client(&self, ctx: &AuditContext, id: i64) -> Result<(), AppError> {
+        let client = self.oauth_repo.find_client_by_id(id).await?;
+        self.oauth_repo.delete_client(id).await?;
+        self.audit
+            .record_deleted(
+                ctx,
+                "oauth_client_deleted",
+                AuditEntity::OAuthClient(id),
+                &OAuthClientAuditState::from(client),
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Issues a new secret. The current secret stays valid for `overlap_secs`
+    /// (default one day, 0 revokes it immediately).
+    pub async fn rotate_secret(
+        &self,
+        ctx: &AuditContext,
+        id: i64,
+        req: RotateOAuthClientSecretRequest,
+    ) -> Result<OAuthClientSecretResponse, AppError> {
+        req.validate()?;
+        let client = self.oauth_repo.find_client_by_id(id).await?;
+
+        let overlap_secs = req.overlap_secs.unwrap_or(DEFAULT_SECRET_OVERLAP_SECS);
+        // 유예 중인 이전 비밀값을 조용히 버리지 않도록, 겹치는 교체는 즉시 만료(0)만 허용
+        let pending_previous = client
+            .previous_secret_expires_at
+            .filter(|expires_at| *expires_at > Utc::now().naive_utc());
+        if let (true, Some(expires_at)) = (overlap_secs > 0, pending_previous) {
+            return Err(AppError::Conflict(format!(
+                "The previous secret is still accepted until {}. Wait until then, or rotate with overlap_secs=0 to revoke both secrets",
+                expires_at.and_utc().to_rfc3339()
+            )));
+        }
+        // 비밀값이 없는 클라이언트는 유예할 해시가 없음
+        let previous_expires_at = (overlap_secs > 0 && client.client_secret_hash.is_some())
+            .then(|| Utc::now() + Duration::seconds(overlap_secs));
+
+        let client_secret = token_util::generate_opaque_token();
+        self.oauth_repo
+            .rotate_client_secret(
+                id,
+                &token_util::hash_opaque_token(&client_secret),
+                previous_expires_at,
+            )
+            .await?;
+
+        let after = self.audit_state(id).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "oauth_client_secret_rotated",
+                AuditEntity::OAuthClient(id),
+                &OAuthClientAuditState::from(client),
+                &after,
+            )
+            .await;
+
+        Ok(OAuthClientSecretResponse {
+            client: self.get_client(id).await?,
+            client_secret,
+        })
+    }
+
+    async fn audit_state(&self, id: i64) -> Result<OAuthClientAuditState, AppError> {
+        Ok(self.oauth_repo.find_client_by_id(id).await?.into())
+    }
+}
+
+/// Validates the grant types and joins them comma-separated, as stored in `oauth_client`
+fn normalize_grant_types(grant_types: &[String]) -> Result<String, AppError> {
+    let mut normalized: Vec<&str> = Vec::new();
+    for grant_type in grant_types.iter().map(|g| g.trim()) {
+        if !SUPPORTED_GRANT_TYPES.contains(&grant_type) {
+            return Err(AppError::BadRequest(format!(
+                "Unsupported grant type '{}'",
+                grant_type
+            )));
+        }
+        if !normalized.contains(&grant_type) {
+            normalized.push(grant_type);
+        }
+    }
+    if normalized.is_empty() {
+        return Err(AppError::BadRequest(
+            "At least one grant type is required".to_string(),
+        ));
+    }
+    Ok(normalized.join(","))
+}
+
+/// Redirect URIs must be absolute and without a fragment (RFC 6749 3.1.2).
+/// The authorization code grant needs at least one.
+fn normalize_redirect_uris(
+    redirect_uris: &[String],
+    grant_types: &str,
+) -> Result<String, AppError> {
+    let mut normalized: Vec<&str> = Vec::new();
+    for uri in redirect_uris
+        .iter()
+        .map(|u| u.trim())
+        .filter(|u| !u.is_empty())
+    {
+        let parsed = Url::parse(uri)
+            .map_err(|_| AppError::BadRequest(format!("Invalid redirect URI '{}'", uri)))?;
+        if parsed.fragment().is_some() || uri.contains(char::is_whitespace) {
+            return Err(AppError::BadRequest(format!(
+                "Redirect URI '{}' must not contain a fragment or whitespace",
+                uri
+            )));
+        }
+        if !normalized.contains(&uri) {
+            normalized.push(uri);
+        }
+    }
+    if normalized.is_empty() && grant_types.split(',').any(|g| g == "authorization_code") {
+        return Err(AppError::BadRequest(
+            "The authorization_code grant requires at least one redirect URI".to_string(),
+        ));
+    }
+    Ok(normalized.join(" "))
+}
+
+/// Validates the permission codes and joins them space-separated. Codes the client does not
+/// already hold (`current`) must be held by the actor, so nobody can grant more than they have.
+fn normalize_permissions(
+    permissions: &[String],
+    current: &[String],
+    actor: &AuthnUser,
+) -> Result<String, AppError> {
+    let mut normalized: Vec<&str> = Vec::new();
+    for code in permissions.iter().flat_map(|p| p.split_whitespace()) {
+        if !actor.permissions.contains(code) && !current.iter().any(|c| c == code) {
+            return Err(AppError::BadRequest(format!(
+                "Permission '{}' is not granted to you",
+                code
+            )));
+        }
+        if !normalized.contains(&code) {
+            normalized.push(code);
+        }
+    }
+    Ok(normalized.join(" "))
+}
+
+/// Scope tokens may use any printable ASCII except space, '"' and '\' (RFC 6749 3.3)
+fn normalize_scopes(scopes: &[String]) -> Result<String, AppError> {
+    let mut normalized: Vec<&str> = Vec::new();
+    for scope in scopes.iter().flat_map(|s| s.split_whitespace()) {
+        if !scope
+            .bytes()
+            .all(|b| (0x21..=0x7e).contains(&b) && b != b'"' && b != b'\\')
+        {
+            return Err(AppError::BadRequest(format!("Invalid scope '{}'", scope)));
+        }
+        if !normalized.contains(&scope) {
+            normalized.push(scope);
+        }
+    }
+    Ok(normalized.join(" "))
+}
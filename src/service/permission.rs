@@ -1,25 +1,54 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
     model::dto::{
         common::ListQueryParams, permission::CreatePermissionRequest,
         permission::PermissionResponse, permission::UpdatePermissionRequest,
     },
     repository::permission::PermissionRepository,
+    service::audit::{AuditEntity, AuditService},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, RwLock},
 };
 use validator::Validate;
 
 pub struct PermissionService {
     permission_repo: PermissionRepository,
+    audit: Arc<AuditService>,
+    /// Permission codes cached per user type, filled on first lookup
+    permission_cache: RwLock<HashMap<i64, Arc<HashSet<String>>>>,
 }
 
 impl PermissionService {
-    pub fn new(permission_repo: PermissionRepository) -> Self {
-        Self { permission_repo }
+    pub fn new(permission_repo: PermissionRepository, audit: Arc<AuditService>) -> Self {
+        Self {
+            permission_repo,
+            audit,
+            permission_cache: RwLock::new(HashMap::new()),
+        }
     }
 
-    pub async fn create_permission(&self, req: CreatePermissionRequest) -> Result<i64, AppError> {
+    pub async fn create_permission(
+        &self,
+        req: CreatePermissionRequest,
+        ctx: &AuditContext,
+    ) -> Result<i64, AppError> {
         req.validate()?;
-        self.permission_repo.create(req).await
+        let id = self.permission_repo.create(req).await?;
+
+        let created = self.permission_repo.find_by_id(id as i32).await?;
+        self.audit
+            .record_created(
+                ctx,
+                "permission_created",
+                AuditEntity::Permission(id),
+                &created,
+            )
+            .await;
+
+        Ok(id)
     }
 
     pub async fn get_permissions(
@@ -29,21 +58,59 @@ impl PermissionService {
         self.permission_repo.find_all(query).await
     }
 
+    /// Returns every permission ordered by category and code
+    pub async fn get_permissions_by_category(&self) -> Result<Vec<PermissionResponse>, AppError> {
+        self.permission_repo.find_all_by_category().await
+    }
+
     pub async fn get_permission_by_id(&self, id: i32) -> Result<PermissionResponse, AppError> {
         self.permission_repo.find_by_id(id).await
     }
 
-    /// Check if a user has a specific permission
-    pub async fn has_permission(
+    /// Returns the permission codes granted to a user type, served from the cache when possible
+    pub async fn get_permission_set(
         &self,
-        _user_id: i64,
-        _permission_name: &str,
-    ) -> Result<bool, AppError> {
-        // In a real application, you would check the user's roles/permissions
-        // For now, we'll just check if the permission exists in the database
-        // and return true for demonstration purposes
-        // TODO: Implement proper permission checking logic
-        Ok(true)
+        user_type_id: i64,
+    ) -> Result<Arc<HashSet<String>>, AppError> {
+        if let Some(permissions) = self
+            .permission_cache
+            .read()
+            .expect("permission cache lock poisoned")
+            .get(&user_type_id)
+        {
+            return Ok(permissions.clone());
+        }
+
+        let permissions: Arc<HashSet<String>> = Arc::new(
+            self.permission_repo
+                .find_codes_by_user_type(user_type_id)
+                .await?
+                .into_iter()
+                .collect(),
+        );
+
+        self.permission_cache
+            .write()
+            .expect("permission cache lock poisoned")
+            .insert(user_type_id, permissions.clone());
+
+        Ok(permissions)
+    }
+
+    /// Drops the cached permission set of a user type so the next lookup hits the database
+    pub fn invalidate_user_type(&self, user_type_id: i64) {
+        self.permission_cache
+            .write()
+            .expect("permission cache lock poisoned")
+            .remove(&user_type_id);
+    }
+
+    /// Drops every cached permission set
+    pub fn clear_permission_cache(&self) {
+        self.permission_cache
+            .write()
+            .expect("permission cache lock poisoned")
+            .clear();
     }
 
     pub async fn count_permissions(&self) -> Result<i64, AppError> {
@@ -54,6 +121,7 @@ impl PermissionService {
         &self,
         id: i32,
         req: UpdatePermissionRequest,
+        ctx: &AuditContext,
     ) -> Result<PermissionResponse, AppError> {
         if let Some(name) = &req.name {
             if name.trim().is_empty() {
@@ -67,6 +135,21 @@ impl PermissionService {
             }
         }
 
-        self.permission_repo.update(id, req).await
+        let before = self.permission_repo.find_by_id(id).await?;
+        let response = self.permission_repo.update(id, req).await?;
+        self.audit
+            .record_updated(
+                ctx,
+                "permission_updated",
+                AuditEntity::Permission(id as i64),
+                &before,
+                &response,
+            )
+            .await;
+
+        // A renamed code changes every user type that holds it
+        self.clear_permission_cache();
+
+        Ok(response)
     }
 }
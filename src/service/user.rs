@@ -1,22 +1,30 @@
 use crate::model::dto::user::UpdateUserRequest;
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
     model::dto::{common::ListQueryParams, user::CreateUserRequest, user::UserResponse},
     repository::user::UserRepository,
+    service::audit::{AuditEntity, AuditService},
     util::password_util,
 };
+use std::sync::Arc;
 use validator::Validate;
 
 pub struct UserService {
     user_repo: UserRepository,
+    audit: Arc<AuditService>,
 }
 
 impl UserService {
-    pub fn new(user_repo: UserRepository) -> Self {
-        Self { user_repo }
+    pub fn new(user_repo: UserRepository, audit: Arc<AuditService>) -> Self {
+        Self { user_repo, audit }
     }
 
-    pub async fn create_user(&self, req: CreateUserRequest) -> Result<i64, AppError> {
+    pub async fn create_user(
+        &self,
+        req: CreateUserRequest,
+        ctx: &AuditContext,
+    ) -> Result<i64, AppError> {
         req.validate()?;
         let password_hash = password_util::hash_password(&req.password).await?;
         let is_active = req._is_active.unwrap_or(true);
@@ -37,6 +45,11 @@ impl UserService {
             )
             .await?;
 
+        let created = self.user_repo.find_by_id(user_id).await?;
+        self.audit
+            .record_created(ctx, "user_created", AuditEntity::User(user_id), &created)
+            .await;
+
         Ok(user_id)
     }
 
@@ -59,7 +72,14 @@ impl UserService {
         self.user_repo.count_active_users().await
     }
 
-    pub async fn update_user(&self, id: i64, req: UpdateUserRequest) -> Result<(), AppError> {
+    pub async fn update_user(
+        &self,
+        id: i64,
+        req: UpdateUserRequest,
+        ctx: &AuditContext,
+    ) -> Result<(), AppError> {
+        let before = self.user_repo.find_by_id(id).await?;
+
         // Check if email is being updated and if it's already in use
         if let Some(email) = &req.email {
             if self.user_repo.is_email_in_use(email, Some(id)).await? {
@@ -78,11 +98,20 @@ impl UserService {
             )
             .await?;
 
+        let after = self.user_repo.find_by_id(id).await?;
+        self.audit
+            .record_updated(ctx, "user_updated", AuditEntity::User(id), &before, &after)
+            .await;
+
         Ok(())
     }
 
-    pub async fn delete_user(&self, id: i64) -> Result<(), AppError> {
+    pub async fn delete_user(&self, id: i64, ctx: &AuditContext) -> Result<(), AppError> {
+        let before = self.user_repo.find_by_id(id).await?;
         self.user_repo.delete_user(id).await?;
+        self.audit
+            .record_deleted(ctx, "user_deleted", AuditEntity::User(id), &before)
+            .await;
         Ok(())
     }
 }
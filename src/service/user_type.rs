@@ -1,29 +1,48 @@
 use crate::{
+    config::auth::audit_context::AuditContext,
     errors::AppError,
     model::dto::{
         common::ListQueryParams,
+        permission::PermissionResponse,
         user_type::{CreateUserTypeRequest, UpdateUserTypeRequest, UserTypeResponse},
     },
-    repository::user_type::UserTypeRepository,
+    repository::{permission::PermissionRepository, user_type::UserTypeRepository},
+    service::{
+        audit::{AuditEntity, AuditService},
+        permission::PermissionService,
+    },
 };
-use std::sync::Arc;
+use serde_json::json;
+use std::{collections::BTreeSet, sync::Arc};
 use validator::Validate;
 
 #[derive(Clone)]
 pub struct UserTypeService {
     user_type_repo: Arc<UserTypeRepository>,
+    permission_repo: PermissionRepository,
+    permission_service: Arc<PermissionService>,
+    audit: Arc<AuditService>,
 }
 
 impl UserTypeService {
-    pub fn new(user_type_repo: UserTypeRepository) -> Self {
+    pub fn new(
+        user_type_repo: UserTypeRepository,
+        permission_repo: PermissionRepository,
+        permission_service: Arc<PermissionService>,
+        audit: Arc<AuditService>,
+    ) -> Self {
         Self {
             user_type_repo: Arc::new(user_type_repo),
+            permission_repo,
+            permission_service,
+            audit,
         }
     }
 
     pub async fn create_user_type(
         &self,
         req: CreateUserTypeRequest,
+        ctx: &AuditContext,
     ) -> Result<UserTypeResponse, AppError> {
         req.validate()?;
 
@@ -35,7 +54,16 @@ impl UserTypeService {
             ));
         }
 
-        self.user_type_repo.create(req).await
+        let response = self.user_type_repo.create(req).await?;
+        self.audit
+            .record_created(
+                ctx,
+                "user_type_created",
+                AuditEntity::UserType(response.id),
+                &response,
+            )
+            .await;
+        Ok(response)
     }
 
     pub async fn get_user_type_array(
@@ -53,6 +81,7 @@ impl UserTypeService {
         &self,
         type_id: i64,
         req: UpdateUserTypeRequest,
+        ctx: &AuditContext,
     ) -> Result<UserTypeResponse, AppError> {
         req.validate()?;
 
@@ -67,10 +96,163 @@ impl UserTypeService {
             }
         }
 
-        self.user_type_repo.update(type_id, req).await
+        let before = self.user_type_repo.find_by_id(type_id).await?;
+        let response = self.user_type_repo.update(type_id, req).await?;
+        self.permission_service.invalidate_user_type(type_id);
+        self.audit
+            .record_updated(
+                ctx,
+                "user_type_updated",
+                AuditEntity::UserType(type_id),
+                &before,
+                &response,
+            )
+            .await;
+        Ok(response)
+    }
+
+    pub async fn delete_user_type(&self, type_id: i64, ctx: &AuditContext) -> Result<(), AppError> {
+        let before = self.user_type_repo.find_by_id(type_id).await?;
+        self.user_type_repo.delete(type_id).await?;
+        self.permission_service.invalidate_user_type(type_id);
+        self.audit
+            .record_deleted(
+                ctx,
+                "user_type_deleted",
+                AuditEntity::UserType(type_id),
+                &before,
+            )
+            .await;
+        Ok(())
+    }
+
+    /// Returns the permissions assigned to a user type
+    pub async fn get_permissions(&self, type_id: i64) -> Result<Vec<PermissionResponse>, AppError> {
+        self.user_type_repo.find_by_id(type_id).await?;
+        self.permission_repo.find_by_user_type(type_id).await
+    }
+
+    /// Grants a single permission to a user type and records the change in the audit trail
+    pub async fn grant_permission(
+        &self,
+        type_id: i64,
+        permission_id: i64,
+        ctx: &AuditContext,
+    ) -> Result<Vec<PermissionResponse>, AppError> {
+        self.user_type_repo.find_by_id(type_id).await?;
+        self.find_permission(permission_id).await?;
+
+        let before = self.permission_codes(type_id).await?;
+        if self.permission_repo.grant(type_id, permission_id).await? {
+            self.permission_service.invalidate_user_type(type_id);
+            let after = self.permission_codes(type_id).await?;
+            self.audit
+                .record_updated(
+                    ctx,
+                    "user_type_permission_granted",
+                    AuditEntity::UserType(type_id),
+                    &json!({ "permissions": before }),
+                    &json!({ "permissions": after }),
+                )
+                .await;
+        }
+
+        self.permission_repo.find_by_user_type(type_id).await
+    }
+
+    /// Revokes a single permission from a user type and records the change in the audit trail
+    pub async fn revoke_permission(
+        &self,
+        type_id: i64,
+        permission_id: i64,
+        ctx: &AuditContext,
+    ) -> Result<Vec<PermissionResponse>, AppError> {
+        self.user_type_repo.find_by_id(type_id).await?;
+        self.find_permission(permission_id).await?;
+
+        let before = self.permission_codes(type_id).await?;
+        if self.permission_repo.revoke(type_id, permission_id).await? {
+            self.permission_service.invalidate_user_type(type_id);
+            let after = self.permission_codes(type_id).await?;
+            self.audit
+                .record_updated(
+                    ctx,
+                    "user_type_permission_revoked",
+                    AuditEntity::UserType(type_id),
+                    &json!({ "permissions": before }),
+                    &json!({ "permissions": after }),
+                )
+                .await;
+        }
+
+        self.permission_repo.find_by_user_type(type_id).await
+    }
+
+    /// Replaces every permission of a user type and records the before/after codes in the audit trail
+    pub async fn replace_permissions(
+        &self,
+        type_id: i64,
+        permission_ids: Vec<i64>,
+        ctx: &AuditContext,
+    ) -> Result<Vec<PermissionResponse>, AppError> {
+        self.user_type_repo.find_by_id(type_id).await?;
+
+        let all_permissions = self.permission_repo.find_all_by_category().await?;
+        let requested: BTreeSet<i64> = permission_ids.into_iter().collect();
+        let unknown: Vec<i64> = requested
+            .iter()
+            .copied()
+            .filter(|id| !all_permissions.iter().any(|p| p.id == Some(*id)))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(AppError::BadRequest(format!(
+                "Unknown permission IDs: {:?}",
+                unknown
+            )));
+        }
+
+        let current = self.permission_codes(type_id).await?;
+        let next: BTreeSet<String> = all_permissions
+            .into_iter()
+            .filter(|p| p.id.is_some_and(|id| requested.contains(&id)))
+            .map(|p| p.code)
+            .collect();
+
+        if current != next {
+            let ids: Vec<i64> = requested.iter().copied().collect();
+            self.permission_repo
+                .replace_for_user_type(type_id, &ids)
+                .await?;
+            self.permission_service.invalidate_user_type(type_id);
+            self.audit
+                .record_updated(
+                    ctx,
+                    "user_type_permissions_replaced",
+                    AuditEntity::UserType(type_id),
+                    &json!({ "permissions": current }),
+                    &json!({ "permissions": next }),
+                )
+                .await;
+        }
+
+        self.permission_repo.find_by_user_type(type_id).await
+    }
+
+    async fn find_permission(&self, permission_id: i64) -> Result<PermissionResponse, AppError> {
+        let id = i32::try_from(permission_id).map_err(|_| {
+            AppError::NotFound(format!("Permission with ID {} not found", permission_id))
+        })?;
+        self.permission_repo.find_by_id(id).await
     }
 
-    pub async fn delete_user_type(&self, type_id: i64) -> Result<(), AppError> {
-        self.user_type_repo.delete(type_id).await
+    /// Sorted permission codes of a user type, the shape permission changes are audited in
+    async fn permission_codes(&self, type_id: i64) -> Result<BTreeSet<String>, AppError> {
+        Ok(self
+            .permission_repo
+            .find_by_user_type(type_id)
+            .await?
+            .into_iter()
+            .map(|p| p.code)
+            .collect())
     }
 }
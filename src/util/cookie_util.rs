@@ -35,14 +35,14 @@ pub fn create_refresh_token_cookie(config: &AppConfig, value: &str) -> Cookie<'s
     )
 }
 
-pub fn expire_access_token_cookie(config: &AppConfig) -> Cookie {
+pub fn expire_access_token_cookie(config: &AppConfig) -> Cookie<'_> {
     expire_cookie(
         config.cookie.access_token_name.as_str(),
         config.cookie.secure,
     )
 }
 
-pub fn expire_refresh_token_cookie(config: &AppConfig) -> Cookie {
+pub fn expire_refresh_token_cookie(config: &AppConfig) -> Cookie<'_> {
     expire_cookie(
         config.cookie.refresh_token_name.as_str(),
         config.cookie.secure,
@@ -81,7 +81,7 @@ fn create_cookie(name: String, value: String, max_age: i64, is_secure: bool) ->
         .build()
 }
 
-fn expire_cookie(name: &str, secure_cookies: bool) -> Cookie {
+fn expire_cookie(name: &str, secure_cookies: bool) -> Cookie<'_> {
     CookieBuilder::new(name, "")
         .http_only(true)
         .secure(secure_cookies)
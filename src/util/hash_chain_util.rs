@@ -0,0 +1,34 @@
+use ring::hmac;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+/// `prev_hash` of the first row of a chain that has no predecessor or checkpoint
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hashes history rows and checkpoints: SHA-256, or HMAC-SHA256 when a key is configured so
+/// that someone with database access alone cannot recompute a consistent chain.
+#[derive(Clone)]
+pub struct ChainHasher {
+    key: Option<hmac::Key>,
+}
+
+impl ChainHasher {
+    pub fn new(key: Option<&[u8]>) -> Self {
+        Self {
+            key: key.map(|key| hmac::Key::new(hmac::HMAC_SHA256, key)),
+        }
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.key.is_some()
+    }
+
+    /// Hex digest of the canonical JSON encoding of `content`
+    pub fn hash(&self, content: &Value) -> String {
+        let bytes = content.to_string();
+        match &self.key {
+            Some(key) => hex::encode(hmac::sign(key, bytes.as_bytes())),
+            None => hex::encode(Sha256::digest(bytes.as_bytes())),
+        }
+    }
+}
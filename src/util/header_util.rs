@@ -1,4 +1,6 @@
 use axum::http::{header, HeaderMap};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use percent_encoding::percent_decode_str;
 
 /// Extracts the token from the Authorization header, removing the 'Bearer ' prefix if present.
 /// Returns None if the header is missing or malformed.
@@ -13,11 +15,84 @@ pub fn extract_token_from_header(headers: &HeaderMap) -> Option<&str> {
     }
 }
 
+/// Extracts a personal API key from the `X-API-Key` header
+pub fn extract_api_key(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get("x-api-key")
+        .and_then(|header| header.to_str().ok())
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+}
+
+/// Extracts `(client_id, client_secret)` from an HTTP Basic Authorization header.
+/// Both parts are form-urlencoded before base64 encoding (RFC 6749 2.3.1).
+pub fn extract_basic_credentials(headers: &HeaderMap) -> Option<(String, String)> {
+    let encoded = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())?
+        .strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (id, secret) = decoded.split_once(':')?;
+    let form_decode = |value: &str| {
+        percent_decode_str(&value.replace('+', " "))
+            .decode_utf8()
+            .ok()
+            .map(|v| v.into_owned())
+    };
+    Some((form_decode(id)?, form_decode(secret)?))
+}
+
 /// Checks if the request is an API request by examining the Accept header.
 /// Returns true if the Accept header contains 'application/json'.
 pub fn is_api_request(headers: &HeaderMap) -> bool {
     headers
         .get(header::ACCEPT)
         .and_then(|v| v.to_str().ok())
-        .map_or(false, |accept| accept.contains("application/json"))
+        .is_some_and(|accept| accept.contains("application/json"))
+}
+
+/// Builds a short, human readable device label (e.g. "Chrome on Windows") from a User-Agent string.
+pub fn describe_user_agent(user_agent: &str) -> String {
+    let browser = if user_agent.contains("Edg/") {
+        "Edge"
+    } else if user_agent.contains("OPR/") || user_agent.contains("Opera") {
+        "Opera"
+    } else if user_agent.contains("Firefox/") {
+        "Firefox"
+    } else if user_agent.contains("Chrome/") || user_agent.contains("CriOS/") {
+        "Chrome"
+    } else if user_agent.contains("Safari/") {
+        "Safari"
+    } else if user_agent.starts_with("curl/") {
+        "curl"
+    } else {
+        "Unknown browser"
+    };
+
+    let os = if user_agent.contains("Windows") {
+        "Windows"
+    } else if user_agent.contains("iPhone") || user_agent.contains("iPad") {
+        "iOS"
+    } else if user_agent.contains("Mac OS X") || user_agent.contains("Macintosh") {
+        "macOS"
+    } else if user_agent.contains("Android") {
+        "Android"
+    } else if user_agent.contains("Linux") {
+        "Linux"
+    } else {
+        return browser.to_string();
+    };
+
+    format!("{} on {}", browser, os)
+}
+
+/// Returns `url` if it is a path on this site, so it can be used as a post-login redirect
+/// without turning the login page into an open redirect.
+pub fn sanitize_redirect_url(url: &str) -> Option<String> {
+    let url = url.trim();
+    if url.starts_with('/') && !url.starts_with("//") && !url.contains('\\') {
+        Some(url.to_string())
+    } else {
+        None
+    }
 }
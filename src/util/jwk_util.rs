@@ -0,0 +1,207 @@
+use anyhow::{anyhow, bail, Context, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use jsonwebtoken::{
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, EllipticCurveKeyParameters,
+        EllipticCurveKeyType, Jwk, JwkSet, KeyAlgorithm, OctetKeyPairParameters, OctetKeyPairType,
+        PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+    Algorithm, DecodingKey, EncodingKey,
+};
+use ring::{
+    rand::SystemRandom,
+    signature::{EcdsaKeyPair, Ed25519KeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING},
+};
+use rsa::{
+    pkcs1::{DecodeRsaPrivateKey, DecodeRsaPublicKey},
+    pkcs8::{DecodePrivateKey, DecodePublicKey},
+    traits::PublicKeyParts,
+    RsaPrivateKey, RsaPublicKey,
+};
+use sha2::{Digest, Sha256};
+
+/// SubjectPublicKeyInfo DER prefix of a P-256 key, followed by the 65 byte uncompressed point
+const P256_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x59, 0x30, 0x13, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x08, 0x2a,
+    0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07, 0x03, 0x42, 0x00,
+];
+/// SubjectPublicKeyInfo DER prefix of an Ed25519 key, followed by the 32 byte public key
+const ED25519_SPKI_PREFIX: &[u8] = &[
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// Key used to sign or verify JWTs (RS256, ES256 or EdDSA). The public half is published
+/// as a JWK. Keys loaded from a public key PEM can only verify.
+#[derive(Clone)]
+pub struct JwtKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub encoding_key: Option<EncodingKey>,
+    pub decoding_key: DecodingKey,
+    pub jwk: Jwk,
+}
+
+/// Public key material, raw big-endian bytes
+enum PublicKey {
+    Rsa { n: Vec<u8>, e: Vec<u8> },
+    Ec { x: Vec<u8>, y: Vec<u8> },
+    Ed { x: Vec<u8> },
+}
+
+impl JwtKey {
+    /// Loads a PEM encoded key: an RSA (PKCS#8 or PKCS#1), P-256 or Ed25519 (PKCS#8) private
+    /// key, or the matching public key. Without an explicit `kid` the key id is derived from
+    /// the public key, so it stays stable across restarts and rotations.
+    pub fn from_pem_file(path: &str, kid: Option<String>) -> Result<Self> {
+        let pem = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read key `{}`", path))?;
+        Self::from_pem(&pem, kid).with_context(|| format!("Invalid key `{}`", path))
+    }
+
+    pub fn from_pem(pem: &str, kid: Option<String>) -> Result<Self> {
+        let block = pem::parse(pem).map_err(|e| anyhow!("Invalid PEM: {}", e))?;
+        let der = block.contents();
+        let (public_key, private) = match block.tag() {
+            "RSA PRIVATE KEY" => (rsa_public_key(&RsaPrivateKey::from_pkcs1_der(der)?), true),
+            "RSA PUBLIC KEY" => (rsa_public_key(&RsaPublicKey::from_pkcs1_der(der)?), false),
+            "PRIVATE KEY" => (private_key_from_pkcs8(der)?, true),
+            "PUBLIC KEY" => (public_key_from_spki(der)?, false),
+            tag => bail!("Unsupported PEM block `{}`", tag),
+        };
+
+        let (algorithm, key_algorithm) = match public_key {
+            PublicKey::Rsa { .. } => (Algorithm::RS256, KeyAlgorithm::RS256),
+            PublicKey::Ec { .. } => (Algorithm::ES256, KeyAlgorithm::ES256),
+            PublicKey::Ed { .. } => (Algorithm::EdDSA, KeyAlgorithm::EdDSA),
+        };
+        let encoding_key = match (&public_key, private) {
+            (_, false) => None,
+            (PublicKey::Rsa { .. }, true) => Some(EncodingKey::from_rsa_pem(pem.as_bytes())?),
+            (PublicKey::Ec { .. }, true) => Some(EncodingKey::from_ec_pem(pem.as_bytes())?),
+            (PublicKey::Ed { .. }, true) => Some(EncodingKey::from_ed_pem(pem.as_bytes())?),
+        };
+
+        let (fingerprint, decoding_key, parameters) = match public_key {
+            PublicKey::Rsa { n, e } => {
+                let fingerprint = Sha256::digest(&n);
+                let n = URL_SAFE_NO_PAD.encode(n);
+                let e = URL_SAFE_NO_PAD.encode(e);
+                (
+                    fingerprint,
+                    DecodingKey::from_rsa_components(&n, &e)?,
+                    AlgorithmParameters::RSA(RSAKeyParameters {
+                        key_type: RSAKeyType::RSA,
+                        n,
+                        e,
+                    }),
+                )
+            }
+            PublicKey::Ec { x, y } => {
+                let fingerprint = Sha256::digest([x.as_slice(), y.as_slice()].concat());
+                let x = URL_SAFE_NO_PAD.encode(x);
+                let y = URL_SAFE_NO_PAD.encode(y);
+                (
+                    fingerprint,
+                    DecodingKey::from_ec_components(&x, &y)?,
+                    AlgorithmParameters::EllipticCurve(EllipticCurveKeyParameters {
+                        key_type: EllipticCurveKeyType::EC,
+                        curve: EllipticCurve::P256,
+                        x,
+                        y,
+                    }),
+                )
+            }
+            PublicKey::Ed { x } => {
+                let fingerprint = Sha256::digest(&x);
+                let x = URL_SAFE_NO_PAD.encode(x);
+                (
+                    fingerprint,
+                    DecodingKey::from_ed_components(&x)?,
+                    AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                        key_type: OctetKeyPairType::OctetKeyPair,
+                        curve: EllipticCurve::Ed25519,
+                        x,
+                    }),
+                )
+            }
+        };
+        let kid = kid.unwrap_or_else(|| hex::encode(&fingerprint[..8]));
+
+        Ok(Self {
+            algorithm,
+            encoding_key,
+            decoding_key,
+            jwk: Jwk {
+                common: CommonParameters {
+                    public_key_use: Some(PublicKeyUse::Signature),
+                    key_algorithm: Some(key_algorithm),
+                    key_id: Some(kid.clone()),
+                    ..Default::default()
+                },
+                algorithm: parameters,
+            },
+            kid,
+        })
+    }
+
+    pub fn can_sign(&self) -> bool {
+        self.encoding_key.is_some()
+    }
+}
+
+fn rsa_public_key(key: &impl PublicKeyParts) -> PublicKey {
+    PublicKey::Rsa {
+        n: key.n().to_bytes_be(),
+        e: key.e().to_bytes_be(),
+    }
+}
+
+fn private_key_from_pkcs8(der: &[u8]) -> Result<PublicKey> {
+    if let Ok(key) = RsaPrivateKey::from_pkcs8_der(der) {
+        return Ok(rsa_public_key(&key));
+    }
+    if let Ok(key) =
+        EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, der, &SystemRandom::new())
+    {
+        return ec_point(key.public_key().as_ref());
+    }
+    if let Ok(key) = Ed25519KeyPair::from_pkcs8_maybe_unchecked(der) {
+        return Ok(PublicKey::Ed {
+            x: key.public_key().as_ref().to_vec(),
+        });
+    }
+    bail!("Expected an RSA, P-256 or Ed25519 private key")
+}
+
+fn public_key_from_spki(der: &[u8]) -> Result<PublicKey> {
+    if let Ok(key) = RsaPublicKey::from_public_key_der(der) {
+        return Ok(rsa_public_key(&key));
+    }
+    if let Some(point) = der.strip_prefix(P256_SPKI_PREFIX) {
+        return ec_point(point);
+    }
+    if let Some(x) = der.strip_prefix(ED25519_SPKI_PREFIX) {
+        if x.len() == 32 {
+            return Ok(PublicKey::Ed { x: x.to_vec() });
+        }
+    }
+    bail!("Expected an RSA, P-256 or Ed25519 public key")
+}
+
+/// Splits an uncompressed SEC1 point (0x04 || x || y) into its coordinates
+fn ec_point(point: &[u8]) -> Result<PublicKey> {
+    match point {
+        [0x04, coordinates @ ..] if coordinates.len() == 64 => Ok(PublicKey::Ec {
+            x: coordinates[..32].to_vec(),
+            y: coordinates[32..].to_vec(),
+        }),
+        _ => bail!("Expected an uncompressed P-256 point"),
+    }
+}
+
+/// JWKS document (RFC 7517) publishing the public keys
+pub fn jwk_set(keys: &[&JwtKey]) -> JwkSet {
+    JwkSet {
+        keys: keys.iter().map(|key| key.jwk.clone()).collect(),
+    }
+}
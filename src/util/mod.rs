@@ -1,5 +1,7 @@
 pub mod cookie_util;
+pub mod hash_chain_util;
 pub mod header_util;
+pub mod jwk_util;
 pub mod password_util;
 pub mod template_util;
 pub mod token_util;
@@ -1,80 +1,328 @@
 use crate::{
-    config::{env_loader, env_loader::AppConfig},
+    config::{
+        env_loader,
+        env_loader::{AppConfig, Token},
+    },
     errors::AppError,
+    util::jwk_util::JwtKey,
 };
 use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{
+    decode, decode_header, encode, errors::ErrorKind, Algorithm, DecodingKey, EncodingKey, Header,
+    Validation,
+};
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
     pub sub: i64, // Subject (user id)
     pub username: String,
-    pub role: String,
-    pub exp: usize, // Expiration time (timestamp)
+    pub role: String, // user_type.code
+    /// Permission codes of the user type at issue time (access tokens only), sorted
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub perms: Vec<String>,
+    pub typ: TokenType,
+    pub iss: String,
+    pub aud: String,
+    pub iat: usize,  // Issued at (timestamp)
+    pub nbf: usize,  // Not valid before (timestamp)
+    pub exp: usize,  // Expiration time (timestamp)
+    pub jti: String, // Token id, key of the revocation denylist
+    #[serde(default)]
+    pub ver: i64, // admin_user.token_version at issue time
+}
+
+/// Purpose of a user token, so a refresh token can never be used as an access token
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    Access,
+    Refresh,
 }
 
-/// Token 생성
-fn create_token(
+/// Claims of the short-lived token handed out between the password and TOTP login steps.
+/// It carries no role, so it can never pass as an access token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MfaClaims {
+    pub sub: i64,
+    pub exp: usize,
+    pub jti: String,
+    pub ver: i64,
+    pub purpose: String,
+}
+
+const MFA_TOKEN_PURPOSE: &str = "mfa_pending";
+
+/// Claims of the anti-CSRF token embedded in the OAuth consent form. It ties the form
+/// to the user who was shown it and to the client asking for access.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsentClaims {
+    pub sub: i64,
+    pub client_id: String,
+    pub exp: usize,
+    pub jti: String,
+    pub purpose: String,
+}
+
+const CONSENT_TOKEN_PURPOSE: &str = "oauth_consent";
+const CONSENT_TOKEN_EXP: i64 = 600;
+
+/// OpenID Connect ID token claims (OIDC Core 2). Profile claims are only set for the
+/// matching scopes.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IdTokenClaims {
+    pub iss: String,
+    pub sub: String,
+    pub aud: String,
+    pub exp: usize,
+    pub iat: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferred_username: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+/// Token Claims 생성 (권한 목록은 비어 있음)
+fn new_claims(
+    typ: TokenType,
     user_id: i64,
     username: &str,
-    user_type_id: &str,
+    user_type_code: &str,
+    token_version: i64,
     duration: Duration,
-    secret: &[u8],
-) -> Result<String, AppError> {
-    let expiration = Utc::now()
+    token_config: &Token,
+) -> Claims {
+    let now = Utc::now();
+    let expiration = now
         .checked_add_signed(duration)
         .expect("valid timestamp")
         .timestamp();
-    let claims = Claims {
+    Claims {
         sub: user_id,
         username: username.to_string(),
-        role: user_type_id.to_string(),
+        role: user_type_code.to_string(),
+        perms: Vec::new(),
+        typ,
+        iss: token_config.issuer.clone(),
+        aud: token_config.audience.clone(),
+        iat: now.timestamp() as usize,
+        nbf: now.timestamp() as usize,
         exp: expiration as usize,
-    };
-    let header = Header::new(Algorithm::HS256);
-    encode(&header, &claims, &EncodingKey::from_secret(secret)).map_err(AppError::JwtError)
+        jti: uuid::Uuid::new_v4().to_string(),
+        ver: token_version,
+    }
+}
+
+/// Token 서명: 비대칭 키가 있으면 그 키로, 없으면 HS256 비밀키로
+fn create_token(claims: &Claims, token_config: &Token) -> Result<String, AppError> {
+    match &token_config.signing_key {
+        Some(key) => sign_with_key(key, claims),
+        None => encode(
+            &Header::new(Algorithm::HS256),
+            claims,
+            &EncodingKey::from_secret(token_config.secret.as_ref()),
+        )
+        .map_err(AppError::JwtError),
+    }
 }
 
 /// Token 검증 및 Claims 반환
-pub fn validate_token(token: &str) -> Result<Claims, AppError> {
-    let config = env_loader::get_config();
-    let decoding_key = DecodingKey::from_secret(config.token.secret.as_ref());
-    let validation = Validation::new(Algorithm::HS256);
+/// kid가 있으면 현재 서명 키 또는 교체 중인 이전 키로, 없으면 HS256 비밀키로 검증한다.
+/// 비대칭 서명 키가 설정된 뒤에는 HS256 토큰을 받지 않는다.
+/// 서명과 exp/nbf(leeway 적용), iss, aud, typ를 확인한다. 폐기 목록(jti)은 호출 측에서 확인한다.
+pub fn validate_token(token: &str, typ: TokenType) -> Result<Claims, AppError> {
+    let config = &env_loader::get_config().token;
+    let header = decode_header(token).map_err(AppError::JwtError)?;
+
+    let secret_key;
+    let (decoding_key, algorithm) = match header.kid.as_deref() {
+        Some(kid) => {
+            let key = config
+                .verification_key(kid)
+                .ok_or_else(|| AppError::JwtError(ErrorKind::InvalidSignature.into()))?;
+            (&key.decoding_key, key.algorithm)
+        }
+        None if config.signing_key.is_none() => {
+            secret_key = DecodingKey::from_secret(config.secret.as_ref());
+            (&secret_key, Algorithm::HS256)
+        }
+        None => return Err(AppError::JwtError(ErrorKind::InvalidSignature.into())),
+    };
 
-    decode::<Claims>(token, &decoding_key, &validation)
+    let mut validation = Validation::new(algorithm);
+    validation.leeway = config.leeway;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[&config.issuer]);
+    validation.set_audience(&[&config.audience]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud"]);
+
+    let claims = decode::<Claims>(token, decoding_key, &validation)
         .map(|token_data| token_data.claims)
-        .map_err(AppError::JwtError)
+        .map_err(AppError::JwtError)?;
+
+    if claims.typ != typ {
+        return Err(AppError::JwtError(ErrorKind::InvalidToken.into()));
+    }
+    Ok(claims)
 }
 
 /// accessToken 생성: 1시간
+/// 권한 목록을 함께 담아 다른 서비스가 DB 조회 없이 인가할 수 있게 한다
 pub fn generate_access_token(
     config: &AppConfig,
     user_id: i64,
-    user_type_name: &str,
+    user_type_code: &str,
+    permissions: Vec<String>,
     username: &str,
+    token_version: i64,
 ) -> Result<String, AppError> {
-    create_token(
+    let mut claims = new_claims(
+        TokenType::Access,
         user_id,
         username,
-        user_type_name,
+        user_type_code,
+        token_version,
         Duration::seconds(config.token.access_exp),
-        config.token.secret.as_ref(),
-    )
+        &config.token,
+    );
+    claims.perms = permissions;
+    create_token(&claims, &config.token)
 }
 
 /// refreshToken 생성: 1주일
 pub fn generate_refresh_token(
     config: &AppConfig,
     user_id: i64,
-    user_type_name: &str,
+    user_type_code: &str,
     username: &str,
+    token_version: i64,
 ) -> Result<String, AppError> {
-    create_token(
+    let claims = new_claims(
+        TokenType::Refresh,
         user_id,
         username,
-        user_type_name,
+        user_type_code,
+        token_version,
         Duration::seconds(config.token.refresh_exp),
-        config.token.secret.as_ref(),
+        &config.token,
+    );
+    create_token(&claims, &config.token)
+}
+
+/// 비밀번호 확인 후 2단계 인증 대기 토큰 생성
+pub fn generate_mfa_token(
+    config: &AppConfig,
+    user_id: i64,
+    token_version: i64,
+) -> Result<String, AppError> {
+    let expiration = Utc::now()
+        .checked_add_signed(Duration::seconds(config.token.mfa_exp))
+        .expect("valid timestamp")
+        .timestamp();
+    let claims = MfaClaims {
+        sub: user_id,
+        exp: expiration as usize,
+        jti: uuid::Uuid::new_v4().to_string(),
+        ver: token_version,
+        purpose: MFA_TOKEN_PURPOSE.to_string(),
+    };
+    encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(config.token.secret.as_ref()),
+    )
+    .map_err(AppError::JwtError)
+}
+
+/// 2단계 인증 대기 토큰 검증
+pub fn validate_mfa_token(config: &AppConfig, token: &str) -> Result<MfaClaims, AppError> {
+    let decoding_key = DecodingKey::from_secret(config.token.secret.as_ref());
+    let claims = decode::<MfaClaims>(token, &decoding_key, &Validation::new(Algorithm::HS256))
+        .map(|token_data| token_data.claims)
+        .map_err(|_| {
+            AppError::Unauthorized("MFA session expired, please log in again".to_string())
+        })?;
+
+    if claims.purpose != MFA_TOKEN_PURPOSE {
+        return Err(AppError::Unauthorized("Invalid MFA token".to_string()));
+    }
+    Ok(claims)
+}
+
+/// OAuth 동의 화면 폼에 넣을 토큰 생성
+pub fn generate_consent_token(
+    config: &AppConfig,
+    user_id: i64,
+    client_id: &str,
+) -> Result<String, AppError> {
+    let expiration = Utc::now()
+        .checked_add_signed(Duration::seconds(CONSENT_TOKEN_EXP))
+        .expect("valid timestamp")
+        .timestamp();
+    let claims = ConsentClaims {
+        sub: user_id,
+        client_id: client_id.to_string(),
+        exp: expiration as usize,
+        jti: uuid::Uuid::new_v4().to_string(),
+        purpose: CONSENT_TOKEN_PURPOSE.to_string(),
+    };
+    encode(
+        &Header::new(Algorithm::HS256),
+        &claims,
+        &EncodingKey::from_secret(config.token.secret.as_ref()),
     )
+    .map_err(AppError::JwtError)
+}
+
+/// OAuth 동의 폼 토큰 검증: 같은 사용자, 같은 클라이언트에 대해 발급된 토큰인지 확인
+pub fn validate_consent_token(
+    config: &AppConfig,
+    token: &str,
+    user_id: i64,
+    client_id: &str,
+) -> Result<(), AppError> {
+    let decoding_key = DecodingKey::from_secret(config.token.secret.as_ref());
+    let claims = decode::<ConsentClaims>(token, &decoding_key, &Validation::new(Algorithm::HS256))
+        .map(|token_data| token_data.claims)
+        .map_err(|_| AppError::BadRequest("Consent form expired, please try again".to_string()))?;
+
+    if claims.purpose != CONSENT_TOKEN_PURPOSE
+        || claims.sub != user_id
+        || claims.client_id != client_id
+    {
+        return Err(AppError::BadRequest("Invalid consent form".to_string()));
+    }
+    Ok(())
+}
+
+/// OpenID Connect ID 토큰 서명 (kid 포함)
+pub fn generate_id_token(key: &JwtKey, claims: &IdTokenClaims) -> Result<String, AppError> {
+    sign_with_key(key, claims)
+}
+
+/// 비대칭 키로 서명, 헤더에 kid를 넣어 검증 측이 JWKS에서 키를 고를 수 있게 한다
+fn sign_with_key<T: Serialize>(key: &JwtKey, claims: &T) -> Result<String, AppError> {
+    let encoding_key = key
+        .encoding_key
+        .as_ref()
+        .ok_or_else(|| AppError::JwtError(ErrorKind::InvalidKeyFormat.into()))?;
+    let mut header = Header::new(key.algorithm);
+    header.kid = Some(key.kid.clone());
+    encode(&header, claims, encoding_key).map_err(AppError::JwtError)
+}
+
+/// 외부로 전달되는 일회용 토큰 생성 (32바이트 난수, hex 인코딩)
+pub fn generate_opaque_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// DB 저장용 토큰 해시 (SHA-256, hex 인코딩)
+pub fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
 }
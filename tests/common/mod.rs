@@ -0,0 +1,111 @@
+//! Shared setup for the integration tests: the server binary running on a free port with its
+//! own database and files.
+
+use std::{
+    net::TcpListener,
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+/// A fresh directory for the database and files of one server
+pub fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("admin-server-{}-{}", name, uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).expect("create test directory");
+    dir
+}
+
+/// Database URL of the server started in `dir`
+#[allow(dead_code)] // only tests that prepare the database before starting the server
+pub fn database_url(dir: &std::path::Path) -> String {
+    format!("sqlite:{}", dir.join("test.db").display())
+}
+
+/// The server binary on a free port, using `dir` for its database. The process is killed and
+/// the directory removed when dropped.
+pub struct TestServer {
+    child: Child,
+    dir: PathBuf,
+    pub base_url: String,
+}
+
+impl TestServer {
+    /// Starts the server with `env` on top of the test defaults and waits until it answers
+    pub async fn start(dir: PathBuf, env: &[(&str, String)]) -> Self {
+        let port = TcpListener::bind("127.0.0.1:0")
+            .and_then(|listener| listener.local_addr())
+            .expect("free port")
+            .port();
+        let base_url = format!("http://127.0.0.1:{}", port);
+
+        let child = Command::new(env!("CARGO_BIN_EXE_admin-server"))
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .env("DATABASE_URL", database_url(&dir))
+            .env("SERVER_HOST", "127.0.0.1")
+            .env("SERVER_PORT", port.to_string())
+            .env("APP_BASE_URL", &base_url)
+            .env("TOKEN_SECRET", "integration-test-secret")
+            .env("LOG_FILE_ENABLE", "false")
+            .env("HISTORY_RETENTION_ENABLE", "false")
+            .env("HISTORY_ARCHIVE_PATH", dir.join("archive"))
+            .env("RUST_LOG", "warn")
+            .envs(env.iter().map(|(key, value)| (key, value)))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("start admin-server");
+
+        let server = Self {
+            child,
+            dir,
+            base_url,
+        };
+        server.wait_until_ready().await;
+        server
+    }
+
+    async fn wait_until_ready(&self) {
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while Instant::now() < deadline {
+            if reqwest::get(self.url("/.well-known/openid-configuration"))
+                .await
+                .is_ok()
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        panic!("admin-server did not start within 30 seconds");
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Signs in as the seeded administrator and returns the access token
+    pub async fn admin_token(&self, client: &reqwest::Client) -> String {
+        let body = client
+            .post(self.url("/api/auth/login"))
+            .form(&[("username", "admin"), ("password", "admin123")])
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        let login: serde_json::Value =
+            serde_json::from_str(&body).unwrap_or_else(|_| panic!("login response: {}", body));
+        login["access_token"]
+            .as_str()
+            .unwrap_or_else(|| panic!("access token in {}", body))
+            .to_string()
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.dir);
+    }
+}
@@ -0,0 +1,137 @@
+//! Runs history retention through `DELETE /api/history/cleanup` against a database holding
+//! expired rows, with and without per-action policies, and checks that the hash chain still
+//! verifies across the pruned gaps.
+
+mod common;
+
+use common::{database_url, test_dir, TestServer};
+use reqwest::StatusCode;
+use serde_json::Value;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use std::{path::Path, str::FromStr};
+
+/// Actions of the expired rows written before the server starts, in id order
+const EXPIRED_ACTIONS: [&str; 3] = ["login_failed", "user_deleted", "login_failed"];
+
+/// Creates the database with rows far past any retention period. They are written unsealed,
+/// like history from before the hash chain, and linked by the server on startup.
+async fn prepare_database(dir: &Path) {
+    let options = SqliteConnectOptions::from_str(&database_url(dir))
+        .unwrap()
+        .create_if_missing(true);
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect_with(options)
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+    for action in EXPIRED_ACTIONS {
+        sqlx::query(
+            "INSERT INTO history (action, details, created_at) VALUES (?, '{}', '2000-01-01 00:00:00')",
+        )
+        .bind(action)
+        .execute(&pool)
+        .await
+        .unwrap();
+    }
+    pool.close().await;
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .user_agent("history-retention-test")
+        .build()
+        .unwrap()
+}
+
+/// Runs cleanup keeping 30 days and returns the `data` of the response
+async fn cleanup(server: &TestServer, client: &reqwest::Client, token: &str) -> Value {
+    let response = client
+        .delete(server.url("/api/history/cleanup?days=30"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body: Value = response.json().await.unwrap();
+    body["data"].clone()
+}
+
+async fn assert_chain_intact(server: &TestServer, client: &reqwest::Client, token: &str) {
+    let body: Value = client
+        .get(server.url("/api/history/verify"))
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(body["data"]["valid"], true, "{}", body);
+    assert!(body["data"]["broken"].is_null(), "{}", body);
+}
+
+async fn count_history(
+    server: &TestServer,
+    client: &reqwest::Client,
+    token: &str,
+    action: &str,
+) -> i64 {
+    let body: Value = client
+        .get(server.url("/api/history"))
+        .query(&[("action", action)])
+        .bearer_auth(token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    body["pagination"]["total"]
+        .as_i64()
+        .unwrap_or_else(|| panic!("history total in {}", body))
+}
+
+#[tokio::test]
+async fn cleanup_without_policies_prunes_every_expired_row() {
+    let dir = test_dir("retention");
+    prepare_database(&dir).await;
+    let server = TestServer::start(dir, &[("HISTORY_RETENTION_POLICIES", String::new())]).await;
+    let client = http_client();
+    let token = server.admin_token(&client).await;
+
+    let data = cleanup(&server, &client, &token).await;
+    assert_eq!(data["deleted"], EXPIRED_ACTIONS.len());
+    assert_eq!(data["archived"], EXPIRED_ACTIONS.len());
+    assert_eq!(data["run"]["status"], "succeeded");
+    assert_eq!(
+        count_history(&server, &client, &token, "user_deleted").await,
+        0
+    );
+
+    assert_chain_intact(&server, &client, &token).await;
+}
+
+#[tokio::test]
+async fn cleanup_keeps_actions_with_their_own_policy() {
+    let dir = test_dir("retention");
+    prepare_database(&dir).await;
+    let server = TestServer::start(
+        dir,
+        &[("HISTORY_RETENTION_POLICIES", "user_deleted=0".to_string())],
+    )
+    .await;
+    let client = http_client();
+    let token = server.admin_token(&client).await;
+
+    // user_deleted은 영구 보관이므로 양옆의 login_failed만 각각 체크포인트를 남기고 삭제
+    let data = cleanup(&server, &client, &token).await;
+    assert_eq!(data["deleted"], 2);
+    assert_eq!(data["run"]["status"], "succeeded");
+    assert_eq!(
+        count_history(&server, &client, &token, "user_deleted").await,
+        1
+    );
+
+    assert_chain_intact(&server, &client, &token).await;
+}
@@ -0,0 +1,216 @@
+//! Drives the OpenID Connect authorization code flow against a running server, the way a
+//! relying party would: discovery, login, consent, token exchange with PKCE, ID token
+//! verification against the published JWKS, and userinfo.
+
+mod common;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use common::{test_dir, TestServer};
+use jsonwebtoken::{decode, decode_header, jwk::JwkSet, DecodingKey, Validation};
+use reqwest::{redirect::Policy, StatusCode};
+use ring::{rand::SystemRandom, signature::Ed25519KeyPair};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+const CLIENT_ID: &str = "test-client";
+const REDIRECT_URI: &str = "http://localhost:8080/callback";
+
+/// The server with its own OIDC signing key
+async fn start_server() -> TestServer {
+    let dir = test_dir("oidc");
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).expect("generate key");
+    let key_path = dir.join("oidc.pem");
+    std::fs::write(
+        &key_path,
+        pem::encode(&pem::Pem::new("PRIVATE KEY", pkcs8.as_ref().to_vec())),
+    )
+    .expect("write signing key");
+
+    TestServer::start(
+        dir,
+        &[("OIDC_SIGNING_KEY_PATH", key_path.display().to_string())],
+    )
+    .await
+}
+
+/// Reads the value of a hidden input from the consent page
+fn hidden_input(html: &str, name: &str) -> String {
+    let marker = format!("name=\"{}\" value=\"", name);
+    let start = html.find(&marker).expect("hidden input on the page") + marker.len();
+    let end = start + html[start..].find('"').expect("closing quote");
+    html[start..end].to_string()
+}
+
+fn query_param(url: &str, name: &str) -> Option<String> {
+    url::Url::parse(url)
+        .expect("redirect URL")
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.into_owned())
+}
+
+#[tokio::test]
+async fn authorization_code_flow_issues_a_verifiable_id_token() {
+    let server = start_server().await;
+    let client = reqwest::Client::builder()
+        .redirect(Policy::none())
+        .user_agent("oidc-flow-test")
+        .build()
+        .unwrap();
+
+    // Discovery
+    let discovery: Value = client
+        .get(server.url("/.well-known/openid-configuration"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(discovery["issuer"], server.base_url);
+    assert_eq!(
+        discovery["authorization_endpoint"],
+        server.url("/api/oauth/authorize")
+    );
+    assert_eq!(
+        discovery["id_token_signing_alg_values_supported"][0],
+        "EdDSA"
+    );
+    let endpoint = |name: &str| discovery[name].as_str().unwrap().to_string();
+
+    // The resource owner signs in
+    let access_token = &server.admin_token(&client).await;
+
+    // The sample client ships without a secret; the admin issues one
+    let clients: Vec<Value> = client
+        .get(server.url("/api/oauth-client"))
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id = clients
+        .iter()
+        .find(|c| c["client_id"] == CLIENT_ID)
+        .and_then(|c| c["id"].as_i64())
+        .expect("sample client");
+    let rotated: Value = client
+        .post(server.url(&format!("/api/oauth-client/{}/secret", id)))
+        .bearer_auth(access_token)
+        .json(&serde_json::json!({ "overlap_secs": 0 }))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let client_secret = rotated["client_secret"].as_str().expect("client secret");
+
+    // Authorization request with PKCE and a nonce
+    let code_verifier = URL_SAFE_NO_PAD.encode(uuid::Uuid::new_v4().as_bytes().repeat(2));
+    let code_challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()));
+    let nonce = uuid::Uuid::new_v4().to_string();
+    let authorize_params = [
+        ("response_type", "code"),
+        ("client_id", CLIENT_ID),
+        ("redirect_uri", REDIRECT_URI),
+        ("scope", "openid profile"),
+        ("state", "xyz"),
+        ("nonce", nonce.as_str()),
+        ("code_challenge", code_challenge.as_str()),
+        ("code_challenge_method", "S256"),
+    ];
+    let consent_page = client
+        .get(endpoint("authorization_endpoint"))
+        .query(&authorize_params)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(consent_page.status(), StatusCode::OK);
+    let consent_token = hidden_input(&consent_page.text().await.unwrap(), "consent_token");
+
+    // Consent
+    let mut consent_form = authorize_params.to_vec();
+    consent_form.push(("consent_token", consent_token.as_str()));
+    consent_form.push(("decision", "approve"));
+    let redirect = client
+        .post(endpoint("authorization_endpoint"))
+        .form(&consent_form)
+        .bearer_auth(access_token)
+        .send()
+        .await
+        .unwrap();
+    assert!(redirect.status().is_redirection());
+    let location = redirect.headers()["location"].to_str().unwrap().to_string();
+    assert!(location.starts_with(REDIRECT_URI));
+    assert_eq!(query_param(&location, "state").as_deref(), Some("xyz"));
+    let code = query_param(&location, "code").expect("authorization code");
+
+    // Token exchange
+    let token_form = [
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", REDIRECT_URI),
+        ("code_verifier", code_verifier.as_str()),
+    ];
+    let tokens: Value = client
+        .post(endpoint("token_endpoint"))
+        .basic_auth(CLIENT_ID, Some(client_secret))
+        .form(&token_form)
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let id_token = tokens["id_token"].as_str().expect("id token");
+
+    // ID token verification against the published keys
+    let jwks: JwkSet = client
+        .get(endpoint("jwks_uri"))
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    let header = decode_header(id_token).unwrap();
+    let jwk = jwks
+        .find(header.kid.as_deref().expect("kid"))
+        .expect("signing key in JWKS");
+    let mut validation = Validation::new(header.alg);
+    validation.set_audience(&[CLIENT_ID]);
+    validation.set_issuer(&[server.base_url.as_str()]);
+    let claims = decode::<Value>(id_token, &DecodingKey::from_jwk(jwk).unwrap(), &validation)
+        .expect("valid ID token")
+        .claims;
+    assert_eq!(claims["nonce"], nonce.as_str());
+    assert_eq!(claims["preferred_username"], "admin");
+
+    // Userinfo describes the same subject
+    let userinfo: Value = client
+        .get(endpoint("userinfo_endpoint"))
+        .bearer_auth(tokens["access_token"].as_str().unwrap())
+        .send()
+        .await
+        .unwrap()
+        .json()
+        .await
+        .unwrap();
+    assert_eq!(userinfo["sub"], claims["sub"]);
+
+    // The code is single-use
+    let replay = client
+        .post(endpoint("token_endpoint"))
+        .basic_auth(CLIENT_ID, Some(client_secret))
+        .form(&token_form)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(replay.status(), StatusCode::BAD_REQUEST);
+    let error: Value = replay.json().await.unwrap();
+    assert_eq!(error["error"], "invalid_grant");
+}